@@ -0,0 +1,140 @@
+//! Per-project change history. Every mutating command appends one JSON line to `activity.jsonl`
+//! in the project directory. The file is machine-local like agent transcripts: archives and
+//! duplicates leave it behind, and it is trimmed to the newest entries once it grows large.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::error::AppError;
+use crate::events::ChangeSource;
+
+pub(crate) const ACTIVITY_FILE: &str = "activity.jsonl";
+/// Entries returned when the caller gives no limit.
+pub(crate) const DEFAULT_ACTIVITY_LIMIT: usize = 100;
+/// Entries kept when the log is trimmed.
+const MAX_ENTRIES: usize = 1000;
+/// Log size that triggers trimming. Entries are around 100 bytes, so this is well past `MAX_ENTRIES`.
+const TRIM_BYTES: u64 = 512 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ActivityOperation {
+  Save,
+  CollabEdit,
+  ApplyProposal,
+  RevertProposal,
+  UpdateSiteUrl,
+  UpdateDetails,
+  UpdateTheme,
+  Rename,
+  Duplicate,
+  AddAsset,
+  Archive,
+  Trash,
+  Restore,
+}
+
+impl ActivityOperation {
+  /// The serialized name, for text output.
+  pub(crate) fn label(self) -> &'static str {
+    match self {
+      ActivityOperation::Save => "save",
+      ActivityOperation::CollabEdit => "collabEdit",
+      ActivityOperation::ApplyProposal => "applyProposal",
+      ActivityOperation::RevertProposal => "revertProposal",
+      ActivityOperation::UpdateSiteUrl => "updateSiteUrl",
+      ActivityOperation::UpdateDetails => "updateDetails",
+      ActivityOperation::UpdateTheme => "updateTheme",
+      ActivityOperation::Rename => "rename",
+      ActivityOperation::Duplicate => "duplicate",
+      ActivityOperation::AddAsset => "addAsset",
+      ActivityOperation::Archive => "archive",
+      ActivityOperation::Trash => "trash",
+      ActivityOperation::Restore => "restore",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ActivityEntry {
+  /// Matches the `updatedAt` the change wrote to `project.json`.
+  pub(crate) at: String,
+  pub(crate) operation: ActivityOperation,
+  /// Pages added, updated or removed; empty for project-level changes.
+  #[serde(default)]
+  pub(crate) pages: Vec<String>,
+  pub(crate) source: ChangeSource,
+}
+
+/// Appends an entry. The change itself has already been written, so failures are logged rather
+/// than returned.
+pub(crate) fn record(
+  project_dir: &Path,
+  at: &str,
+  operation: ActivityOperation,
+  pages: Vec<String>,
+  source: ChangeSource,
+) {
+  let entry = ActivityEntry {
+    at: at.to_string(),
+    operation,
+    pages,
+    source,
+  };
+  if let Err(err) = append(&project_dir.join(ACTIVITY_FILE), &entry) {
+    log::warn!("failed recording project activity: {}", err);
+  }
+}
+
+/// Newest entries first, at most `limit` of them. Lines that do not parse are skipped.
+pub(crate) fn read_activity(project_dir: &Path, limit: usize) -> Result<Vec<ActivityEntry>, AppError> {
+  let path = project_dir.join(ACTIVITY_FILE);
+  if !path.exists() {
+    return Ok(Vec::new());
+  }
+  let text = fs::read_to_string(&path).map_err(|err| AppError::fs("Failed reading", &path, err))?;
+  Ok(
+    text
+      .lines()
+      .rev()
+      .filter_map(|line| serde_json::from_str(line).ok())
+      .take(limit)
+      .collect(),
+  )
+}
+
+fn append(path: &Path, entry: &ActivityEntry) -> Result<(), AppError> {
+  let line = serde_json::to_string(entry).map_err(|err| AppError::internal(err.to_string()))?;
+  let mut file = OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(path)
+    .map_err(|err| AppError::fs("Failed opening", path, err))?;
+  writeln!(file, "{}", line).map_err(|err| AppError::fs("Failed writing", path, err))?;
+  let len = file
+    .metadata()
+    .map_err(|err| AppError::fs("Failed reading", path, err))?
+    .len();
+  if len > TRIM_BYTES {
+    trim(path)?;
+  }
+  Ok(())
+}
+
+/// Rewrites the log with only its newest `MAX_ENTRIES` lines.
+fn trim(path: &Path) -> Result<(), AppError> {
+  let text = fs::read_to_string(path).map_err(|err| AppError::fs("Failed reading", path, err))?;
+  let lines: Vec<&str> = text.lines().collect();
+  let kept = &lines[lines.len().saturating_sub(MAX_ENTRIES)..];
+  let mut trimmed = kept.join("\n");
+  trimmed.push('\n');
+  let temp = path.with_extension("jsonl.tmp");
+  fs::write(&temp, trimmed).map_err(|err| AppError::fs("Failed writing", &temp, err))?;
+  fs::rename(&temp, path).map_err(|err| AppError::fs("Failed replacing", path, err))
+}
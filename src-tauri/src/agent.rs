@@ -0,0 +1,684 @@
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::activity::ActivityOperation;
+use crate::error::{AppError, ErrorCode};
+use crate::events::ChangeSource;
+use crate::policy::{AgentOperation, MethodRequirement, PolicyDenial, PolicyGrant};
+use crate::validation::{lint_builder_doc, validate_builder_doc, IssueSeverity, KNOWN_BLOCK_TYPES};
+use crate::{
+  commit_revision, load_builder_doc, normalize_builder_doc, page_doc_path, read_json_file,
+  read_project_metadata, save_builder_doc, write_json_file, BlockDoc, BlockStyleDoc, BuilderProjectDoc, LiveServices, PageDoc,
+  PageSeoDoc, PROJECT_META_FILE, SITEMAP_FILE, SITE_FILE, THEME_FILE,
+};
+
+/// Bumped whenever a request or response shape changes incompatibly.
+pub(crate) const AGENT_PROTOCOL_VERSION: u32 = 1;
+const AGENT_CLIENT_ID: &str = "agent";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AgentRequest {
+  version: u32,
+  #[serde(default)]
+  id: serde_json::Value,
+  project_path: String,
+  method: String,
+  #[serde(default)]
+  params: serde_json::Value,
+  /// Policy session from `start_agent_session`; without one the request runs read-only.
+  #[serde(default)]
+  session_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentResponse {
+  version: u32,
+  id: serde_json::Value,
+  ok: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  result: Option<serde_json::Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  error: Option<AgentError>,
+}
+
+impl AgentResponse {
+  pub(crate) fn into_result(self) -> Result<serde_json::Value, AgentError> {
+    match (self.result, self.error) {
+      (Some(result), None) => Ok(result),
+      (_, Some(error)) => Err(error),
+      (None, None) => Ok(serde_json::Value::Null),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentError {
+  code: &'static str,
+  message: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  details: Option<serde_json::Value>,
+}
+
+impl AgentError {
+  fn new(code: &'static str, message: impl Into<String>) -> Self {
+    Self {
+      code,
+      message: message.into(),
+      details: None,
+    }
+  }
+
+  fn denied(denial: Box<PolicyDenial>) -> Self {
+    Self {
+      code: "policy_denied",
+      message: denial.message.clone(),
+      details: serde_json::to_value(&denial).ok(),
+    }
+  }
+
+  fn invalid_params(message: impl Into<String>) -> Self {
+    Self::new("invalid_params", message)
+  }
+
+  fn failed(message: impl Into<String>) -> Self {
+    Self::new("command_failed", message)
+  }
+}
+
+/// Keeps the agent protocol's own codes; the studio error's path/field travel in `details`.
+impl From<AppError> for AgentError {
+  fn from(err: AppError) -> Self {
+    let code = match err.code() {
+      ErrorCode::InvalidInput => "invalid_params",
+      ErrorCode::NotFound => "not_found",
+      ErrorCode::Conflict | ErrorCode::PreconditionFailed => "conflict",
+      _ => "command_failed",
+    };
+    Self {
+      code,
+      message: err.message().to_string(),
+      details: err.details().and_then(|details| serde_json::to_value(details).ok()),
+    }
+  }
+}
+
+/// Per-request state: the shared services plus the policy grant that writes are checked against.
+struct AgentContext<'a> {
+  services: &'a LiveServices,
+  grant: PolicyGrant,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageCreateParams {
+  title: String,
+  route: String,
+  #[serde(default)]
+  seo_title: Option<String>,
+  #[serde(default)]
+  seo_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageRefParams {
+  page_id: String,
+}
+
+/// Only the provided fields change.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageUpdateParams {
+  page_id: String,
+  #[serde(default)]
+  title: Option<String>,
+  #[serde(default)]
+  seo_title: Option<String>,
+  #[serde(default)]
+  seo_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockAddParams {
+  page_id: String,
+  block_type: String,
+  #[serde(default)]
+  props: Option<serde_json::Map<String, serde_json::Value>>,
+  /// Insert position; appends when omitted.
+  #[serde(default)]
+  index: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockReorderParams {
+  page_id: String,
+  block_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContentUpdateParams {
+  page_id: String,
+  block_id: String,
+  /// Merged into the block's props; `null` removes a key.
+  props: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThemeUpdateParams {
+  /// Merged into `theme.json` tokens; `null` removes a token.
+  tokens: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PatchProposeParams {
+  /// RFC 6902 operations against the `BuilderProjectDoc` returned by `load_builder_project`.
+  patch: json_patch::Patch,
+  #[serde(default)]
+  summary: String,
+}
+
+/// Runs one deterministic agent command. Never fails at the transport level: every problem is
+/// reported in the response envelope with a stable error code.
+pub(crate) fn dispatch_agent_command(
+  request: serde_json::Value,
+  services: &LiveServices,
+) -> AgentResponse {
+  let id = request.get("id").cloned().unwrap_or(serde_json::Value::Null);
+  let request = match serde_json::from_value::<AgentRequest>(request) {
+    Ok(request) => request,
+    Err(err) => {
+      return respond(id, Err(AgentError::new("invalid_request", err.to_string())));
+    }
+  };
+  if request.version != AGENT_PROTOCOL_VERSION {
+    return respond(
+      request.id,
+      Err(AgentError::new(
+        "unsupported_version",
+        format!(
+          "Protocol version {} is not supported; expected {}.",
+          request.version, AGENT_PROTOCOL_VERSION
+        ),
+      )),
+    );
+  }
+  let project_dir = PathBuf::from(&request.project_path);
+  if !project_dir.is_dir() {
+    return respond(
+      request.id,
+      Err(AgentError::new("project_not_found", "Project path is invalid")),
+    );
+  }
+  let requirement = method_requirement(&request.method);
+  let grant = match services.policy.authorize(
+    request.session_id.as_deref(),
+    &project_dir,
+    &request.method,
+    requirement,
+  ) {
+    Ok(grant) => grant,
+    Err(denial) => return respond(request.id, Err(AgentError::denied(denial))),
+  };
+  let ctx = AgentContext { services, grant };
+  let result = if requirement.writes {
+    // Writing methods read, change and save the project; nothing else may save in between.
+    services
+      .writes
+      .run(&project_dir, || Ok(run_method(&project_dir, &request.method, request.params, &ctx)))
+      .unwrap_or_else(|err| Err(err.into()))
+  } else {
+    run_method(&project_dir, &request.method, request.params, &ctx)
+  };
+  respond(request.id, result)
+}
+
+fn respond(id: serde_json::Value, result: Result<serde_json::Value, AgentError>) -> AgentResponse {
+  match result {
+    Ok(result) => AgentResponse {
+      version: AGENT_PROTOCOL_VERSION,
+      id,
+      ok: true,
+      result: Some(result),
+      error: None,
+    },
+    Err(error) => AgentResponse {
+      version: AGENT_PROTOCOL_VERSION,
+      id,
+      ok: false,
+      result: None,
+      error: Some(error),
+    },
+  }
+}
+
+fn parse_params<T: DeserializeOwned>(params: serde_json::Value) -> Result<T, AgentError> {
+  serde_json::from_value(params).map_err(|err| AgentError::invalid_params(err.to_string()))
+}
+
+/// Operation toggle and write access each method needs; unknown methods need nothing and
+/// fail with `unknown_method`.
+fn method_requirement(method: &str) -> MethodRequirement {
+  let (operation, writes) = match method {
+    "page.create" | "page.update" | "block.add" | "block.reorder" | "content.update" | "theme.update" => {
+      (Some(AgentOperation::EditContent), true)
+    }
+    // Proposals only write once the user approves them.
+    "patch.propose" => (Some(AgentOperation::EditContent), false),
+    "validate" | "lint" | "export.astro" => (Some(AgentOperation::RunTools), false),
+    _ => (None, false),
+  };
+  MethodRequirement { operation, writes }
+}
+
+fn run_method(
+  project_dir: &Path,
+  method: &str,
+  params: serde_json::Value,
+  ctx: &AgentContext,
+) -> Result<serde_json::Value, AgentError> {
+  match method {
+    "project.info" => project_info(project_dir),
+    "pages.list" => pages_list(project_dir),
+    "page.get" => page_get(project_dir, parse_params(params)?),
+    "page.create" => page_create(project_dir, parse_params(params)?, ctx),
+    "page.update" => page_update(project_dir, parse_params(params)?, ctx),
+    "block.add" => block_add(project_dir, parse_params(params)?, ctx),
+    "block.reorder" => block_reorder(project_dir, parse_params(params)?, ctx),
+    "content.update" => content_update(project_dir, parse_params(params)?, ctx),
+    "theme.update" => theme_update(project_dir, parse_params(params)?, ctx),
+    "patch.propose" => patch_propose(project_dir, parse_params(params)?, ctx),
+    "validate" => validate(project_dir),
+    "lint" => lint(project_dir),
+    // Part of protocol version 1 so clients can feature-detect it; answers `not_implemented`
+    // until the Astro exporter (packages/exporter-astro) exists.
+    "export.astro" => Err(AgentError::new(
+      "not_implemented",
+      "Astro export is not implemented yet; nothing was written.",
+    )),
+    other => Err(AgentError::new("unknown_method", format!("Unknown method {}.", other))),
+  }
+}
+
+fn load(project_dir: &Path) -> Result<BuilderProjectDoc, AgentError> {
+  load_builder_doc(project_dir).map_err(AgentError::from)
+}
+
+fn authorize_write(project_dir: &Path, target: &Path, ctx: &AgentContext) -> Result<(), AgentError> {
+  ctx
+    .services
+    .policy
+    .authorize_write(&ctx.grant, project_dir, target)
+    .map_err(AgentError::denied)
+}
+
+fn save(project_dir: &Path, doc: &BuilderProjectDoc, ctx: &AgentContext) -> Result<u64, AgentError> {
+  let normalized = normalize_builder_doc(doc.clone());
+  for file in [PROJECT_META_FILE, SITE_FILE, SITEMAP_FILE] {
+    authorize_write(project_dir, &project_dir.join(file), ctx)?;
+  }
+  for page in &normalized.pages {
+    authorize_write(project_dir, &page_doc_path(project_dir, page), ctx)?;
+  }
+  let services = ctx.services;
+  let revision = save_builder_doc(
+    project_dir,
+    doc,
+    &services.events,
+    &services.locks,
+    AGENT_CLIENT_ID,
+    ChangeSource::Agent,
+    ActivityOperation::Save,
+  )
+  .map_err(AgentError::from)?;
+  services.collab.reload(project_dir).map_err(AgentError::from)?;
+  Ok(revision)
+}
+
+fn page_mut<'a>(doc: &'a mut BuilderProjectDoc, page_id: &str) -> Result<&'a mut PageDoc, AgentError> {
+  doc
+    .pages
+    .iter_mut()
+    .find(|page| page.id == page_id)
+    .ok_or_else(|| AgentError::new("not_found", format!("Page {} does not exist.", page_id)))
+}
+
+/// Lowest free `block-N` id across the whole project, so repeated runs produce the same ids.
+fn next_block_id(doc: &BuilderProjectDoc) -> String {
+  let mut counter = 1;
+  loop {
+    let candidate = format!("block-{}", counter);
+    let taken = doc
+      .pages
+      .iter()
+      .any(|page| page.blocks.iter().any(|block| block.id == candidate));
+    if !taken {
+      return candidate;
+    }
+    counter += 1;
+  }
+}
+
+pub(crate) fn default_block_style() -> BlockStyleDoc {
+  BlockStyleDoc {
+    variant: "default".to_string(),
+    margin_top: None,
+    margin_right: None,
+    margin_bottom: None,
+    margin_left: None,
+    padding_top: None,
+    padding_right: None,
+    padding_bottom: None,
+    padding_left: None,
+    border_width: None,
+    border_style: None,
+    border_color: None,
+    border_radius: None,
+    background_color: None,
+    background_image: None,
+    text_color: None,
+    font_size: None,
+    translate_x: None,
+    translate_y: None,
+    primitive_styles: None,
+    viewport_styles: None,
+    primitive_viewport_styles: None,
+    state_viewport_styles: None,
+    primitive_state_viewport_styles: None,
+  }
+}
+
+fn project_info(project_dir: &Path) -> Result<serde_json::Value, AgentError> {
+  let metadata = read_project_metadata(project_dir).map_err(AgentError::from)?;
+  let doc = load(project_dir)?;
+  let block_count: usize = doc.pages.iter().map(|page| page.blocks.len()).sum();
+  Ok(serde_json::json!({
+    "name": metadata.name,
+    "slug": metadata.slug,
+    "siteUrl": metadata.site_url,
+    "createdAt": metadata.created_at,
+    "updatedAt": metadata.updated_at,
+    "revision": metadata.revision,
+    "siteName": doc.site.site_name,
+    "baseUrl": doc.site.base_url,
+    "rootPageId": doc.sitemap.root_page_id,
+    "pageCount": doc.pages.len(),
+    "blockCount": block_count,
+  }))
+}
+
+fn pages_list(project_dir: &Path) -> Result<serde_json::Value, AgentError> {
+  let doc = load(project_dir)?;
+  let pages: Vec<serde_json::Value> = doc
+    .pages
+    .iter()
+    .map(|page| {
+      serde_json::json!({
+        "id": page.id,
+        "title": page.title,
+        "route": page.route,
+        "isRoot": page.id == doc.sitemap.root_page_id,
+        "blocks": page.blocks.iter().map(|block| serde_json::json!({
+          "id": block.id,
+          "type": block.block_type,
+          "visibility": block.visibility,
+        })).collect::<Vec<_>>(),
+      })
+    })
+    .collect();
+  Ok(serde_json::json!({ "pages": pages }))
+}
+
+fn page_get(project_dir: &Path, params: PageRefParams) -> Result<serde_json::Value, AgentError> {
+  let mut doc = load(project_dir)?;
+  let page = page_mut(&mut doc, &params.page_id)?;
+  serde_json::to_value(&*page).map_err(|err| AgentError::failed(err.to_string()))
+}
+
+fn page_update(
+  project_dir: &Path,
+  params: PageUpdateParams,
+  ctx: &AgentContext,
+) -> Result<serde_json::Value, AgentError> {
+  let mut doc = load(project_dir)?;
+  let page = page_mut(&mut doc, &params.page_id)?;
+  if let Some(title) = params.title {
+    if title.trim().is_empty() {
+      return Err(AgentError::invalid_params("Page title cannot be empty."));
+    }
+    page.title = title.trim().to_string();
+  }
+  if let Some(seo_title) = params.seo_title {
+    page.seo.title = seo_title;
+  }
+  if let Some(seo_description) = params.seo_description {
+    page.seo.description = seo_description;
+  }
+  let seo = page.seo.clone();
+  let title = page.title.clone();
+  let revision = save(project_dir, &doc, ctx)?;
+  Ok(serde_json::json!({ "title": title, "seo": seo, "revision": revision }))
+}
+
+fn page_create(
+  project_dir: &Path,
+  params: PageCreateParams,
+  ctx: &AgentContext,
+) -> Result<serde_json::Value, AgentError> {
+  let title = params.title.trim().to_string();
+  if title.is_empty() {
+    return Err(AgentError::invalid_params("Page title is required."));
+  }
+  let route = format!("/{}", params.route.trim().trim_start_matches('/'));
+  let mut doc = load(project_dir)?;
+  if doc.pages.iter().any(|page| page.route == route) {
+    return Err(AgentError::new("conflict", format!("Route {} already exists.", route)));
+  }
+  doc.pages.push(PageDoc {
+    id: String::new(),
+    title: title.clone(),
+    route: route.clone(),
+    seo: PageSeoDoc {
+      title: params.seo_title.unwrap_or(title),
+      description: params.seo_description.unwrap_or_default(),
+    },
+    blocks: Vec::new(),
+  });
+  let doc = normalize_builder_doc(doc);
+  let page_id = doc
+    .pages
+    .iter()
+    .find(|page| page.route == route)
+    .map(|page| page.id.clone())
+    .unwrap_or_default();
+  let revision = save(project_dir, &doc, ctx)?;
+  Ok(serde_json::json!({ "pageId": page_id, "route": route, "revision": revision }))
+}
+
+fn block_add(
+  project_dir: &Path,
+  params: BlockAddParams,
+  ctx: &AgentContext,
+) -> Result<serde_json::Value, AgentError> {
+  if !KNOWN_BLOCK_TYPES.contains(&params.block_type.as_str()) {
+    return Err(AgentError::invalid_params(format!(
+      "Unknown block type {}. Expected one of: {}.",
+      params.block_type,
+      KNOWN_BLOCK_TYPES.join(", ")
+    )));
+  }
+  let mut doc = load(project_dir)?;
+  let block_id = next_block_id(&doc);
+  let page = page_mut(&mut doc, &params.page_id)?;
+  let index = params.index.unwrap_or(page.blocks.len());
+  if index > page.blocks.len() {
+    return Err(AgentError::invalid_params(format!(
+      "Index {} is past the end of page {} ({} blocks).",
+      index,
+      params.page_id,
+      page.blocks.len()
+    )));
+  }
+  page.blocks.insert(
+    index,
+    BlockDoc {
+      id: block_id.clone(),
+      block_type: params.block_type,
+      props: serde_json::Value::Object(params.props.unwrap_or_default()),
+      visibility: "visible".to_string(),
+      style_overrides: default_block_style(),
+    },
+  );
+  let revision = save(project_dir, &doc, ctx)?;
+  Ok(serde_json::json!({ "blockId": block_id, "index": index, "revision": revision }))
+}
+
+fn block_reorder(
+  project_dir: &Path,
+  params: BlockReorderParams,
+  ctx: &AgentContext,
+) -> Result<serde_json::Value, AgentError> {
+  let mut doc = load(project_dir)?;
+  let page = page_mut(&mut doc, &params.page_id)?;
+  let mut current: Vec<&str> = page.blocks.iter().map(|block| block.id.as_str()).collect();
+  let mut requested: Vec<&str> = params.block_ids.iter().map(String::as_str).collect();
+  current.sort_unstable();
+  requested.sort_unstable();
+  if current != requested {
+    return Err(AgentError::invalid_params(
+      "blockIds must list every block on the page exactly once.",
+    ));
+  }
+  let mut reordered = Vec::with_capacity(page.blocks.len());
+  for block_id in &params.block_ids {
+    if let Some(index) = page.blocks.iter().position(|block| &block.id == block_id) {
+      reordered.push(page.blocks.remove(index));
+    }
+  }
+  page.blocks = reordered;
+  let revision = save(project_dir, &doc, ctx)?;
+  Ok(serde_json::json!({ "blockIds": params.block_ids, "revision": revision }))
+}
+
+fn content_update(
+  project_dir: &Path,
+  params: ContentUpdateParams,
+  ctx: &AgentContext,
+) -> Result<serde_json::Value, AgentError> {
+  let mut doc = load(project_dir)?;
+  let page = page_mut(&mut doc, &params.page_id)?;
+  let block = page
+    .blocks
+    .iter_mut()
+    .find(|block| block.id == params.block_id)
+    .ok_or_else(|| {
+      AgentError::new(
+        "not_found",
+        format!("Block {} does not exist on page {}.", params.block_id, params.page_id),
+      )
+    })?;
+  if !block.props.is_object() {
+    block.props = serde_json::json!({});
+  }
+  if let Some(props) = block.props.as_object_mut() {
+    for (key, value) in params.props {
+      if value.is_null() {
+        props.remove(&key);
+      } else {
+        props.insert(key, value);
+      }
+    }
+  }
+  let props = block.props.clone();
+  let revision = save(project_dir, &doc, ctx)?;
+  Ok(serde_json::json!({ "props": props, "revision": revision }))
+}
+
+fn theme_update(
+  project_dir: &Path,
+  params: ThemeUpdateParams,
+  ctx: &AgentContext,
+) -> Result<serde_json::Value, AgentError> {
+  let theme_path = project_dir.join(THEME_FILE);
+  authorize_write(project_dir, &theme_path, ctx)?;
+  let mut theme = if theme_path.exists() {
+    read_json_file::<serde_json::Value>(&theme_path).map_err(AgentError::from)?
+  } else {
+    serde_json::json!({ "tokens": {} })
+  };
+  let Some(theme_fields) = theme.as_object_mut() else {
+    return Err(AgentError::failed(format!("{} is not a JSON object.", THEME_FILE)));
+  };
+  let tokens = theme_fields
+    .entry("tokens")
+    .or_insert_with(|| serde_json::json!({}));
+  if !tokens.is_object() {
+    *tokens = serde_json::json!({});
+  }
+  if let Some(tokens) = tokens.as_object_mut() {
+    for (key, value) in params.tokens {
+      if value.is_null() {
+        tokens.remove(&key);
+      } else {
+        tokens.insert(key, value);
+      }
+    }
+  }
+  write_json_file(&theme_path, &theme).map_err(AgentError::from)?;
+  let revision = commit_revision(
+    project_dir,
+    &ctx.services.events,
+    ChangeSource::Agent,
+    ActivityOperation::UpdateTheme,
+    Vec::new(),
+  )
+  .map_err(AgentError::from)?;
+  Ok(serde_json::json!({ "tokens": theme["tokens"], "revision": revision }))
+}
+
+/// Queues a change for user review instead of writing it.
+fn patch_propose(
+  project_dir: &Path,
+  params: PatchProposeParams,
+  ctx: &AgentContext,
+) -> Result<serde_json::Value, AgentError> {
+  let proposal = ctx
+    .services
+    .proposals
+    .submit(
+      project_dir,
+      params.patch,
+      &params.summary,
+      AGENT_CLIENT_ID,
+      ChangeSource::Agent,
+    )
+    .map_err(AgentError::from)?;
+  serde_json::to_value(proposal).map_err(|err| AgentError::failed(err.to_string()))
+}
+
+fn validate(project_dir: &Path) -> Result<serde_json::Value, AgentError> {
+  let doc = load(project_dir)?;
+  let issues = validate_builder_doc(&doc);
+  let valid = !issues.iter().any(|issue| issue.severity == IssueSeverity::Error);
+  Ok(serde_json::json!({ "valid": valid, "issues": issues }))
+}
+
+fn lint(project_dir: &Path) -> Result<serde_json::Value, AgentError> {
+  let doc = load(project_dir)?;
+  let issues = lint_builder_doc(&doc);
+  Ok(serde_json::json!({ "issueCount": issues.len(), "issues": issues }))
+}
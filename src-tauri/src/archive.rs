@@ -0,0 +1,644 @@
+//! Portable project archives: one zip holding the files that make up a project and a manifest
+//! of their checksums. Generated files, transcripts and other machine-local state stay behind.
+//! Imports treat the archive as untrusted: every entry must be a plain relative path listed in
+//! the manifest with a matching checksum, and the project is unpacked into a staging directory
+//! that only becomes a `*.manifold` project once it validates.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use axum::extract::{self, State};
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use ts_rs::TS;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::assets::ASSETS_DIR;
+use crate::error::AppError;
+use crate::rest::project_dir;
+use crate::schema::{first_violation, validate_project_files, write_schemas, SCHEMAS_DIR};
+use crate::{
+  load_builder_doc, normalize_slug, now_iso, persist_builder_doc, project_record_from_dir,
+  read_json_file, read_project_metadata, require_remote_token, write_json_file, write_project_metadata,
+  ProjectRecord, RemoteApiState, PAGES_DIR, PROJECT_FORMAT_VERSION, PROJECT_META_FILE, SITEMAP_FILE,
+  SITE_FILE, THEME_FILE,
+};
+
+/// Archives are `<slug>.manifold.zip` so they never pass for a project directory.
+pub(crate) const ARCHIVE_SUFFIX: &str = ".manifold.zip";
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+pub(crate) const ARCHIVE_FORMAT: &str = "manifold-archive";
+pub(crate) const ARCHIVE_VERSION: u32 = 1;
+pub(crate) const ARCHIVE_CONTENT_TYPE: &str = "application/zip";
+
+/// Import limits. Sizes are counted from the bytes actually inflated, not the sizes the zip
+/// headers claim.
+const MAX_ARCHIVE_BYTES: u64 = 512 * 1024 * 1024;
+const MAX_UNPACKED_BYTES: u64 = 1024 * 1024 * 1024;
+const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+const MAX_MANIFEST_BYTES: u64 = 8 * 1024 * 1024;
+const STAGING_PREFIX: &str = ".import-";
+
+static NEXT_IMPORT: AtomicU64 = AtomicU64::new(1);
+
+/// Default destination inside the project; never archived itself.
+const EXPORTS_DIR: &str = "exports";
+const CONTENT_DIR: &str = "content";
+const BLOCKS_DIR: &str = "blocks";
+
+/// What a project is made of. Everything else in the directory (`exports/`, `schemas/`,
+/// dotfiles such as partial uploads) is regenerated or specific to this machine.
+const ARCHIVE_FILES: &[&str] = &[PROJECT_META_FILE, SITE_FILE, SITEMAP_FILE, THEME_FILE];
+const ARCHIVE_DIRS: &[&str] = &[PAGES_DIR, CONTENT_DIR, ASSETS_DIR, BLOCKS_DIR];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveEntry {
+  /// Path inside the archive and the project, with `/` separators.
+  pub(crate) path: String,
+  #[ts(type = "number")]
+  pub(crate) size: u64,
+  pub(crate) sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveManifest {
+  /// Always `manifold-archive`.
+  pub(crate) format: String,
+  pub(crate) version: u32,
+  /// Studio build that wrote the archive.
+  pub(crate) app_version: String,
+  pub(crate) exported_at: String,
+  pub(crate) name: String,
+  pub(crate) slug: String,
+  /// Every file in the archive except the manifest, sorted by path.
+  pub(crate) files: Vec<ArchiveEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveExport {
+  /// Where the archive was written.
+  pub(crate) path: String,
+  #[ts(type = "number")]
+  pub(crate) size: u64,
+  pub(crate) manifest: ArchiveManifest,
+}
+
+pub(crate) fn archive_file_name(slug: &str) -> String {
+  format!("{}{}", slug, ARCHIVE_SUFFIX)
+}
+
+fn zip_error(err: zip::result::ZipError) -> AppError {
+  AppError::internal(format!("Failed writing archive: {}", err))
+}
+
+/// Files that make up a project as (relative path, file on disk), sorted by path. Archives and
+/// duplicates carry exactly these.
+pub(crate) fn project_files(project_dir: &Path) -> Result<Vec<(String, PathBuf)>, AppError> {
+  let mut files = Vec::new();
+  for name in ARCHIVE_FILES {
+    let path = project_dir.join(name);
+    if path.is_file() {
+      files.push((name.to_string(), path));
+    }
+  }
+  for name in ARCHIVE_DIRS {
+    collect_dir(&project_dir.join(name), name, &mut files)?;
+  }
+  files.sort_by(|a, b| a.0.cmp(&b.0));
+  Ok(files)
+}
+
+/// Regular files under `dir`, skipping dotfiles and symlinks, which may point off the project.
+fn collect_dir(dir: &Path, prefix: &str, out: &mut Vec<(String, PathBuf)>) -> Result<(), AppError> {
+  if !dir.is_dir() {
+    return Ok(());
+  }
+  let entries = fs::read_dir(dir).map_err(|err| AppError::fs("Failed reading", dir, err))?;
+  for entry in entries {
+    let entry = entry.map_err(|err| AppError::fs("Failed reading", dir, err))?;
+    let name = entry.file_name().to_string_lossy().to_string();
+    if name.starts_with('.') {
+      continue;
+    }
+    let path = entry.path();
+    let file_type = entry.file_type().map_err(|err| AppError::fs("Failed reading", &path, err))?;
+    let archive_path = format!("{}/{}", prefix, name);
+    if file_type.is_dir() {
+      collect_dir(&path, &archive_path, out)?;
+    } else if file_type.is_file() {
+      out.push((archive_path, path));
+    }
+  }
+  Ok(())
+}
+
+/// Writes the project as a zip into `writer` and returns the manifest stored with it.
+pub(crate) fn write_archive<W: Write + Seek>(project_dir: &Path, writer: W) -> Result<ArchiveManifest, AppError> {
+  if !project_dir.join(PROJECT_META_FILE).is_file() {
+    return Err(AppError::project_not_found(project_dir));
+  }
+  let metadata = read_project_metadata(project_dir)?;
+  let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+  let mut zip = ZipWriter::new(writer);
+  let mut entries = Vec::new();
+  for (archive_path, path) in project_files(project_dir)? {
+    let bytes = fs::read(&path).map_err(|err| AppError::fs("Failed reading", &path, err))?;
+    zip.start_file(archive_path.as_str(), options).map_err(zip_error)?;
+    zip
+      .write_all(&bytes)
+      .map_err(|err| AppError::internal(format!("Failed writing archive: {}", err)))?;
+    entries.push(ArchiveEntry {
+      path: archive_path,
+      size: bytes.len() as u64,
+      sha256: Sha256::digest(&bytes).iter().map(|byte| format!("{:02x}", byte)).collect(),
+    });
+  }
+
+  let manifest = ArchiveManifest {
+    format: ARCHIVE_FORMAT.to_string(),
+    version: ARCHIVE_VERSION,
+    app_version: env!("CARGO_PKG_VERSION").to_string(),
+    exported_at: now_iso(),
+    name: metadata.name,
+    slug: metadata.slug,
+    files: entries,
+  };
+  let manifest_json = serde_json::to_vec_pretty(&manifest)
+    .map_err(|err| AppError::internal(format!("Failed serializing manifest: {}", err)))?;
+  zip.start_file(MANIFEST_FILE, options).map_err(zip_error)?;
+  zip
+    .write_all(&manifest_json)
+    .map_err(|err| AppError::internal(format!("Failed writing archive: {}", err)))?;
+  zip.finish().map_err(zip_error)?;
+  Ok(manifest)
+}
+
+/// Writes the archive to `destination`, or to `<project>/exports/<slug>-<timestamp>.manifold.zip`.
+/// The file appears only once complete.
+pub(crate) fn export_project_archive(
+  project_dir: &Path,
+  destination: Option<&Path>,
+) -> Result<ArchiveExport, AppError> {
+  let destination = match destination {
+    Some(destination) => destination.to_path_buf(),
+    None => {
+      let metadata = read_project_metadata(project_dir)?;
+      let stamp = Utc::now().format("%Y%m%dT%H%M%S");
+      project_dir
+        .join(EXPORTS_DIR)
+        .join(archive_file_name(&format!("{}-{}", metadata.slug, stamp)))
+    }
+  };
+  if let Some(parent) = destination.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+    fs::create_dir_all(parent).map_err(|err| AppError::fs("Failed creating", parent, err))?;
+  }
+
+  let partial = destination.with_extension("zip.part");
+  let result = File::create(&partial)
+    .map_err(|err| AppError::fs("Failed creating", &partial, err))
+    .and_then(|file| write_archive(project_dir, file))
+    .and_then(|manifest| {
+      fs::rename(&partial, &destination).map_err(|err| AppError::fs("Failed writing", &destination, err))?;
+      Ok(manifest)
+    });
+  let manifest = match result {
+    Ok(manifest) => manifest,
+    Err(err) => {
+      let _ = fs::remove_file(&partial);
+      return Err(err);
+    }
+  };
+  let size = fs::metadata(&destination)
+    .map_err(|err| AppError::fs("Failed reading", &destination, err))?
+    .len();
+  Ok(ArchiveExport {
+    path: destination.to_string_lossy().to_string(),
+    size,
+    manifest,
+  })
+}
+
+fn invalid_archive(message: impl Into<String>) -> AppError {
+  AppError::invalid(message).with_field("archivePath")
+}
+
+fn read_error(err: zip::result::ZipError) -> AppError {
+  invalid_archive(format!("Not a readable archive: {}", err))
+}
+
+/// Entry names must be relative `/`-separated paths inside one of the archived files or
+/// directories. Rejects `..`, absolute and drive paths, backslashes and dotfiles.
+fn check_entry_path(name: &str) -> Result<(), AppError> {
+  let safe = !name.is_empty()
+    && !name.contains('\\')
+    && !name.contains(':')
+    && name.split('/').all(|part| !part.is_empty() && !part.starts_with('.'));
+  let allowed = ARCHIVE_FILES.contains(&name)
+    || ARCHIVE_DIRS
+      .iter()
+      .any(|dir| name.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/')));
+  if safe && allowed {
+    Ok(())
+  } else {
+    Err(invalid_archive(format!("Archive contains a disallowed path: {}", name)))
+  }
+}
+
+fn read_manifest<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<ArchiveManifest, AppError> {
+  let mut entry = zip
+    .by_name(MANIFEST_FILE)
+    .map_err(|_| invalid_archive(format!("Archive has no {}", MANIFEST_FILE)))?;
+  let mut content = Vec::new();
+  (&mut entry)
+    .take(MAX_MANIFEST_BYTES + 1)
+    .read_to_end(&mut content)
+    .map_err(|err| invalid_archive(format!("Failed reading {}: {}", MANIFEST_FILE, err)))?;
+  if content.len() as u64 > MAX_MANIFEST_BYTES {
+    return Err(invalid_archive(format!("{} is too large", MANIFEST_FILE)));
+  }
+  let manifest = serde_json::from_slice::<ArchiveManifest>(&content)
+    .map_err(|err| invalid_archive(format!("Invalid {}: {}", MANIFEST_FILE, err)))?;
+  if manifest.format != ARCHIVE_FORMAT {
+    return Err(invalid_archive(format!("Not a Manifold archive (format {})", manifest.format)));
+  }
+  if manifest.version > ARCHIVE_VERSION {
+    return Err(invalid_archive(format!(
+      "Archive version {} needs a newer studio (this one reads up to {})",
+      manifest.version, ARCHIVE_VERSION
+    )));
+  }
+  Ok(manifest)
+}
+
+/// Unpacks every entry into `staging_dir`, checking each against the manifest.
+fn unpack<R: Read + Seek>(
+  zip: &mut ZipArchive<R>,
+  manifest: &ArchiveManifest,
+  staging_dir: &Path,
+) -> Result<(), AppError> {
+  if zip.len() > MAX_ARCHIVE_ENTRIES {
+    return Err(AppError::too_large(format!("Archive has more than {} entries", MAX_ARCHIVE_ENTRIES)));
+  }
+  let expected = manifest
+    .files
+    .iter()
+    .map(|entry| (entry.path.as_str(), entry))
+    .collect::<HashMap<_, _>>();
+  let mut unpacked = HashSet::new();
+  let mut total = 0u64;
+  for index in 0..zip.len() {
+    let mut entry = zip.by_index(index).map_err(read_error)?;
+    let name = entry.name().to_string();
+    if entry.is_symlink() {
+      return Err(invalid_archive(format!("Archive contains a symlink: {}", name)));
+    }
+    if name == MANIFEST_FILE || entry.is_dir() {
+      continue;
+    }
+    check_entry_path(&name)?;
+    let Some(listed) = expected.get(name.as_str()) else {
+      return Err(invalid_archive(format!("{} is not listed in the manifest", name)));
+    };
+    if !unpacked.insert(name.clone()) {
+      return Err(invalid_archive(format!("Archive lists {} twice", name)));
+    }
+
+    let mut bytes = Vec::new();
+    (&mut entry)
+      .take(MAX_UNPACKED_BYTES - total + 1)
+      .read_to_end(&mut bytes)
+      .map_err(|err| invalid_archive(format!("Failed reading {}: {}", name, err)))?;
+    total += bytes.len() as u64;
+    if total > MAX_UNPACKED_BYTES {
+      return Err(AppError::too_large(format!(
+        "Archive unpacks to more than {} MB",
+        MAX_UNPACKED_BYTES / (1024 * 1024)
+      )));
+    }
+    let sha256 = Sha256::digest(&bytes).iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    if bytes.len() as u64 != listed.size || sha256 != listed.sha256 {
+      return Err(invalid_archive(format!("{} does not match its manifest checksum", name)));
+    }
+
+    let path = staging_dir.join(&name);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).map_err(|err| AppError::fs("Failed creating", parent, err))?;
+    }
+    fs::write(&path, &bytes).map_err(|err| AppError::fs("Failed writing", &path, err))?;
+  }
+  if let Some(missing) = manifest.files.iter().find(|entry| !unpacked.contains(&entry.path)) {
+    return Err(invalid_archive(format!("{} is listed in the manifest but missing", missing.path)));
+  }
+  if !unpacked.contains(PROJECT_META_FILE) {
+    return Err(invalid_archive(format!("Archive has no {}", PROJECT_META_FILE)));
+  }
+  Ok(())
+}
+
+/// `<slug>.manifold`, or the first free `<slug>-2.manifold`, `<slug>-3.manifold`, ...
+pub(crate) fn free_project_slug(workspace: &Path, slug: &str) -> String {
+  let mut candidate = slug.to_string();
+  let mut counter = 2;
+  while workspace.join(format!("{}.manifold", candidate)).exists() {
+    candidate = format!("{}-{}", slug, counter);
+    counter += 1;
+  }
+  candidate
+}
+
+/// One step upgrading unpacked project files from format `from` to `from + 1`. Steps edit the
+/// raw JSON, so they run before the files are checked against the current schemas.
+struct Migration {
+  from: u32,
+  apply: fn(&Path, &mut serde_json::Map<String, Value>) -> Result<(), AppError>,
+}
+
+/// In order; the last one ends at `PROJECT_FORMAT_VERSION`.
+const MIGRATIONS: &[Migration] = &[Migration {
+  from: 0,
+  apply: fill_metadata_defaults,
+}];
+
+/// Format 0 predates `formatVersion`. Its `project.json` may lack the fields added since, which
+/// are written out with the values reading them would assume.
+fn fill_metadata_defaults(_: &Path, project: &mut serde_json::Map<String, Value>) -> Result<(), AppError> {
+  for (key, value) in [
+    ("revision", Value::from(0)),
+    ("tags", Value::Array(Vec::new())),
+    ("clientName", Value::from("")),
+    ("pinned", Value::Bool(false)),
+    ("color", Value::from("none")),
+  ] {
+    project.entry(key).or_insert(value);
+  }
+  Ok(())
+}
+
+/// Project format of unpacked files. Version 1 archives may predate `formatVersion`, so one
+/// without it holds a format 0 project; later archive versions must record it.
+fn source_format(
+  manifest: &ArchiveManifest,
+  project: &serde_json::Map<String, Value>,
+) -> Result<u32, AppError> {
+  match project.get("formatVersion") {
+    Some(version) => version
+      .as_u64()
+      .and_then(|version| u32::try_from(version).ok())
+      .ok_or_else(|| invalid_archive(format!("{} has an invalid formatVersion", PROJECT_META_FILE))),
+    None if manifest.version <= 1 => Ok(0),
+    None => Err(invalid_archive(format!("{} has no formatVersion", PROJECT_META_FILE))),
+  }
+}
+
+/// Runs every migration the unpacked project needs, then checks the result against the
+/// current schemas. Checking before anything is loaded matters: loading skips pages that fail
+/// to parse.
+fn migrate_project(project_dir: &Path, manifest: &ArchiveManifest) -> Result<(), AppError> {
+  let meta_path = project_dir.join(PROJECT_META_FILE);
+  let mut project = match read_json_file::<Value>(&meta_path)? {
+    Value::Object(project) => project,
+    _ => return Err(invalid_archive(format!("{} is not a JSON object", PROJECT_META_FILE))),
+  };
+  let version = source_format(manifest, &project)?;
+  if version > PROJECT_FORMAT_VERSION {
+    return Err(invalid_archive(format!(
+      "Project format {} needs a newer studio (this one reads up to {})",
+      version, PROJECT_FORMAT_VERSION
+    )));
+  }
+  for migration in MIGRATIONS.iter().skip_while(|migration| migration.from < version) {
+    (migration.apply)(project_dir, &mut project)?;
+  }
+  project.insert("formatVersion".to_string(), Value::from(PROJECT_FORMAT_VERSION));
+  write_json_file(&meta_path, &project)?;
+
+  write_schemas(&project_dir.join(SCHEMAS_DIR))?;
+  if let Some(violation) = first_violation(&validate_project_files(project_dir)?) {
+    return Err(invalid_archive(violation));
+  }
+  Ok(())
+}
+
+/// Removes a staging directory unless the import moved it into place.
+struct Staging {
+  dir: PathBuf,
+  kept: bool,
+}
+
+impl Drop for Staging {
+  fn drop(&mut self) {
+    if !self.kept && self.dir.exists() {
+      if let Err(err) = fs::remove_dir_all(&self.dir) {
+        log::warn!("failed removing import staging {}: {}", self.dir.display(), err);
+      }
+    }
+  }
+}
+
+/// Unpacks an archive into a new project in `workspace_root`. The slug comes from the archive,
+/// with a numeric suffix when a project of that name already exists.
+pub(crate) fn import_project_archive(workspace_root: &Path, archive_path: &Path) -> Result<ProjectRecord, AppError> {
+  if !workspace_root.exists() {
+    fs::create_dir_all(workspace_root)
+      .map_err(|err| AppError::fs("Failed creating workspace", workspace_root, err))?;
+  }
+  if !workspace_root.is_dir() {
+    return Err(AppError::invalid("Workspace root must be a directory").with_field("workspaceRoot"));
+  }
+  let archive_size = fs::metadata(archive_path)
+    .map_err(|err| AppError::fs("Failed reading", archive_path, err))?
+    .len();
+  if archive_size > MAX_ARCHIVE_BYTES {
+    return Err(
+      AppError::too_large(format!("Archives are limited to {} MB", MAX_ARCHIVE_BYTES / (1024 * 1024)))
+        .with_field("archivePath"),
+    );
+  }
+  let file = File::open(archive_path).map_err(|err| AppError::fs("Failed opening", archive_path, err))?;
+  let mut zip = ZipArchive::new(file).map_err(read_error)?;
+  let manifest = read_manifest(&mut zip)?;
+
+  let mut staging = Staging {
+    dir: workspace_root.join(format!(
+      "{}{}-{}",
+      STAGING_PREFIX,
+      Utc::now().format("%Y%m%dT%H%M%S%3f"),
+      NEXT_IMPORT.fetch_add(1, Ordering::Relaxed)
+    )),
+    kept: false,
+  };
+  fs::create_dir_all(&staging.dir).map_err(|err| AppError::fs("Failed creating", &staging.dir, err))?;
+  unpack(&mut zip, &manifest, &staging.dir)?;
+  migrate_project(&staging.dir, &manifest)?;
+
+  let mut metadata = read_project_metadata(&staging.dir)?;
+  let mut base_slug = normalize_slug(&manifest.slug);
+  if base_slug.is_empty() {
+    base_slug = normalize_slug(&metadata.name);
+  }
+  let slug = free_project_slug(workspace_root, &base_slug);
+  metadata.slug = slug.clone();
+  metadata.updated_at = now_iso();
+  write_project_metadata(&staging.dir, &metadata)?;
+  // Saved back so the project holds the normalized document (page ids from routes, a
+  // consistent sitemap) rather than normalizing it on every load.
+  persist_builder_doc(&staging.dir, &load_builder_doc(&staging.dir)?)?;
+
+  let project_dir = workspace_root.join(format!("{}.manifold", slug));
+  fs::rename(&staging.dir, &project_dir).map_err(|err| AppError::fs("Failed creating project", &project_dir, err))?;
+  staging.kept = true;
+  project_record_from_dir(&project_dir)
+}
+
+/// Archive download for remote editors, built in memory.
+pub(crate) async fn download_archive(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  extract::Path(slug): extract::Path<String>,
+) -> Result<Response, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let project_dir = project_dir(&api, &slug)?;
+  let mut buffer = Cursor::new(Vec::new());
+  write_archive(&project_dir, &mut buffer)?;
+  let disposition = format!("attachment; filename=\"{}\"", archive_file_name(&slug));
+  Ok(
+    (
+      [(CONTENT_TYPE, ARCHIVE_CONTENT_TYPE.to_string()), (CONTENT_DISPOSITION, disposition)],
+      buffer.into_inner(),
+    )
+      .into_response(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A version 1 archive holding `project.json` plus one hostile entry, listed in the manifest
+  /// with a matching checksum so only its path or type can get it rejected.
+  fn hostile_archive(dir: &Path, add: impl FnOnce(&mut ZipWriter<File>) -> ArchiveEntry) -> PathBuf {
+    let path = dir.join("hostile.manifold.zip");
+    let mut zip = ZipWriter::new(File::create(&path).unwrap());
+    let options = SimpleFileOptions::default();
+    let project = serde_json::to_vec(&serde_json::json!({
+      "name": "Hostile",
+      "slug": "hostile",
+      "siteUrl": "https://example.com",
+      "createdAt": "2026-01-01T00:00:00Z",
+      "updatedAt": "2026-01-01T00:00:00Z"
+    }))
+    .unwrap();
+    zip.start_file(PROJECT_META_FILE, options).unwrap();
+    zip.write_all(&project).unwrap();
+    let hostile = add(&mut zip);
+    let manifest = ArchiveManifest {
+      format: ARCHIVE_FORMAT.to_string(),
+      version: 1,
+      app_version: String::new(),
+      exported_at: String::new(),
+      name: "Hostile".to_string(),
+      slug: "hostile".to_string(),
+      files: vec![
+        ArchiveEntry {
+          path: PROJECT_META_FILE.to_string(),
+          size: project.len() as u64,
+          sha256: Sha256::digest(&project).iter().map(|byte| format!("{:02x}", byte)).collect(),
+        },
+        hostile,
+      ],
+    };
+    zip.start_file(MANIFEST_FILE, options).unwrap();
+    zip.write_all(&serde_json::to_vec(&manifest).unwrap()).unwrap();
+    zip.finish().unwrap();
+    path
+  }
+
+  fn file_entry(zip: &mut ZipWriter<File>, name: &str) -> ArchiveEntry {
+    let bytes = b"{}";
+    zip.start_file(name, SimpleFileOptions::default()).unwrap();
+    zip.write_all(bytes).unwrap();
+    ArchiveEntry {
+      path: name.to_string(),
+      size: bytes.len() as u64,
+      sha256: Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect(),
+    }
+  }
+
+  fn assert_rejected(archive: &Path) {
+    let workspace = tempfile::tempdir().unwrap();
+    let err = import_project_archive(workspace.path(), archive).unwrap_err();
+    assert_eq!(err.code(), crate::error::ErrorCode::InvalidInput, "{}", err);
+    assert_eq!(fs::read_dir(workspace.path()).unwrap().count(), 0, "staging left behind");
+  }
+
+  #[test]
+  fn rejects_entries_that_escape_the_project() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["../evil.json", "pages/../../evil.json", "/tmp/evil.json", "C:/evil.json"] {
+      assert_rejected(&hostile_archive(dir.path(), |zip| file_entry(zip, name)));
+    }
+  }
+
+  #[test]
+  fn rejects_symlinks() {
+    let dir = tempfile::tempdir().unwrap();
+    let archive = hostile_archive(dir.path(), |zip| {
+      zip
+        .add_symlink("pages/home.json", "/etc/passwd", SimpleFileOptions::default())
+        .unwrap();
+      ArchiveEntry {
+        path: "pages/home.json".to_string(),
+        size: 11,
+        sha256: String::new(),
+      }
+    });
+    assert_rejected(&archive);
+  }
+
+  #[test]
+  fn migrates_projects_from_before_format_versions() {
+    let source = tempfile::tempdir().unwrap();
+    let project_dir = crate::test_project(source.path());
+    let meta_path = project_dir.join(PROJECT_META_FILE);
+    let mut project = read_json_file::<Value>(&meta_path).unwrap();
+    for key in ["formatVersion", "revision", "tags", "clientName", "pinned", "color"] {
+      project.as_object_mut().unwrap().remove(key);
+    }
+    write_json_file(&meta_path, &project).unwrap();
+    let archive = source.path().join("site.manifold.zip");
+    write_archive(&project_dir, File::create(&archive).unwrap()).unwrap();
+
+    let workspace = tempfile::tempdir().unwrap();
+    let record = import_project_archive(workspace.path(), &archive).unwrap();
+    let imported = read_json_file::<Value>(&Path::new(&record.path).join(PROJECT_META_FILE)).unwrap();
+    assert_eq!(imported["formatVersion"], PROJECT_FORMAT_VERSION);
+    assert_eq!(imported["tags"], serde_json::json!([]));
+    assert_eq!(imported["color"], "none");
+  }
+
+  #[test]
+  fn refuses_newer_project_formats() {
+    let source = tempfile::tempdir().unwrap();
+    let project_dir = crate::test_project(source.path());
+    let meta_path = project_dir.join(PROJECT_META_FILE);
+    let mut project = read_json_file::<Value>(&meta_path).unwrap();
+    project["formatVersion"] = Value::from(PROJECT_FORMAT_VERSION + 1);
+    write_json_file(&meta_path, &project).unwrap();
+    let archive = source.path().join("site.manifold.zip");
+    write_archive(&project_dir, File::create(&archive).unwrap()).unwrap();
+    assert_rejected(&archive);
+  }
+}
@@ -0,0 +1,401 @@
+//! Image uploads into a project's `assets/` folder. Uploads stream to a temporary file while
+//! they are hashed and checked, then take a content-hash name, so uploading the same image
+//! twice stores it once.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use axum::extract::multipart::{Field, MultipartError};
+use axum::extract::{self, Multipart, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::Utc;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use ts_rs::TS;
+
+use crate::activity::ActivityOperation;
+use crate::error::AppError;
+use crate::events::{ChangeSource, ProjectEvent};
+use crate::rest::project_dir;
+use crate::{project_path_string, require_remote_token, touch_project, RemoteApiState};
+
+pub(crate) const ASSETS_DIR: &str = "assets";
+/// Multipart field that carries the file; other fields are ignored.
+pub(crate) const FILE_FIELD: &str = "file";
+pub(crate) const MAX_ASSET_BYTES: u64 = 20 * 1024 * 1024;
+/// Request body limit for the upload route: one asset plus multipart framing.
+pub(crate) const MAX_UPLOAD_BYTES: usize = MAX_ASSET_BYTES as usize + 64 * 1024;
+
+/// Enough of the file to recognize every accepted format, including SVGs behind an XML prolog.
+const SNIFF_BYTES: usize = 512;
+/// Hex digits of the SHA-256 used in stored file names.
+const HASH_NAME_LEN: usize = 16;
+const PARTIAL_PREFIX: &str = ".upload-";
+
+static NEXT_UPLOAD: AtomicU64 = AtomicU64::new(1);
+
+struct AssetFormat {
+  extension: &'static str,
+  content_type: &'static str,
+}
+
+const PNG: AssetFormat = AssetFormat { extension: "png", content_type: "image/png" };
+const JPEG: AssetFormat = AssetFormat { extension: "jpg", content_type: "image/jpeg" };
+const GIF: AssetFormat = AssetFormat { extension: "gif", content_type: "image/gif" };
+const WEBP: AssetFormat = AssetFormat { extension: "webp", content_type: "image/webp" };
+const AVIF: AssetFormat = AssetFormat { extension: "avif", content_type: "image/avif" };
+const ICO: AssetFormat = AssetFormat { extension: "ico", content_type: "image/x-icon" };
+const SVG: AssetFormat = AssetFormat { extension: "svg", content_type: "image/svg+xml" };
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StoredAsset {
+  /// `<sha256 prefix>.<ext>`, e.g. `3f9a0c1d2b4e5f60.png`.
+  pub(crate) file_name: String,
+  /// Relative to the project, with `/` separators; what blocks reference.
+  pub(crate) path: String,
+  /// Detected from the file's contents, not the client's declared type.
+  pub(crate) content_type: String,
+  #[ts(type = "number")]
+  pub(crate) size: u64,
+  pub(crate) sha256: String,
+  /// File name the client sent, if any.
+  pub(crate) original_name: Option<String>,
+  /// False when an identical file was already stored; the response then describes that file.
+  pub(crate) created: bool,
+}
+
+/// An upload being written. Dropping it before [`PartialUpload::persist`] deletes the file;
+/// that covers validation errors and clients that disconnect mid-upload, since axum drops the
+/// handler future when the connection goes away.
+struct PartialUpload {
+  path: PathBuf,
+  file: Option<File>,
+  persisted: bool,
+}
+
+impl PartialUpload {
+  fn create(dir: &Path) -> Result<Self, AppError> {
+    let path = dir.join(format!(
+      "{}{}-{}.part",
+      PARTIAL_PREFIX,
+      Utc::now().format("%Y%m%dT%H%M%S%3f"),
+      NEXT_UPLOAD.fetch_add(1, Ordering::Relaxed)
+    ));
+    let file = OpenOptions::new()
+      .write(true)
+      .create_new(true)
+      .open(&path)
+      .map_err(|err| AppError::fs("Failed creating", &path, err))?;
+    Ok(Self {
+      path,
+      file: Some(file),
+      persisted: false,
+    })
+  }
+
+  fn write(&mut self, bytes: &[u8]) -> Result<(), AppError> {
+    let Some(file) = self.file.as_mut() else {
+      return Err(AppError::internal("Upload file is already closed."));
+    };
+    file
+      .write_all(bytes)
+      .map_err(|err| AppError::fs("Failed writing", &self.path, err))
+  }
+
+  /// Moves the upload to `dest`. Returns false, discarding the upload, when `dest` already
+  /// exists: the name is a content hash, so it holds the same bytes.
+  fn persist(mut self, dest: &Path) -> Result<bool, AppError> {
+    if let Some(file) = self.file.take() {
+      file
+        .sync_all()
+        .map_err(|err| AppError::fs("Failed writing", &self.path, err))?;
+    }
+    if dest.is_file() {
+      return Ok(false);
+    }
+    fs::rename(&self.path, dest).map_err(|err| AppError::fs("Failed storing", dest, err))?;
+    self.persisted = true;
+    Ok(true)
+  }
+}
+
+impl Drop for PartialUpload {
+  fn drop(&mut self) {
+    // Close first; Windows cannot remove an open file.
+    self.file.take();
+    if !self.persisted {
+      if let Err(err) = fs::remove_file(&self.path) {
+        log::warn!("failed removing partial upload {}: {}", self.path.display(), err);
+      }
+    }
+  }
+}
+
+fn sniff(head: &[u8]) -> Option<&'static AssetFormat> {
+  if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+    return Some(&PNG);
+  }
+  if head.starts_with(&[0xff, 0xd8, 0xff]) {
+    return Some(&JPEG);
+  }
+  if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+    return Some(&GIF);
+  }
+  if head.len() >= 12 && &head[..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+    return Some(&WEBP);
+  }
+  if head.len() >= 12 && &head[4..8] == b"ftyp" && matches!(&head[8..12], b"avif" | b"avis") {
+    return Some(&AVIF);
+  }
+  if head.starts_with(&[0, 0, 1, 0]) {
+    return Some(&ICO);
+  }
+  let text = String::from_utf8_lossy(head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head)).to_lowercase();
+  if text.trim_start().starts_with('<') && text.contains("<svg") {
+    return Some(&SVG);
+  }
+  None
+}
+
+fn unsupported_format() -> AppError {
+  AppError::invalid("Unsupported asset format; upload a PNG, JPEG, GIF, WebP, AVIF, ICO or SVG image.")
+    .with_field(FILE_FIELD)
+}
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+
+/// SVG elements an uploaded image may use: shapes, text, paint servers, filters and
+/// animation. `script`, `foreignObject` and anything outside the SVG namespace are not listed.
+const SVG_ELEMENTS: &[&str] = &[
+  "svg", "g", "defs", "symbol", "use", "title", "desc", "metadata", "style", "switch", "a",
+  "path", "rect", "circle", "ellipse", "line", "polyline", "polygon", "image", "text", "tspan",
+  "textPath", "linearGradient", "radialGradient", "stop", "pattern", "clipPath", "mask", "marker",
+  "filter", "feBlend", "feColorMatrix", "feComponentTransfer", "feComposite", "feConvolveMatrix",
+  "feDiffuseLighting", "feDisplacementMap", "feDistantLight", "feDropShadow", "feFlood",
+  "feFuncA", "feFuncB", "feFuncG", "feFuncR", "feGaussianBlur", "feImage", "feMerge",
+  "feMergeNode", "feMorphology", "feOffset", "fePointLight", "feSpecularLighting", "feSpotLight",
+  "feTile", "feTurbulence", "animate", "animateMotion", "animateTransform", "set", "mpath",
+];
+
+/// Embedded raster images an `<image>` may carry inline.
+const SVG_DATA_IMAGES: &[&str] = &["data:image/png", "data:image/jpeg", "data:image/gif", "data:image/webp"];
+
+/// Sites serve assets from their own origin, so an SVG that could run script is rejected rather
+/// than stored. The file is parsed as XML (DTDs and entities refused) and checked against an
+/// allow-list, so encodings such as character references cannot hide a payload.
+fn check_svg(path: &Path) -> Result<(), AppError> {
+  let text = fs::read_to_string(path)
+    .map_err(|_| AppError::invalid("SVG is not valid UTF-8 text.").with_field(FILE_FIELD))?;
+  let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+  svg_violation(text).map_or(Ok(()), |reason| {
+    Err(AppError::invalid(format!("SVG rejected: {}", reason)).with_field(FILE_FIELD))
+  })
+}
+
+/// Why the document is unsafe to serve, if it is.
+fn svg_violation(text: &str) -> Option<String> {
+  let doc = match roxmltree::Document::parse(text) {
+    Ok(doc) => doc,
+    Err(err) => return Some(format!("not well-formed XML ({})", err)),
+  };
+  let root = doc.root_element();
+  if root.tag_name().name() != "svg" || root.tag_name().namespace() != Some(SVG_NAMESPACE) {
+    return Some("the root element is not <svg>".to_string());
+  }
+  for node in doc.descendants().filter(|node| node.is_element()) {
+    let name = node.tag_name().name();
+    if node.tag_name().namespace() != Some(SVG_NAMESPACE) || !SVG_ELEMENTS.contains(&name) {
+      return Some(format!("<{}> is not allowed", name));
+    }
+    for attribute in node.attributes() {
+      let attr_name = attribute.name();
+      let value = attribute.value();
+      if attr_name.to_ascii_lowercase().starts_with("on") {
+        return Some(format!("event handler {} on <{}>", attr_name, name));
+      }
+      let is_href = attr_name == "href"
+        && matches!(attribute.namespace(), None | Some(XLINK_NAMESPACE));
+      if is_href && !svg_href_allowed(name, value) {
+        return Some(format!("<{}> links to {}", name, value.trim()));
+      }
+      if attr_name == "attributeName" && attribute.namespace().is_none() {
+        let target = value.trim().to_ascii_lowercase();
+        if target.starts_with("on") || target == "href" || target.ends_with(":href") {
+          return Some(format!("<{}> animates {}", name, value.trim()));
+        }
+      }
+    }
+    if name == "style" {
+      let css = node.text().unwrap_or_default().to_ascii_lowercase();
+      if css.contains("@import") || css.contains("javascript:") {
+        return Some("<style> imports or scripts".to_string());
+      }
+    }
+  }
+  None
+}
+
+/// Local fragments everywhere; http(s) on links and images, which may also embed rasters.
+/// `<use>` and `<feImage>` stay local so they cannot pull in another document.
+fn svg_href_allowed(element: &str, href: &str) -> bool {
+  // Browsers drop whitespace and control characters inside a scheme, e.g. `java\tscript:`.
+  let href: String = href
+    .chars()
+    .filter(|c| !c.is_whitespace() && !c.is_control())
+    .collect::<String>()
+    .to_ascii_lowercase();
+  if href.starts_with('#') {
+    return true;
+  }
+  let remote = href.starts_with("https://") || href.starts_with("http://");
+  match element {
+    "a" => remote,
+    "image" => remote || SVG_DATA_IMAGES.iter().any(|prefix| href.starts_with(prefix)),
+    _ => false,
+  }
+}
+
+fn upload_error(err: MultipartError) -> AppError {
+  if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+    too_large()
+  } else {
+    AppError::invalid(format!("Malformed upload: {}", err.body_text())).with_field(FILE_FIELD)
+  }
+}
+
+fn too_large() -> AppError {
+  AppError::too_large(format!("Assets are limited to {} MB.", MAX_ASSET_BYTES / (1024 * 1024)))
+    .with_field(FILE_FIELD)
+}
+
+async fn store_field(assets_dir: &Path, mut field: Field<'_>) -> Result<StoredAsset, AppError> {
+  let original_name = field.file_name().map(str::to_string);
+  let mut partial = PartialUpload::create(assets_dir)?;
+  let mut hasher = Sha256::new();
+  let mut head = Vec::with_capacity(SNIFF_BYTES);
+  let mut format = None;
+  let mut size = 0u64;
+  while let Some(chunk) = field.chunk().await.map_err(upload_error)? {
+    size += chunk.len() as u64;
+    if size > MAX_ASSET_BYTES {
+      return Err(too_large());
+    }
+    // Reject the wrong kind of file as soon as its header arrives, not after the whole body.
+    if format.is_none() {
+      let take = chunk.len().min(SNIFF_BYTES - head.len());
+      head.extend_from_slice(&chunk[..take]);
+      if head.len() == SNIFF_BYTES {
+        format = Some(sniff(&head).ok_or_else(unsupported_format)?);
+      }
+    }
+    hasher.update(&chunk);
+    partial.write(&chunk)?;
+  }
+  if size == 0 {
+    return Err(AppError::invalid("Uploaded file is empty.").with_field(FILE_FIELD));
+  }
+  let format = match format {
+    Some(format) => format,
+    None => sniff(&head).ok_or_else(unsupported_format)?,
+  };
+  if format.extension == SVG.extension {
+    check_svg(&partial.path)?;
+  }
+
+  let sha256 = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+  let file_name = format!("{}.{}", &sha256[..HASH_NAME_LEN], format.extension);
+  let created = partial.persist(&assets_dir.join(&file_name))?;
+  Ok(StoredAsset {
+    path: format!("{}/{}", ASSETS_DIR, file_name),
+    file_name,
+    content_type: format.content_type.to_string(),
+    size,
+    sha256,
+    original_name,
+    created,
+  })
+}
+
+/// Stores the multipart `file` field. Answers 201 for a new asset and 200 when the same bytes
+/// were already stored.
+pub(crate) async fn upload_asset(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  extract::Path(slug): extract::Path<String>,
+  mut multipart: Multipart,
+) -> Result<Response, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let project_dir = project_dir(&api, &slug)?;
+  let assets_dir = project_dir.join(ASSETS_DIR);
+  fs::create_dir_all(&assets_dir).map_err(|err| AppError::fs("Failed creating assets dir", &assets_dir, err))?;
+
+  while let Some(field) = multipart.next_field().await.map_err(upload_error)? {
+    if field.name() != Some(FILE_FIELD) {
+      continue;
+    }
+    let asset = store_field(&assets_dir, field).await?;
+    if asset.created {
+      touch_project(&project_dir, ActivityOperation::AddAsset, Vec::new(), ChangeSource::Remote)?;
+      api.services.events.publish(ProjectEvent::AssetAdded {
+        project_path: project_path_string(&project_dir)?,
+        asset: asset.clone(),
+        source: ChangeSource::Remote,
+      });
+    }
+    let status = if asset.created { StatusCode::CREATED } else { StatusCode::OK };
+    return Ok((status, Json(asset)).into_response());
+  }
+  Err(AppError::invalid(format!("Upload has no `{}` field.", FILE_FIELD)).with_field(FILE_FIELD))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn svg(body: &str) -> String {
+    format!(
+      r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">{}</svg>"##,
+      body
+    )
+  }
+
+  #[test]
+  fn accepts_plain_drawings() {
+    let drawing = svg(concat!(
+      r##"<defs><linearGradient id="g"><stop offset="0" stop-color="red"/></linearGradient></defs>"##,
+      r##"<rect width="10" height="10" fill="url(#g)"/><use href="#g"/>"##,
+      r##"<a href="https://example.com"><text>Link</text></a>"##,
+      r##"<image href="data:image/png;base64,AAAA"/><animate attributeName="opacity" to="0"/>"##,
+    ));
+    assert_eq!(svg_violation(&drawing), None);
+  }
+
+  #[test]
+  fn rejects_script_payloads() {
+    let payloads = [
+      r##"<svg onload ="alert(1)" xmlns="http://www.w3.org/2000/svg"/>"##.to_string(),
+      r##"<svg xmlns="http://www.w3.org/2000/svg"><rect ONCLICK="alert(1)"/></svg>"##.to_string(),
+      svg(r##"<a href="jav&#x61;script:alert(1)"><text>x</text></a>"##),
+      svg(r##"<a xlink:href=" java&#9;script:alert(1)"><text>x</text></a>"##),
+      svg(r##"<use href="https://evil.example/sprite.svg#icon"/>"##),
+      svg(r##"<use xlink:href="data:image/svg+xml;base64,PHN2Zy8+#x"/>"##),
+      svg(r##"<set attributeName="onmouseover" to="alert(1)"/>"##),
+      svg(r##"<animate attributeName="href" values="javascript:alert(1)"/>"##),
+      svg(r##"<foreignObject><div xmlns="http://www.w3.org/1999/xhtml">x</div></foreignObject>"##),
+      svg(r##"<script>alert(1)</script>"##),
+      svg(r##"<style>@import url(https://evil.example/x.css);</style>"##),
+      r##"<!DOCTYPE svg [<!ENTITY x "y">]><svg xmlns="http://www.w3.org/2000/svg">&x;</svg>"##.to_string(),
+      r##"<html xmlns="http://www.w3.org/1999/xhtml"><svg/></html>"##.to_string(),
+    ];
+    for payload in payloads {
+      assert!(svg_violation(&payload).is_some(), "accepted {}", payload);
+    }
+  }
+}
@@ -0,0 +1,415 @@
+//! TypeScript bindings for the desktop commands and remote routes, generated from the same Rust
+//! types serde uses on the wire. `manifold bindings` writes them into the UI; `--check` fails CI
+//! when they are stale.
+
+use std::any::TypeId;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+use ts_rs::{TypeVisitor, TS};
+
+use crate::activity::ActivityEntry;
+use crate::agent::{AgentError, AgentResponse};
+use crate::archive::ArchiveExport;
+use crate::collab::{CollabChanges, CollabSnapshot, DocOperation, Participant, SubmitOutcome};
+use crate::error::AppError;
+use crate::locks::PagePresence;
+use crate::openapi::{
+  describe_routes, operation_id, path_params, ApiType, HealthDoc, RouteSink, API_PREFIX,
+};
+use crate::policy::{AgentAccessMode, AgentOperation, AgentSessionPolicy, PolicyDenial};
+use crate::project_index::{ProjectPage, ProjectQuery};
+use crate::proposals::{PatchProposal, ProposalOutcome};
+use crate::runner::{AgentRunInfo, AgentRunRequest};
+use crate::schema::ProjectFilesReport;
+use crate::shelf::{DeletionRequest, ProjectShelf, ShelvedProject};
+use crate::templates::ProjectTemplate;
+use crate::{BuilderProjectDoc, ProjectDetailsPatch, ProjectRecord, RemoteServerStatus};
+
+pub(crate) const TYPES_FILE: &str = "types.ts";
+pub(crate) const COMMANDS_FILE: &str = "commands.ts";
+
+const HEADER: &str = "// Generated by `manifold bindings` from the Rust types in src-tauri. Do not edit.\n";
+
+/// RFC 6902 operations; `json_patch::Patch` has no `TS` impl of its own.
+const JSON_PATCH_DECL: &str = "export type JsonPatch = Array<\n  \
+  | { op: \"add\" | \"replace\" | \"test\"; path: string; value: JsonValue }\n  \
+  | { op: \"remove\"; path: string }\n  \
+  | { op: \"move\" | \"copy\"; from: string; path: string }\n>;";
+
+pub(crate) struct Bindings {
+  pub(crate) types: String,
+  pub(crate) commands: String,
+}
+
+impl Bindings {
+  /// Paths under `out_dir` whose contents differ from the generated files.
+  pub(crate) fn stale_files(&self, out_dir: &Path) -> Vec<String> {
+    [(TYPES_FILE, &self.types), (COMMANDS_FILE, &self.commands)]
+      .into_iter()
+      .filter(|(name, contents)| fs::read_to_string(out_dir.join(name)).ok().as_ref() != Some(*contents))
+      .map(|(name, _)| out_dir.join(name).to_string_lossy().to_string())
+      .collect()
+  }
+}
+
+/// Collects `export type` declarations for every named type reachable from a root.
+#[derive(Default)]
+struct Declarations {
+  seen: HashSet<TypeId>,
+  decls: BTreeMap<String, String>,
+}
+
+impl TypeVisitor for Declarations {
+  fn visit<T: TS + 'static + ?Sized>(&mut self) {
+    if !self.seen.insert(TypeId::of::<T>()) {
+      return;
+    }
+    // Only derived and library types with their own file are declarable; wrappers such as
+    // `Vec<T>` and `Option<T>` are inlined and just lead to their parameters.
+    if T::output_path().is_some() {
+      let docs = T::docs().unwrap_or_default();
+      self.decls.insert(T::ident(), format!("{}export {}", docs, T::decl()));
+    }
+    T::visit_dependencies(self);
+    T::visit_generics(self);
+  }
+}
+
+#[derive(Default)]
+struct Generator {
+  declarations: Declarations,
+  commands: Vec<String>,
+  routes: Vec<String>,
+}
+
+impl Generator {
+  fn ty<T: TS + 'static + ?Sized>(&mut self) -> String {
+    self.declarations.visit::<T>();
+    if TypeId::of::<T>() == TypeId::of::<()>() {
+      "void".to_string()
+    } else {
+      T::name()
+    }
+  }
+
+  fn arg<T: TS + 'static>(&mut self, name: &str) -> String {
+    format!("{}: {}", name, self.ty::<T>())
+  }
+
+  /// `Option<T>` command argument; Tauri accepts it missing or null.
+  fn optional<T: TS + 'static>(&mut self, name: &str) -> String {
+    format!("{}?: {} | null", name, self.ty::<T>())
+  }
+
+  /// A `#[tauri::command]`; argument names are the camelCase keys Tauri expects.
+  fn command<R: TS + 'static>(&mut self, name: &str, args: Vec<String>) {
+    let returns = self.ty::<R>();
+    let method = operation_id(name);
+    let entry = if args.is_empty() {
+      format!("  {}: () => invokeCommand<{}>(\"{}\"),", method, returns, name)
+    } else {
+      format!(
+        "  {}: (args: {{ {} }}) => invokeCommand<{}>(\"{}\", args),",
+        method,
+        args.join("; "),
+        returns,
+        name
+      )
+    };
+    self.commands.push(entry);
+  }
+
+  fn finish(self) -> Bindings {
+    let mut types = String::from(HEADER);
+    for decl in self.declarations.decls.values() {
+      types.push('\n');
+      types.push_str(decl);
+      types.push('\n');
+    }
+    types.push('\n');
+    types.push_str(JSON_PATCH_DECL);
+    types.push('\n');
+
+    let body = format!(
+      "export const commands = {{\n{}\n}};\n\nexport const remote = {{\n{}\n}};\n",
+      self.commands.join("\n"),
+      self.routes.join("\n")
+    );
+    let words = body
+      .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+      .collect::<BTreeSet<_>>();
+    let imports = self
+      .declarations
+      .decls
+      .keys()
+      .map(String::as_str)
+      .chain(["JsonPatch"])
+      .filter(|ident| words.contains(ident))
+      .collect::<BTreeSet<_>>()
+      .into_iter()
+      .collect::<Vec<_>>()
+      .join(",\n  ");
+    let commands = format!(
+      "{}\nimport {{\n  remoteDownload,\n  remotePost,\n  remoteResource,\n  remoteUpload,\n  type RemoteResourceOptions,\n}} from \"../features/remote/client\";\nimport {{ invokeCommand }} from \"../lib/appError\";\nimport type {{\n  {},\n}} from \"./types\";\n\n{}",
+      HEADER, imports, body
+    );
+    Bindings { types, commands }
+  }
+}
+
+impl RouteSink for Generator {
+  fn post<I: ApiType, R: ApiType>(&mut self, path: &str, _summary: &str) {
+    let input = self.ty::<I>();
+    let returns = self.ty::<R>();
+    self.routes.push(format!(
+      "  {}: (input: {}) => remotePost<{}>(\"{}{}\", input),",
+      operation_id(path),
+      input,
+      returns,
+      API_PREFIX,
+      path
+    ));
+  }
+
+  fn post_without_input<R: ApiType>(&mut self, path: &str, _summary: &str) {
+    let returns = self.ty::<R>();
+    self.routes.push(format!(
+      "  {}: () => remotePost<{}>(\"{}{}\", {{}}),",
+      operation_id(path),
+      returns,
+      API_PREFIX,
+      path
+    ));
+  }
+
+  fn resource<I: ApiType, R: ApiType>(&mut self, method: &str, path: &str, name: &str, _summary: &str) {
+    let returns = self.ty::<R>();
+    let (url, mut args) = route_url(path);
+    let body = if TypeId::of::<I>() == TypeId::of::<()>() {
+      "undefined".to_string()
+    } else {
+      args.push(format!("body: {}", self.ty::<I>()));
+      "body".to_string()
+    };
+    args.push("options?: RemoteResourceOptions".to_string());
+    self.routes.push(format!(
+      "  {}: ({}) => remoteResource<{}>(\"{}\", `{}`, {}, options),",
+      name,
+      args.join(", "),
+      returns,
+      method.to_uppercase(),
+      url,
+      body
+    ));
+  }
+
+  fn upload<R: ApiType>(&mut self, path: &str, name: &str, _summary: &str) {
+    let returns = self.ty::<R>();
+    let (url, mut args) = route_url(path);
+    args.push("file: Blob".to_string());
+    args.push("fileName?: string".to_string());
+    self.routes.push(format!(
+      "  {}: ({}) => remoteUpload<{}>(`{}`, file, fileName),",
+      name,
+      args.join(", "),
+      returns,
+      url
+    ));
+  }
+
+  fn download(&mut self, path: &str, name: &str, _content_type: &str, _summary: &str) {
+    let (url, args) = route_url(path);
+    self.routes.push(format!("  {}: ({}) => remoteDownload(`{}`),", name, args.join(", "), url));
+  }
+
+  /// Streams are opened with `EventSource`; only the event type is exported.
+  fn stream<E: ApiType>(&mut self, _path: &str, _summary: &str) {
+    self.ty::<E>();
+  }
+}
+
+/// Template literal for a route with `{param}` segments, and the `params` argument that fills
+/// them (none for a fixed path).
+fn route_url(path: &str) -> (String, Vec<String>) {
+  let mut url = format!("{}{}", API_PREFIX, path);
+  for param in path_params(path) {
+    url = url.replace(&format!("{{{}}}", param), &format!("${{encodeURIComponent(params.{})}}", param));
+  }
+  let params = path_params(path).map(|param| format!("{}: string", param)).collect::<Vec<_>>();
+  let args = if params.is_empty() {
+    Vec::new()
+  } else {
+    vec![format!("params: {{ {} }}", params.join("; "))]
+  };
+  (url, args)
+}
+
+pub(crate) fn generate() -> Bindings {
+  let mut g = Generator::default();
+
+  // Types the UI receives outside a command result.
+  g.ty::<AppError>();
+  g.ty::<AgentError>();
+
+  let args = vec![g.arg::<String>("workspaceRoot")];
+  g.command::<Vec<ProjectRecord>>("list_projects", args);
+  let args = vec![g.arg::<String>("workspaceRoot"), g.optional::<ProjectQuery>("query")];
+  g.command::<ProjectPage>("query_projects", args);
+  let args = vec![
+    g.arg::<String>("workspaceRoot"),
+    g.arg::<String>("name"),
+    g.arg::<String>("slug"),
+    g.arg::<String>("siteUrl"),
+    g.optional::<String>("template"),
+    g.optional::<String>("clientName"),
+  ];
+  g.command::<ProjectRecord>("create_project", args);
+  let args = vec![g.arg::<String>("workspaceRoot")];
+  g.command::<Vec<ProjectTemplate>>("list_project_templates", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<String>("newName"),
+    g.arg::<String>("newSlug"),
+  ];
+  g.command::<ProjectRecord>("duplicate_project", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<String>("newName"),
+    g.arg::<String>("newSlug"),
+  ];
+  g.command::<ProjectRecord>("rename_project", args);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<ShelvedProject>("archive_project", args);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<ShelvedProject>("trash_project", args);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<ProjectRecord>("restore_project", args);
+  let args = vec![g.arg::<String>("workspaceRoot"), g.optional::<ProjectShelf>("shelf")];
+  g.command::<Vec<ShelvedProject>>("list_shelved_projects", args);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<DeletionRequest>("request_project_deletion", args);
+  let args = vec![g.arg::<String>("projectPath"), g.arg::<String>("confirmationToken")];
+  g.command::<()>("delete_project", args);
+  let args = vec![g.arg::<String>("projectPath"), g.arg::<String>("siteUrl")];
+  g.command::<ProjectRecord>("update_project_site_url", args);
+  let args = vec![g.arg::<String>("projectPath"), g.arg::<ProjectDetailsPatch>("details")];
+  g.command::<ProjectRecord>("update_project_details", args);
+  let args = vec![g.arg::<String>("projectPath"), g.optional::<u32>("limit")];
+  g.command::<Vec<ActivityEntry>>("get_project_activity", args);
+  g.command::<Option<String>>("pick_workspace_directory", vec![]);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<BuilderProjectDoc>("load_builder_project", args);
+  g.command::<BTreeMap<String, serde_json::Value>>("get_project_schemas", vec![]);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<ProjectFilesReport>("validate_project_files", args);
+  let args = vec![g.arg::<String>("projectPath"), g.optional::<String>("destination")];
+  g.command::<ArchiveExport>("export_project_archive", args);
+  let args = vec![g.arg::<String>("workspaceRoot"), g.arg::<String>("archivePath")];
+  g.command::<ProjectRecord>("import_project_archive", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<BuilderProjectDoc>("document"),
+    g.optional::<String>("clientId"),
+  ];
+  g.command::<()>("save_builder_project", args);
+  let args = vec![g.arg::<serde_json::Value>("request")];
+  g.command::<AgentResponse>("run_agent_command", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<AgentAccessMode>("mode"),
+    g.arg::<Vec<AgentOperation>>("operations"),
+  ];
+  g.command::<AgentSessionPolicy>("start_agent_session", args);
+  let args = vec![
+    g.arg::<String>("sessionId"),
+    g.optional::<AgentAccessMode>("mode"),
+    g.optional::<Vec<AgentOperation>>("operations"),
+  ];
+  g.command::<AgentSessionPolicy>("update_agent_session", args);
+  let args = vec![g.arg::<String>("sessionId")];
+  g.command::<AgentSessionPolicy>("get_agent_session", args);
+  let args = vec![g.arg::<String>("sessionId")];
+  g.command::<()>("end_agent_session", args);
+  let args = vec![g.optional::<String>("projectPath")];
+  g.command::<Vec<PolicyDenial>>("list_agent_denials", args);
+  let args = vec![g.arg::<String>("projectPath"), g.arg::<AgentRunRequest>("request")];
+  g.command::<AgentRunInfo>("start_agent_run", args);
+  let args = vec![g.arg::<String>("runId")];
+  g.command::<()>("cancel_agent_run", args);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<Vec<AgentRunInfo>>("list_agent_runs", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<String>("pageId"),
+    g.arg::<bool>("lock"),
+    g.optional::<String>("clientId"),
+    g.optional::<String>("displayName"),
+  ];
+  g.command::<Vec<PagePresence>>("open_builder_page", args);
+  let args = vec![g.arg::<String>("projectPath"), g.optional::<String>("clientId")];
+  g.command::<()>("close_builder_page", args);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<Vec<PagePresence>>("list_page_presence", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<String>("clientId"),
+    g.arg::<String>("displayName"),
+  ];
+  g.command::<CollabSnapshot>("collab_join", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<String>("clientId"),
+    g.arg::<Vec<DocOperation>>("operations"),
+  ];
+  g.command::<SubmitOutcome>("collab_submit", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<String>("clientId"),
+    "since: number".to_string(),
+  ];
+  g.command::<CollabChanges>("collab_changes", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<String>("clientId"),
+    g.optional::<String>("pageId"),
+    g.optional::<String>("blockId"),
+  ];
+  g.command::<Vec<Participant>>("collab_update_presence", args);
+  let args = vec![g.arg::<String>("projectPath"), g.arg::<String>("clientId")];
+  g.command::<()>("collab_leave", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    "patch: JsonPatch".to_string(),
+    g.optional::<String>("summary"),
+    g.optional::<String>("clientId"),
+  ];
+  g.command::<PatchProposal>("submit_patch_proposal", args);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<Vec<PatchProposal>>("list_patch_proposals", args);
+  let args = vec![g.arg::<String>("projectPath"), g.arg::<String>("proposalId")];
+  g.command::<ProposalOutcome>("approve_patch_proposal", args);
+  let args = vec![
+    g.arg::<String>("projectPath"),
+    g.arg::<String>("proposalId"),
+    g.optional::<String>("reason"),
+  ];
+  g.command::<PatchProposal>("reject_patch_proposal", args);
+  let args = vec![g.arg::<String>("projectPath")];
+  g.command::<ProposalOutcome>("revert_last_change", args);
+  let args = vec![
+    g.arg::<String>("host"),
+    g.arg::<u16>("port"),
+    g.arg::<String>("token"),
+    g.arg::<String>("workspaceRoot"),
+  ];
+  g.command::<RemoteServerStatus>("start_remote_server", args);
+  g.command::<RemoteServerStatus>("stop_remote_server", vec![]);
+  g.command::<RemoteServerStatus>("get_remote_server_status", vec![]);
+
+  describe_routes(&mut g);
+  g.ty::<HealthDoc>();
+
+  g.finish()
+}
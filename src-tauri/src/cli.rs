@@ -0,0 +1,714 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+
+use crate::bindings;
+use crate::error::AppError;
+use crate::events::{ChangeSource, ProjectEvent};
+use crate::policy::{AgentAccessMode, AgentOperation};
+use crate::project_index::{ProjectIndex, ProjectQuery, ProjectSort};
+use crate::schema::ProjectFilesReport;
+use crate::shelf::{delete_project, request_deletion, restore_project, shelve_project, ProjectShelf};
+use crate::validation::{lint_builder_doc, validate_builder_doc, IssueSeverity, ProjectIssue};
+use crate::{
+  bind_remote_server, create_project, default_frontend_dist, duplicate_project_dir, get_project_activity,
+  list_project_templates, list_shelved_projects, load_builder_project, query_workspace_projects, read_json_file,
+  rename_project_dir, set_project_details, set_project_site_url, stopped_remote_status,
+  validate_remote_settings, BoundRemoteServer, BuilderProjectDoc, LiveServices, ProjectColor,
+  ProjectDetailsPatch, ProjectRecord, RemoteApiState,
+};
+
+const DEFAULT_SERVE_PORT: u16 = 8787;
+
+/// Headless project tools for CI and terminals; shares every operation with the desktop app.
+#[derive(Debug, Parser)]
+#[command(name = "manifold", version, about = "Manifold project tools")]
+struct Cli {
+  /// Print machine-readable JSON instead of text.
+  #[arg(long, global = true)]
+  json: bool,
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// List projects in a workspace, most recently updated first unless `--sort` says otherwise.
+  List {
+    #[arg(long)]
+    workspace: String,
+    /// Case-insensitive text matched against name, slug and site URL.
+    #[arg(long)]
+    search: Option<String>,
+    /// Only projects carrying this tag; repeat to require several.
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+    /// Only this client's projects.
+    #[arg(long)]
+    client: Option<String>,
+    /// Only pinned (`true`) or unpinned (`false`) projects.
+    #[arg(long)]
+    pinned: Option<bool>,
+    #[arg(long, value_enum)]
+    sort: Option<ProjectSort>,
+    /// Reverse the order: Z–A for names and clients, oldest first for dates.
+    #[arg(long)]
+    reverse: bool,
+    #[arg(long)]
+    limit: Option<usize>,
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+  },
+  /// Create a new project in a workspace.
+  Create {
+    #[arg(long)]
+    workspace: String,
+    #[arg(long)]
+    name: String,
+    /// Directory slug; derived from the name when omitted.
+    #[arg(long)]
+    slug: Option<String>,
+    #[arg(long, default_value = "")]
+    site_url: String,
+    /// Template id from `manifold templates`; a single empty Home page when omitted.
+    #[arg(long)]
+    template: Option<String>,
+    /// Fills `{{clientName}}` in the template; defaults to the project name.
+    #[arg(long)]
+    client_name: Option<String>,
+  },
+  /// List templates `create --template` accepts, built-in and from `<workspace>/templates`.
+  Templates {
+    #[arg(long)]
+    workspace: String,
+  },
+  /// Copy a project as a new one next to it.
+  Duplicate {
+    #[arg(long)]
+    project: String,
+    #[arg(long)]
+    name: String,
+    /// Directory slug; derived from the name when omitted.
+    #[arg(long)]
+    slug: Option<String>,
+  },
+  /// Rename a project; its directory follows the new slug.
+  Rename {
+    #[arg(long)]
+    project: String,
+    #[arg(long)]
+    name: String,
+    /// Directory slug; derived from the name when omitted.
+    #[arg(long)]
+    slug: Option<String>,
+  },
+  /// Move a project to the workspace's `.archive/`, out of the default listing.
+  Archive {
+    #[arg(long)]
+    project: String,
+  },
+  /// Move a project to the workspace's `.trash/`.
+  Trash {
+    #[arg(long)]
+    project: String,
+  },
+  /// Move an archived or trashed project back into the workspace.
+  Restore {
+    #[arg(long)]
+    project: String,
+  },
+  /// List archived and trashed projects, most recently shelved first.
+  Shelved {
+    #[arg(long)]
+    workspace: String,
+    #[arg(long, value_enum)]
+    shelf: Option<ProjectShelf>,
+  },
+  /// Permanently delete a trashed project. Without `--confirm`, prints what would be deleted
+  /// and the token to confirm with.
+  Delete {
+    #[arg(long)]
+    project: String,
+    #[arg(long)]
+    confirm: Option<String>,
+  },
+  /// Show a project's recent changes, newest first.
+  Activity {
+    #[arg(long)]
+    project: String,
+    #[arg(long)]
+    limit: Option<usize>,
+  },
+  /// Change a project's tags, client, pin or color label; omitted options keep their value.
+  Details {
+    #[arg(long)]
+    project: String,
+    /// Comma-separated tags replacing the current ones; an empty value clears them.
+    #[arg(long, value_delimiter = ',')]
+    tags: Option<Vec<String>>,
+    /// Client to file the project under; an empty value removes it.
+    #[arg(long)]
+    client: Option<String>,
+    #[arg(long)]
+    pinned: Option<bool>,
+    #[arg(long, value_enum)]
+    color: Option<ProjectColor>,
+  },
+  /// Change a project's site URL.
+  SetSiteUrl {
+    #[arg(long)]
+    project: String,
+    #[arg(long)]
+    site_url: String,
+  },
+  /// Print a project's site settings and pages.
+  Show {
+    #[arg(long)]
+    project: String,
+  },
+  /// Check a project for structural errors; exits non-zero when any are found.
+  Validate {
+    #[arg(long)]
+    project: String,
+  },
+  /// Check project files against their JSON Schemas; exits non-zero on any violation.
+  ValidateFiles {
+    #[arg(long)]
+    project: String,
+  },
+  /// Package a project as a portable `.manifold.zip` archive.
+  Export {
+    #[arg(long)]
+    project: String,
+    /// Archive path; defaults to `<project>/exports/<slug>-<timestamp>.manifold.zip`.
+    #[arg(long)]
+    out: Option<PathBuf>,
+  },
+  /// Unpack a `.manifold.zip` archive into a new project in a workspace.
+  Import {
+    #[arg(long)]
+    workspace: String,
+    #[arg(long)]
+    archive: PathBuf,
+  },
+  /// Write the project file JSON Schemas, e.g. into `<project>/schemas`.
+  Schemas {
+    #[arg(long)]
+    out: PathBuf,
+  },
+  /// Report SEO, alt text and link hygiene warnings.
+  Lint {
+    #[arg(long)]
+    project: String,
+  },
+  /// Run the remote studio server without the desktop shell until SIGTERM or Ctrl-C.
+  Serve(ServeArgs),
+  /// Serve project tools to an MCP client over stdio, confined to one project. Read-only
+  /// with validate/lint enabled unless `--write` and `--allow` say otherwise.
+  Mcp {
+    #[arg(long)]
+    project: String,
+    /// Let tools write studio files.
+    #[arg(long)]
+    write: bool,
+    /// Operation families to enable; repeatable.
+    #[arg(long, value_enum)]
+    allow: Vec<AgentOperation>,
+  },
+  /// Write TypeScript types and a typed command client for the UI.
+  Bindings {
+    #[arg(long, default_value = "src-ui/src/bindings")]
+    out: PathBuf,
+    /// Fail instead of writing when the checked-in files are out of date.
+    #[arg(long)]
+    check: bool,
+  },
+}
+
+/// Flags override values from `--config`.
+#[derive(Debug, Args)]
+struct ServeArgs {
+  /// JSON file with any of: host, port, token, workspaceRoot, frontendDir.
+  #[arg(long)]
+  config: Option<PathBuf>,
+  #[arg(long)]
+  workspace: Option<String>,
+  #[arg(long)]
+  token: Option<String>,
+  #[arg(long)]
+  host: Option<String>,
+  #[arg(long)]
+  port: Option<u16>,
+  /// Built studio UI to serve to remote browsers.
+  #[arg(long)]
+  frontend_dir: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServeConfigFile {
+  host: Option<String>,
+  port: Option<u16>,
+  token: Option<String>,
+  workspace_root: Option<String>,
+  frontend_dir: Option<String>,
+}
+
+struct CommandOutput {
+  json: serde_json::Value,
+  text: String,
+  success: bool,
+}
+
+impl CommandOutput {
+  fn new<T: Serialize>(value: &T, text: String) -> Result<Self, AppError> {
+    let json = serde_json::to_value(value)
+      .map_err(|err| AppError::internal(format!("Failed serializing output: {}", err)))?;
+    Ok(Self {
+      json,
+      text,
+      success: true,
+    })
+  }
+
+  /// Report printed normally but with a failing exit code.
+  fn failed(mut self) -> Self {
+    self.success = false;
+    self
+  }
+}
+
+pub fn run() -> ExitCode {
+  let cli = Cli::parse();
+  match execute(cli.command, cli.json) {
+    Ok(output) => {
+      if cli.json && !output.json.is_null() {
+        println!("{}", serde_json::to_string_pretty(&output.json).unwrap_or_default());
+      } else if !output.text.is_empty() {
+        println!("{}", output.text);
+      }
+      if output.success {
+        ExitCode::SUCCESS
+      } else {
+        ExitCode::FAILURE
+      }
+    }
+    Err(err) => {
+      if cli.json {
+        println!("{}", serde_json::json!({ "error": err }));
+      } else {
+        eprintln!("error: {}", err);
+      }
+      ExitCode::FAILURE
+    }
+  }
+}
+
+fn execute(command: Command, json: bool) -> Result<CommandOutput, AppError> {
+  match command {
+    Command::List {
+      workspace,
+      search,
+      tags,
+      client,
+      pinned,
+      sort,
+      reverse,
+      limit,
+      offset,
+    } => {
+      let query = ProjectQuery {
+        search: search.unwrap_or_default(),
+        tags,
+        client_name: client,
+        pinned,
+        sort: sort.unwrap_or_default(),
+        reverse,
+        limit,
+        offset,
+      };
+      let page = query_workspace_projects(&ProjectIndex::default(), &workspace, &query)?;
+      let mut lines: Vec<String> = page.projects.iter().map(project_line).collect();
+      if page.projects.len() < page.total {
+        lines.push(format!(
+          "Showing {} of {} projects from offset {}",
+          page.projects.len(),
+          page.total,
+          page.offset
+        ));
+      }
+      CommandOutput::new(&page.projects, lines.join("\n"))
+    }
+    Command::Create {
+      workspace,
+      name,
+      slug,
+      site_url,
+      template,
+      client_name,
+    } => {
+      let slug = slug.unwrap_or_else(|| name.clone());
+      let record = create_project(workspace, name, slug, site_url, template, client_name)?;
+      let text = format!("Created {}", project_line(&record));
+      CommandOutput::new(&record, text)
+    }
+    Command::Templates { workspace } => {
+      let templates = list_project_templates(workspace)?;
+      let text = templates
+        .iter()
+        .map(|template| format!("{}\t{}\t{}", template.id, template.name, template.pages.join(", ")))
+        .collect::<Vec<_>>()
+        .join("\n");
+      CommandOutput::new(&templates, text)
+    }
+    Command::Duplicate { project, name, slug } => {
+      let slug = slug.unwrap_or_else(|| name.clone());
+      let services = LiveServices::new();
+      let record = duplicate_project_dir(&services, Path::new(&project), &name, &slug, ChangeSource::Cli)?;
+      let text = format!("Created {}", project_line(&record));
+      CommandOutput::new(&record, text)
+    }
+    Command::Rename { project, name, slug } => {
+      let slug = slug.unwrap_or_else(|| name.clone());
+      let services = LiveServices::new();
+      let record = rename_project_dir(&services, Path::new(&project), &name, &slug, ChangeSource::Cli)?;
+      let text = format!("Renamed {}", project_line(&record));
+      CommandOutput::new(&record, text)
+    }
+    Command::Archive { project } => {
+      let services = LiveServices::new();
+      let project_dir = Path::new(&project);
+      let shelved = shelve_project(&services, project_dir, ProjectShelf::Archive, ChangeSource::Cli)?;
+      let text = format!("Archived {}", project_line(&shelved.project));
+      CommandOutput::new(&shelved, text)
+    }
+    Command::Trash { project } => {
+      let services = LiveServices::new();
+      let project_dir = Path::new(&project);
+      let shelved = shelve_project(&services, project_dir, ProjectShelf::Trash, ChangeSource::Cli)?;
+      let text = format!("Trashed {}", project_line(&shelved.project));
+      CommandOutput::new(&shelved, text)
+    }
+    Command::Restore { project } => {
+      let services = LiveServices::new();
+      let record = restore_project(&services, Path::new(&project), ChangeSource::Cli)?;
+      let text = format!("Restored {}", project_line(&record));
+      CommandOutput::new(&record, text)
+    }
+    Command::Shelved { workspace, shelf } => {
+      let projects = list_shelved_projects(workspace, shelf)?;
+      let text = projects
+        .iter()
+        .map(|shelved| {
+          let shelf = match shelved.shelf {
+            ProjectShelf::Archive => "archive",
+            ProjectShelf::Trash => "trash",
+          };
+          format!("{}\t{}\t{}", shelf, shelved.shelved_at, project_line(&shelved.project))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+      CommandOutput::new(&projects, text)
+    }
+    Command::Delete { project, confirm } => {
+      let project_dir = Path::new(&project);
+      let Some(token) = confirm else {
+        let request = request_deletion(project_dir)?;
+        let text = format!(
+          "Would delete {} files ({} bytes) in {}\nRe-run with --confirm {} to delete it.",
+          request.files, request.bytes, request.project.path, request.confirmation_token
+        );
+        return Ok(CommandOutput::new(&request, text)?.failed());
+      };
+      let services = LiveServices::new();
+      delete_project(&services, project_dir, &token, ChangeSource::Cli)?;
+      let text = format!("Deleted {}", project);
+      CommandOutput::new(&serde_json::json!({ "deleted": project }), text)
+    }
+    Command::Activity { project, limit } => {
+      let entries = get_project_activity(project, limit)?;
+      let text = entries
+        .iter()
+        .map(|entry| {
+          let source = match entry.source {
+            ChangeSource::Desktop => "desktop",
+            ChangeSource::Cli => "cli",
+            ChangeSource::Remote => "remote",
+            ChangeSource::Agent => "agent",
+          };
+          format!("{}\t{}\t{}\t{}", entry.at, entry.operation.label(), source, entry.pages.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+      CommandOutput::new(&entries, text)
+    }
+    Command::Details {
+      project,
+      tags,
+      client,
+      pinned,
+      color,
+    } => {
+      let details = ProjectDetailsPatch {
+        tags,
+        client_name: client,
+        pinned,
+        color,
+      };
+      let record = set_project_details(Path::new(&project), details, ChangeSource::Cli)?;
+      let text = format!("Updated {}", project_line(&record));
+      CommandOutput::new(&record, text)
+    }
+    Command::SetSiteUrl { project, site_url } => {
+      let record = set_project_site_url(Path::new(&project), &site_url, ChangeSource::Cli)?;
+      let text = format!("Updated {}", project_line(&record));
+      CommandOutput::new(&record, text)
+    }
+    Command::Show { project } => {
+      let doc = load_builder_project(project)?;
+      CommandOutput::new(&doc, describe_builder_doc(&doc))
+    }
+    Command::Validate { project } => {
+      let doc = load_builder_project(project)?;
+      let issues = validate_builder_doc(&doc);
+      let valid = !issues.iter().any(|issue| issue.severity == IssueSeverity::Error);
+      let text = describe_issues(&issues);
+      let output = CommandOutput::new(&serde_json::json!({ "valid": valid, "issues": issues }), text)?;
+      Ok(if valid { output } else { output.failed() })
+    }
+    Command::ValidateFiles { project } => {
+      let report = crate::schema::validate_project_files(Path::new(&project))?;
+      let text = describe_file_report(&report);
+      let output = CommandOutput::new(&report, text)?;
+      Ok(if report.valid { output } else { output.failed() })
+    }
+    Command::Export { project, out } => {
+      let export = crate::archive::export_project_archive(Path::new(&project), out.as_deref())?;
+      let text = format!(
+        "Exported {} files ({} bytes) to {}",
+        export.manifest.files.len(),
+        export.size,
+        export.path
+      );
+      CommandOutput::new(&export, text)
+    }
+    Command::Import { workspace, archive } => {
+      let record = crate::archive::import_project_archive(Path::new(&workspace), &archive)?;
+      let text = format!("Imported {}", project_line(&record));
+      CommandOutput::new(&record, text)
+    }
+    Command::Schemas { out } => {
+      crate::schema::write_schemas(&out)?;
+      let text = format!("Wrote schemas to {}", out.display());
+      CommandOutput::new(&crate::schema::project_schemas(), text)
+    }
+    Command::Lint { project } => {
+      let doc = load_builder_project(project)?;
+      let issues = lint_builder_doc(&doc);
+      let text = describe_issues(&issues);
+      CommandOutput::new(&serde_json::json!({ "issues": issues }), text)
+    }
+    Command::Serve(args) => serve(args, json),
+    Command::Mcp {
+      project,
+      write,
+      allow,
+    } => {
+      let mode = if write {
+        AgentAccessMode::Write
+      } else {
+        AgentAccessMode::ReadOnly
+      };
+      let operations = if allow.is_empty() {
+        vec![AgentOperation::RunTools]
+      } else {
+        allow
+      };
+      crate::mcp::run_stdio(Path::new(&project), mode, operations)?;
+      Ok(CommandOutput {
+        json: serde_json::Value::Null,
+        text: String::new(),
+        success: true,
+      })
+    }
+    Command::Bindings { out, check } => write_bindings(&out, check),
+  }
+}
+
+fn write_bindings(out_dir: &Path, check: bool) -> Result<CommandOutput, AppError> {
+  let bindings = bindings::generate();
+  let stale = bindings.stale_files(out_dir);
+  if check {
+    let text = if stale.is_empty() {
+      "Bindings are up to date".to_string()
+    } else {
+      format!("Out of date, run `manifold bindings`:\n{}", stale.join("\n"))
+    };
+    let output = CommandOutput::new(&serde_json::json!({ "stale": stale }), text)?;
+    return Ok(if stale.is_empty() { output } else { output.failed() });
+  }
+  fs::create_dir_all(out_dir).map_err(|err| AppError::fs("Failed creating", out_dir, err))?;
+  for (name, contents) in [
+    (bindings::TYPES_FILE, &bindings.types),
+    (bindings::COMMANDS_FILE, &bindings.commands),
+  ] {
+    let path = out_dir.join(name);
+    fs::write(&path, contents).map_err(|err| AppError::fs("Failed writing", &path, err))?;
+  }
+  let text = format!("Wrote bindings to {}", out_dir.display());
+  CommandOutput::new(&serde_json::json!({ "updated": stale }), text)
+}
+
+fn describe_issues(issues: &[ProjectIssue]) -> String {
+  if issues.is_empty() {
+    return "No issues found".to_string();
+  }
+  issues
+    .iter()
+    .map(|issue| {
+      let severity = match issue.severity {
+        IssueSeverity::Error => "error",
+        IssueSeverity::Warning => "warning",
+      };
+      let location = match (&issue.page_id, &issue.block_id) {
+        (Some(page_id), Some(block_id)) => format!(" [{} / {}]", page_id, block_id),
+        (Some(page_id), None) => format!(" [{}]", page_id),
+        _ => "".to_string(),
+      };
+      format!("{} {}{}: {}", severity, issue.code, location, issue.message)
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+fn describe_file_report(report: &ProjectFilesReport) -> String {
+  let lines = report
+    .files
+    .iter()
+    .flat_map(|file| {
+      file.violations.iter().map(move |violation| {
+        let pointer = if violation.pointer.is_empty() {
+          "/"
+        } else {
+          violation.pointer.as_str()
+        };
+        format!("{} {}: {}", file.file, pointer, violation.message)
+      })
+    })
+    .collect::<Vec<_>>();
+  if lines.is_empty() {
+    format!("{} files match their schemas", report.files.len())
+  } else {
+    lines.join("\n")
+  }
+}
+
+fn serve(args: ServeArgs, json: bool) -> Result<CommandOutput, AppError> {
+  let config = match &args.config {
+    Some(path) => read_json_file::<ServeConfigFile>(path)?,
+    None => ServeConfigFile::default(),
+  };
+  let token = args.token.or(config.token).unwrap_or_default();
+  let workspace_root = args.workspace.or(config.workspace_root).unwrap_or_default();
+  let host = args.host.or(config.host).unwrap_or_default();
+  let port = args.port.or(config.port).unwrap_or(DEFAULT_SERVE_PORT);
+  let frontend_dist = args
+    .frontend_dir
+    .or(config.frontend_dir)
+    .map(PathBuf::from)
+    .unwrap_or_else(default_frontend_dist);
+  validate_remote_settings(&token, &workspace_root)?;
+
+  let runtime = tokio::runtime::Builder::new_multi_thread()
+    .enable_all()
+    .build()
+    .map_err(|err| AppError::internal(format!("Failed starting async runtime: {}", err)))?;
+  runtime.block_on(async move {
+    let services = LiveServices::new();
+    let api_state = RemoteApiState {
+      token,
+      workspace_root: workspace_root.trim().to_string(),
+      frontend_dist,
+      services: services.clone(),
+    };
+    let BoundRemoteServer {
+      listener,
+      app,
+      status,
+    } = bind_remote_server(&host, port, api_state).await?;
+    if json {
+      println!("{}", serde_json::to_string(&status).unwrap_or_default());
+    } else {
+      println!("Remote server listening on {}", status.server_url);
+    }
+
+    let events = services.events.clone();
+    axum::serve(listener, app)
+      .with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        // Open event streams close on the stopped status, letting graceful shutdown finish.
+        events.publish(ProjectEvent::ServerStatusChanged {
+          status: stopped_remote_status(),
+        });
+      })
+      .await
+      .map_err(|err| AppError::internal(format!("Remote server terminated: {}", err)))?;
+
+    let stopped = stopped_remote_status();
+    CommandOutput::new(&stopped, "Remote server stopped".to_string())
+  })
+}
+
+async fn shutdown_signal() {
+  let ctrl_c = async {
+    let _ = tokio::signal::ctrl_c().await;
+  };
+  #[cfg(unix)]
+  let terminate = async {
+    match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+      Ok(mut signal) => {
+        signal.recv().await;
+      }
+      Err(err) => {
+        eprintln!("warning: SIGTERM handler unavailable: {}", err);
+        std::future::pending::<()>().await;
+      }
+    }
+  };
+  #[cfg(not(unix))]
+  let terminate = std::future::pending::<()>();
+  tokio::select! {
+    _ = ctrl_c => {}
+    _ = terminate => {}
+  }
+}
+
+fn project_line(record: &ProjectRecord) -> String {
+  format!("{}\t{}\t{}\t{}", record.name, record.site_url, record.updated_at, record.path)
+}
+
+fn describe_builder_doc(doc: &BuilderProjectDoc) -> String {
+  let mut lines = vec![
+    format!("Site: {}", doc.site.site_name),
+    format!("Base URL: {}", doc.site.base_url),
+    "Pages:".to_string(),
+  ];
+  for page in &doc.pages {
+    let root = if page.id == doc.sitemap.root_page_id {
+      " (root)"
+    } else {
+      ""
+    };
+    lines.push(format!(
+      "  {}\t{}\t{} blocks{}",
+      page.id,
+      page.route,
+      page.blocks.len(),
+      root
+    ));
+  }
+  lines.join("\n")
+}
@@ -0,0 +1,645 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::activity::ActivityOperation;
+use crate::error::AppError;
+use crate::events::{ChangeSource, EventHub, ProjectEvent};
+use crate::locks::ProjectWriteLocks;
+use crate::{
+  commit_builder_doc, load_builder_doc, page_changes, project_path_string, BlockDoc, BuilderProjectDoc,
+};
+
+const PRESENCE_TIMEOUT: Duration = Duration::from_secs(45);
+const OPERATION_LOG_LIMIT: usize = 500;
+
+/// Edits exchanged by co-editing clients. Targets are addressed by page/block id rather than
+/// index, so operations built against an older version still land on the intended block.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub(crate) enum DocOperation {
+  #[serde(rename_all = "camelCase")]
+  SetBlockProp {
+    page_id: String,
+    block_id: String,
+    key: String,
+    value: serde_json::Value,
+  },
+  #[serde(rename_all = "camelCase")]
+  RemoveBlockProp {
+    page_id: String,
+    block_id: String,
+    key: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  SetBlockStyle {
+    page_id: String,
+    block_id: String,
+    field: String,
+    value: serde_json::Value,
+  },
+  #[serde(rename_all = "camelCase")]
+  SetBlockVisibility {
+    page_id: String,
+    block_id: String,
+    visibility: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  InsertBlock {
+    page_id: String,
+    after_block_id: Option<String>,
+    block: Box<BlockDoc>,
+  },
+  #[serde(rename_all = "camelCase")]
+  RemoveBlock { page_id: String, block_id: String },
+  #[serde(rename_all = "camelCase")]
+  MoveBlock {
+    page_id: String,
+    block_id: String,
+    after_block_id: Option<String>,
+  },
+  #[serde(rename_all = "camelCase")]
+  SetPageTitle { page_id: String, title: String },
+  #[serde(rename_all = "camelCase")]
+  SetPageSeo {
+    page_id: String,
+    title: Option<String>,
+    description: Option<String>,
+  },
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppliedOperation {
+  #[ts(type = "number")]
+  version: u64,
+  client_id: String,
+  operation: DocOperation,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RejectedOperation {
+  index: usize,
+  reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Participant {
+  client_id: String,
+  display_name: String,
+  page_id: Option<String>,
+  block_id: Option<String>,
+  #[serde(skip)]
+  last_seen: Instant,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CollabSnapshot {
+  project_path: String,
+  #[ts(type = "number")]
+  version: u64,
+  document: BuilderProjectDoc,
+  participants: Vec<Participant>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SubmitOutcome {
+  #[ts(type = "number")]
+  version: u64,
+  applied: Vec<AppliedOperation>,
+  rejected: Vec<RejectedOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CollabChanges {
+  #[ts(type = "number")]
+  version: u64,
+  operations: Vec<AppliedOperation>,
+  /// Set when the requested version fell out of the operation log; clients replace their copy.
+  document: Option<BuilderProjectDoc>,
+  participants: Vec<Participant>,
+}
+
+struct CollabSession {
+  project_dir: PathBuf,
+  document: BuilderProjectDoc,
+  version: u64,
+  log: VecDeque<AppliedOperation>,
+  participants: HashMap<String, Participant>,
+}
+
+impl CollabSession {
+  fn participants(&self) -> Vec<Participant> {
+    let mut participants: Vec<Participant> = self.participants.values().cloned().collect();
+    participants.sort_by(|a, b| a.client_id.cmp(&b.client_id));
+    participants
+  }
+
+  /// Drops participants that stopped polling; returns true when presence changed.
+  fn prune_participants(&mut self) -> bool {
+    let before = self.participants.len();
+    self
+      .participants
+      .retain(|_, participant| participant.last_seen.elapsed() < PRESENCE_TIMEOUT);
+    before != self.participants.len()
+  }
+
+  fn touch(&mut self, client_id: &str) -> Result<(), AppError> {
+    let participant = self.participants.get_mut(client_id).ok_or_else(|| {
+      AppError::conflict(format!("Client {} has not joined this collaboration session.", client_id))
+        .with_field("clientId")
+    })?;
+    participant.last_seen = Instant::now();
+    Ok(())
+  }
+}
+
+/// Shared co-editing sessions keyed by project path. One authoritative document lives on the
+/// server; operations are applied in arrival order, so concurrent edits to different blocks or
+/// props merge and concurrent writes to the same prop resolve last-writer-wins.
+#[derive(Clone)]
+pub(crate) struct CollabHub {
+  sessions: Arc<Mutex<HashMap<String, CollabSession>>>,
+  events: EventHub,
+  writes: ProjectWriteLocks,
+}
+
+impl CollabHub {
+  pub(crate) fn new(events: EventHub, writes: ProjectWriteLocks) -> Self {
+    Self {
+      sessions: Arc::new(Mutex::new(HashMap::new())),
+      events,
+      writes,
+    }
+  }
+
+  fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, CollabSession>>, AppError> {
+    self.sessions.lock().map_err(|_| AppError::lock("Collaboration"))
+  }
+
+  fn publish_presence(&self, project_path: &str, session: &CollabSession) {
+    self.events.publish(ProjectEvent::CollabPresence {
+      project_path: project_path.to_string(),
+      participants: session.participants(),
+    });
+  }
+
+  pub(crate) fn join(
+    &self,
+    project_dir: &Path,
+    client_id: &str,
+    display_name: &str,
+  ) -> Result<CollabSnapshot, AppError> {
+    if client_id.trim().is_empty() {
+      return Err(AppError::invalid("Collaboration client id is required.").with_field("clientId"));
+    }
+    let project_path = project_path_string(project_dir)?;
+    let mut sessions = self.lock()?;
+    if !sessions.contains_key(&project_path) {
+      let document = load_builder_doc(project_dir)?;
+      sessions.insert(
+        project_path.clone(),
+        CollabSession {
+          project_dir: project_dir.to_path_buf(),
+          document,
+          version: 0,
+          log: VecDeque::new(),
+          participants: HashMap::new(),
+        },
+      );
+    }
+    let session = sessions
+      .get_mut(&project_path)
+      .ok_or_else(|| AppError::internal("Collaboration session missing."))?;
+    session.prune_participants();
+    let display_name = if display_name.trim().is_empty() {
+      client_id.trim().to_string()
+    } else {
+      display_name.trim().to_string()
+    };
+    session.participants.insert(
+      client_id.to_string(),
+      Participant {
+        client_id: client_id.to_string(),
+        display_name,
+        page_id: None,
+        block_id: None,
+        last_seen: Instant::now(),
+      },
+    );
+    self.publish_presence(&project_path, session);
+    Ok(CollabSnapshot {
+      project_path,
+      version: session.version,
+      document: session.document.clone(),
+      participants: session.participants(),
+    })
+  }
+
+  pub(crate) fn submit(
+    &self,
+    project_dir: &Path,
+    client_id: &str,
+    operations: Vec<DocOperation>,
+    source: ChangeSource,
+  ) -> Result<SubmitOutcome, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    // The write lock comes first, as for every document write, then the session.
+    self.writes.run(project_dir, || {
+      let mut sessions = self.lock()?;
+      let session = sessions
+        .get_mut(&project_path)
+        .ok_or_else(|| no_session(project_dir))?;
+      session.touch(client_id)?;
+
+      // Work on a copy so a failed save leaves the session matching what is on disk.
+      let mut document = session.document.clone();
+      let mut version = session.version;
+      let mut applied = Vec::new();
+      let mut rejected = Vec::new();
+      for (index, operation) in operations.into_iter().enumerate() {
+        match apply_operation(&mut document, &operation) {
+          Ok(()) => {
+            version += 1;
+            applied.push(AppliedOperation {
+              version,
+              client_id: client_id.to_string(),
+              operation,
+            });
+          }
+          Err(reason) => rejected.push(RejectedOperation { index, reason }),
+        }
+      }
+
+      if !applied.is_empty() {
+        // Co-editors coordinate through the session rather than page locks, so there is no
+        // lock check here; the rest matches an editor save.
+        let changes = page_changes(&session.project_dir, &document)?;
+        commit_builder_doc(
+          &session.project_dir,
+          &document,
+          changes,
+          &self.events,
+          source,
+          ActivityOperation::CollabEdit,
+        )?;
+        session.document = document;
+        session.version = version;
+        session.log.extend(applied.iter().cloned());
+        while session.log.len() > OPERATION_LOG_LIMIT {
+          session.log.pop_front();
+        }
+        self.events.publish(ProjectEvent::CollabOperations {
+          project_path: project_path.clone(),
+          version: session.version,
+          operations: applied.clone(),
+        });
+      }
+      if session.prune_participants() {
+        self.publish_presence(&project_path, session);
+      }
+      Ok(SubmitOutcome {
+        version: session.version,
+        applied,
+        rejected,
+      })
+    })
+  }
+
+  pub(crate) fn changes_since(
+    &self,
+    project_dir: &Path,
+    client_id: &str,
+    since: u64,
+  ) -> Result<CollabChanges, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let mut sessions = self.lock()?;
+    let session = sessions
+      .get_mut(&project_path)
+      .ok_or_else(|| no_session(project_dir))?;
+    session.touch(client_id)?;
+    if session.prune_participants() {
+      self.publish_presence(&project_path, session);
+    }
+
+    let oldest_logged = session.log.front().map(|entry| entry.version);
+    let covered = since >= session.version || oldest_logged.is_some_and(|oldest| since + 1 >= oldest);
+    let (operations, document) = if covered {
+      let operations = session
+        .log
+        .iter()
+        .filter(|entry| entry.version > since)
+        .cloned()
+        .collect();
+      (operations, None)
+    } else {
+      (Vec::new(), Some(session.document.clone()))
+    };
+    Ok(CollabChanges {
+      version: session.version,
+      operations,
+      document,
+      participants: session.participants(),
+    })
+  }
+
+  pub(crate) fn update_presence(
+    &self,
+    project_dir: &Path,
+    client_id: &str,
+    page_id: Option<String>,
+    block_id: Option<String>,
+  ) -> Result<Vec<Participant>, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let mut sessions = self.lock()?;
+    let session = sessions
+      .get_mut(&project_path)
+      .ok_or_else(|| no_session(project_dir))?;
+    session.touch(client_id)?;
+    session.prune_participants();
+    if let Some(participant) = session.participants.get_mut(client_id) {
+      participant.page_id = page_id;
+      participant.block_id = block_id;
+    }
+    self.publish_presence(&project_path, session);
+    Ok(session.participants())
+  }
+
+  pub(crate) fn leave(&self, project_dir: &Path, client_id: &str) -> Result<(), AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let mut sessions = self.lock()?;
+    let Some(session) = sessions.get_mut(&project_path) else {
+      return Ok(());
+    };
+    session.participants.remove(client_id);
+    session.prune_participants();
+    self.publish_presence(&project_path, session);
+    if session.participants.is_empty() {
+      sessions.remove(&project_path);
+    }
+    Ok(())
+  }
+
+  /// Re-reads the document after a whole-document save so participants resync instead of
+  /// overwriting it with stale state.
+  pub(crate) fn reload(&self, project_dir: &Path) -> Result<(), AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let mut sessions = self.lock()?;
+    let Some(session) = sessions.get_mut(&project_path) else {
+      return Ok(());
+    };
+    session.document = load_builder_doc(project_dir)?;
+    session.version += 1;
+    session.log.clear();
+    Ok(())
+  }
+
+  /// Follows a renamed project directory. Participants stay joined and pick up the new path
+  /// from the `projectRenamed` event.
+  pub(crate) fn move_project(&self, from: &Path, to: &Path) -> Result<(), AppError> {
+    let from_path = project_path_string(from)?;
+    let to_path = project_path_string(to)?;
+    let mut sessions = self.lock()?;
+    if let Some(mut session) = sessions.remove(&from_path) {
+      session.project_dir = to.to_path_buf();
+      sessions.insert(to_path, session);
+    }
+    Ok(())
+  }
+}
+
+fn no_session(project_dir: &Path) -> AppError {
+  AppError::not_found("No collaboration session is open for this project.").with_path(project_dir)
+}
+
+fn find_block<'a>(
+  doc: &'a mut BuilderProjectDoc,
+  page_id: &str,
+  block_id: &str,
+) -> Result<&'a mut BlockDoc, String> {
+  let page = doc
+    .pages
+    .iter_mut()
+    .find(|page| page.id == page_id)
+    .ok_or_else(|| format!("Page {} no longer exists.", page_id))?;
+  page
+    .blocks
+    .iter_mut()
+    .find(|block| block.id == block_id)
+    .ok_or_else(|| format!("Block {} no longer exists on page {}.", block_id, page_id))
+}
+
+fn find_page_blocks<'a>(doc: &'a mut BuilderProjectDoc, page_id: &str) -> Result<&'a mut Vec<BlockDoc>, String> {
+  doc
+    .pages
+    .iter_mut()
+    .find(|page| page.id == page_id)
+    .map(|page| &mut page.blocks)
+    .ok_or_else(|| format!("Page {} no longer exists.", page_id))
+}
+
+/// Position right after the anchor block. A missing anchor (deleted concurrently) appends.
+fn insertion_index(blocks: &[BlockDoc], after_block_id: Option<&str>) -> usize {
+  match after_block_id {
+    None => 0,
+    Some(anchor) => blocks
+      .iter()
+      .position(|block| block.id == anchor)
+      .map(|index| index + 1)
+      .unwrap_or(blocks.len()),
+  }
+}
+
+fn apply_operation(doc: &mut BuilderProjectDoc, operation: &DocOperation) -> Result<(), String> {
+  match operation {
+    DocOperation::SetBlockProp {
+      page_id,
+      block_id,
+      key,
+      value,
+    } => {
+      let block = find_block(doc, page_id, block_id)?;
+      if !block.props.is_object() {
+        block.props = serde_json::json!({});
+      }
+      if let Some(props) = block.props.as_object_mut() {
+        props.insert(key.clone(), value.clone());
+      }
+    }
+    DocOperation::RemoveBlockProp {
+      page_id,
+      block_id,
+      key,
+    } => {
+      let block = find_block(doc, page_id, block_id)?;
+      if let Some(props) = block.props.as_object_mut() {
+        props.remove(key);
+      }
+    }
+    DocOperation::SetBlockStyle {
+      page_id,
+      block_id,
+      field,
+      value,
+    } => {
+      let block = find_block(doc, page_id, block_id)?;
+      let mut style = serde_json::to_value(&block.style_overrides)
+        .map_err(|err| format!("Failed serializing block style: {}", err))?;
+      let Some(fields) = style.as_object_mut() else {
+        return Err("Block style is not an object.".to_string());
+      };
+      if !fields.contains_key(field) {
+        return Err(format!("Unknown block style field {}.", field));
+      }
+      fields.insert(field.clone(), value.clone());
+      block.style_overrides =
+        serde_json::from_value(style).map_err(|err| format!("Invalid value for style field {}: {}", field, err))?;
+    }
+    DocOperation::SetBlockVisibility {
+      page_id,
+      block_id,
+      visibility,
+    } => {
+      find_block(doc, page_id, block_id)?.visibility = visibility.clone();
+    }
+    DocOperation::InsertBlock {
+      page_id,
+      after_block_id,
+      block,
+    } => {
+      let blocks = find_page_blocks(doc, page_id)?;
+      if blocks.iter().any(|existing| existing.id == block.id) {
+        return Err(format!("Block {} already exists on page {}.", block.id, page_id));
+      }
+      let index = insertion_index(blocks, after_block_id.as_deref());
+      blocks.insert(index, block.as_ref().clone());
+    }
+    DocOperation::RemoveBlock { page_id, block_id } => {
+      let blocks = find_page_blocks(doc, page_id)?;
+      let index = blocks
+        .iter()
+        .position(|block| &block.id == block_id)
+        .ok_or_else(|| format!("Block {} no longer exists on page {}.", block_id, page_id))?;
+      blocks.remove(index);
+    }
+    DocOperation::MoveBlock {
+      page_id,
+      block_id,
+      after_block_id,
+    } => {
+      if after_block_id.as_deref() == Some(block_id.as_str()) {
+        return Err("A block cannot be moved after itself.".to_string());
+      }
+      let blocks = find_page_blocks(doc, page_id)?;
+      let index = blocks
+        .iter()
+        .position(|block| &block.id == block_id)
+        .ok_or_else(|| format!("Block {} no longer exists on page {}.", block_id, page_id))?;
+      let block = blocks.remove(index);
+      let target = insertion_index(blocks, after_block_id.as_deref());
+      blocks.insert(target, block);
+    }
+    DocOperation::SetPageTitle { page_id, title } => {
+      let page = doc
+        .pages
+        .iter_mut()
+        .find(|page| &page.id == page_id)
+        .ok_or_else(|| format!("Page {} no longer exists.", page_id))?;
+      page.title = title.clone();
+    }
+    DocOperation::SetPageSeo {
+      page_id,
+      title,
+      description,
+    } => {
+      let page = doc
+        .pages
+        .iter_mut()
+        .find(|page| &page.id == page_id)
+        .ok_or_else(|| format!("Page {} no longer exists.", page_id))?;
+      if let Some(title) = title {
+        page.seo.title = title.clone();
+      }
+      if let Some(description) = description {
+        page.seo.description = description.clone();
+      }
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::PROJECT_META_FILE;
+
+  fn rename_home(title: &str) -> DocOperation {
+    DocOperation::SetPageTitle {
+      page_id: "home".to_string(),
+      title: title.to_string(),
+    }
+  }
+
+  #[test]
+  fn a_failed_save_leaves_the_session_unchanged() {
+    let workspace = tempfile::tempdir().unwrap();
+    let project_dir = crate::test_project(workspace.path());
+    let collab = CollabHub::new(EventHub::default(), ProjectWriteLocks::default());
+    let joined = collab.join(&project_dir, "tab", "Tab").unwrap();
+
+    let metadata = std::fs::read(project_dir.join(PROJECT_META_FILE)).unwrap();
+    std::fs::remove_file(project_dir.join(PROJECT_META_FILE)).unwrap();
+    assert!(collab
+      .submit(&project_dir, "tab", vec![rename_home("Lost")], ChangeSource::Desktop)
+      .is_err());
+    std::fs::write(project_dir.join(PROJECT_META_FILE), metadata).unwrap();
+
+    let changes = collab.changes_since(&project_dir, "tab", 0).unwrap();
+    assert_eq!(changes.version, joined.version);
+    assert!(changes.operations.is_empty());
+
+    let outcome = collab
+      .submit(&project_dir, "tab", vec![rename_home("Kept")], ChangeSource::Desktop)
+      .unwrap();
+    assert_eq!(outcome.version, joined.version + 1);
+    let changes = collab.changes_since(&project_dir, "tab", joined.version).unwrap();
+    assert_eq!(changes.operations.len(), 1);
+    assert_eq!(load_builder_doc(&project_dir).unwrap().pages[0].title, "Kept");
+  }
+
+  #[test]
+  fn a_submit_waits_for_the_project_write_lock() {
+    let workspace = tempfile::tempdir().unwrap();
+    let project_dir = crate::test_project(workspace.path());
+    let writes = ProjectWriteLocks::default();
+    let collab = CollabHub::new(EventHub::default(), writes.clone());
+    collab.join(&project_dir, "tab", "Tab").unwrap();
+
+    let submitted = writes
+      .run(&project_dir, || {
+        let submitter = {
+          let (collab, project_dir) = (collab.clone(), project_dir.clone());
+          std::thread::spawn(move || {
+            collab.submit(&project_dir, "tab", vec![rename_home("Later")], ChangeSource::Desktop)
+          })
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!submitter.is_finished());
+        assert_ne!(load_builder_doc(&project_dir)?.pages[0].title, "Later");
+        Ok(submitter)
+      })
+      .unwrap();
+    submitted.join().unwrap().unwrap();
+    assert_eq!(load_builder_doc(&project_dir).unwrap().pages[0].title, "Later");
+  }
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+use std::path::Path;
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use schemars::JsonSchema;
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Stable, machine-readable error codes shared by Tauri commands, the remote API and the CLI.
+/// Renaming a variant is a breaking change for clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ErrorCode {
+  /// The request itself is malformed or fails validation.
+  InvalidInput,
+  NotFound,
+  /// The target exists but is in a state that prevents the change (locked, already resolved,
+  /// name taken, changed since read).
+  Conflict,
+  /// An `If-Match` ETag no longer matches the stored resource.
+  PreconditionFailed,
+  /// An upload exceeds the size limit for its kind.
+  PayloadTooLarge,
+  Unauthorized,
+  PermissionDenied,
+  /// A project file on disk could not be parsed.
+  CorruptData,
+  /// The server is missing something it needs to answer, such as the built frontend.
+  ServiceUnavailable,
+  Io,
+  Internal,
+}
+
+impl ErrorCode {
+  pub(crate) fn status(self) -> StatusCode {
+    match self {
+      ErrorCode::InvalidInput => StatusCode::UNPROCESSABLE_ENTITY,
+      ErrorCode::NotFound => StatusCode::NOT_FOUND,
+      ErrorCode::Conflict => StatusCode::CONFLICT,
+      ErrorCode::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+      ErrorCode::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+      ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+      ErrorCode::PermissionDenied => StatusCode::FORBIDDEN,
+      ErrorCode::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+      ErrorCode::CorruptData | ErrorCode::Io | ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+  }
+
+  pub(crate) fn as_str(self) -> &'static str {
+    match self {
+      ErrorCode::InvalidInput => "invalid_input",
+      ErrorCode::NotFound => "not_found",
+      ErrorCode::Conflict => "conflict",
+      ErrorCode::PreconditionFailed => "precondition_failed",
+      ErrorCode::PayloadTooLarge => "payload_too_large",
+      ErrorCode::Unauthorized => "unauthorized",
+      ErrorCode::PermissionDenied => "permission_denied",
+      ErrorCode::CorruptData => "corrupt_data",
+      ErrorCode::ServiceUnavailable => "service_unavailable",
+      ErrorCode::Io => "io",
+      ErrorCode::Internal => "internal",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ErrorDetails {
+  /// File or directory the error is about.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  path: Option<String>,
+  /// Input field (camelCase, as sent by the client) the error is about.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  field: Option<String>,
+}
+
+impl ErrorDetails {
+  fn is_empty(&self) -> bool {
+    self.path.is_none() && self.field.is_none()
+  }
+}
+
+/// Error returned by every command and remote route. `message` is safe to show to users.
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppError {
+  code: ErrorCode,
+  message: String,
+  #[serde(skip_serializing_if = "ErrorDetails::is_empty")]
+  #[ts(as = "Option<ErrorDetails>", optional)]
+  details: ErrorDetails,
+}
+
+impl AppError {
+  pub(crate) fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+    Self {
+      code,
+      message: message.into(),
+      details: ErrorDetails::default(),
+    }
+  }
+
+  pub(crate) fn invalid(message: impl Into<String>) -> Self {
+    Self::new(ErrorCode::InvalidInput, message)
+  }
+
+  pub(crate) fn not_found(message: impl Into<String>) -> Self {
+    Self::new(ErrorCode::NotFound, message)
+  }
+
+  pub(crate) fn conflict(message: impl Into<String>) -> Self {
+    Self::new(ErrorCode::Conflict, message)
+  }
+
+  pub(crate) fn precondition_failed(message: impl Into<String>) -> Self {
+    Self::new(ErrorCode::PreconditionFailed, message)
+  }
+
+  pub(crate) fn too_large(message: impl Into<String>) -> Self {
+    Self::new(ErrorCode::PayloadTooLarge, message)
+  }
+
+  pub(crate) fn unauthorized(message: impl Into<String>) -> Self {
+    Self::new(ErrorCode::Unauthorized, message)
+  }
+
+  pub(crate) fn permission_denied(message: impl Into<String>) -> Self {
+    Self::new(ErrorCode::PermissionDenied, message)
+  }
+
+  pub(crate) fn unavailable(message: impl Into<String>) -> Self {
+    Self::new(ErrorCode::ServiceUnavailable, message)
+  }
+
+  pub(crate) fn internal(message: impl Into<String>) -> Self {
+    Self::new(ErrorCode::Internal, message)
+  }
+
+  /// Filesystem failure on `path`, e.g. `AppError::fs("Failed reading", path, err)`. Missing
+  /// files map to `NotFound` and permission errors to `PermissionDenied`.
+  pub(crate) fn fs(action: &str, path: &Path, err: std::io::Error) -> Self {
+    let code = match err.kind() {
+      std::io::ErrorKind::NotFound => ErrorCode::NotFound,
+      std::io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+      _ => ErrorCode::Io,
+    };
+    Self::new(code, format!("{} {}: {}", action, path.display(), err)).with_path(path)
+  }
+
+  /// A project file that exists but does not parse.
+  pub(crate) fn corrupt(path: &Path, err: impl fmt::Display) -> Self {
+    Self::new(ErrorCode::CorruptData, format!("Failed parsing {}: {}", path.display(), err)).with_path(path)
+  }
+
+  /// Poisoned mutex guarding in-memory state, e.g. `AppError::lock("Collab")`.
+  pub(crate) fn lock(state: &str) -> Self {
+    Self::internal(format!("{} state lock failed.", state))
+  }
+
+  /// The common "path given by the client is not a project" failure.
+  pub(crate) fn project_not_found(project_dir: &Path) -> Self {
+    Self::not_found("Project path is invalid").with_path(project_dir)
+  }
+
+  pub(crate) fn with_path(mut self, path: &Path) -> Self {
+    self.details.path = Some(path.to_string_lossy().to_string());
+    self
+  }
+
+  pub(crate) fn with_field(mut self, field: &str) -> Self {
+    self.details.field = Some(field.to_string());
+    self
+  }
+
+  pub(crate) fn code(&self) -> ErrorCode {
+    self.code
+  }
+
+  pub(crate) fn message(&self) -> &str {
+    &self.message
+  }
+
+  pub(crate) fn details(&self) -> Option<&ErrorDetails> {
+    (!self.details.is_empty()).then_some(&self.details)
+  }
+}
+
+impl fmt::Display for AppError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+impl std::error::Error for AppError {}
+
+impl IntoResponse for AppError {
+  fn into_response(self) -> Response {
+    if self.code.status().is_server_error() {
+      log::error!("remote request failed ({}): {}", self.code.as_str(), self.message);
+    }
+    (self.code.status(), Json(self)).into_response()
+  }
+}
+
+impl From<JsonRejection> for AppError {
+  fn from(rejection: JsonRejection) -> Self {
+    Self::invalid(rejection.body_text())
+  }
+}
+
+/// `Json` body extractor for remote routes: a missing, malformed or mistyped body answers with
+/// the usual `invalid_input` error instead of axum's plain-text rejection.
+pub(crate) struct JsonBody<T>(pub(crate) T);
+
+impl<T, S> FromRequest<S> for JsonBody<T>
+where
+  Json<T>: FromRequest<S, Rejection = JsonRejection>,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let Json(value) = Json::<T>::from_request(req, state).await?;
+    Ok(Self(value))
+  }
+}
@@ -0,0 +1,190 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast;
+use ts_rs::TS;
+
+use crate::assets::StoredAsset;
+use crate::collab::{AppliedOperation, Participant};
+use crate::locks::PagePresence;
+use crate::proposals::ProposalStatus;
+use crate::runner::{AgentRunInfo, OutputStream};
+use crate::shelf::ProjectShelf;
+use crate::{ProjectRecord, RemoteServerStatus};
+
+pub(crate) const PROJECT_EVENT_NAME: &str = "manifold://project-event";
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ChangeSource {
+  Desktop,
+  Remote,
+  Agent,
+  /// The `manifold` command line tool.
+  Cli,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum ProjectEvent {
+  #[serde(rename_all = "camelCase")]
+  ProjectSaved {
+    project_path: String,
+    #[ts(type = "number")]
+    revision: u64,
+    source: ChangeSource,
+  },
+  #[serde(rename_all = "camelCase")]
+  PagesChanged {
+    project_path: String,
+    #[ts(type = "number")]
+    revision: u64,
+    source: ChangeSource,
+    added: Vec<String>,
+    updated: Vec<String>,
+    removed: Vec<String>,
+  },
+  #[serde(rename_all = "camelCase")]
+  AssetAdded {
+    project_path: String,
+    asset: StoredAsset,
+    source: ChangeSource,
+  },
+  /// `project_path` is the old path; `project.path` is where the project lives now.
+  #[serde(rename_all = "camelCase")]
+  ProjectRenamed {
+    project_path: String,
+    project: ProjectRecord,
+    source: ChangeSource,
+  },
+  /// A new project copied from the one at `project_path`.
+  #[serde(rename_all = "camelCase")]
+  ProjectDuplicated {
+    project_path: String,
+    project: ProjectRecord,
+    source: ChangeSource,
+  },
+  /// Moved into the archive or trash; `project_path` is where it was.
+  #[serde(rename_all = "camelCase")]
+  ProjectShelved {
+    project_path: String,
+    project: ProjectRecord,
+    shelf: ProjectShelf,
+    source: ChangeSource,
+  },
+  /// Moved back out of the archive or trash; `project_path` is where it was.
+  #[serde(rename_all = "camelCase")]
+  ProjectRestored {
+    project_path: String,
+    project: ProjectRecord,
+    source: ChangeSource,
+  },
+  #[serde(rename_all = "camelCase")]
+  ProjectDeleted {
+    project_path: String,
+    source: ChangeSource,
+  },
+  #[serde(rename_all = "camelCase")]
+  ServerStatusChanged { status: RemoteServerStatus },
+  #[serde(rename_all = "camelCase")]
+  CollabOperations {
+    project_path: String,
+    #[ts(type = "number")]
+    version: u64,
+    operations: Vec<AppliedOperation>,
+  },
+  #[serde(rename_all = "camelCase")]
+  CollabPresence {
+    project_path: String,
+    participants: Vec<Participant>,
+  },
+  #[serde(rename_all = "camelCase")]
+  PagePresence {
+    project_path: String,
+    presence: Vec<PagePresence>,
+  },
+  #[serde(rename_all = "camelCase")]
+  ProposalChanged {
+    project_path: String,
+    proposal_id: String,
+    status: ProposalStatus,
+  },
+  #[serde(rename_all = "camelCase")]
+  AgentRunChanged { run: AgentRunInfo },
+  #[serde(rename_all = "camelCase")]
+  AgentRunOutput {
+    project_path: String,
+    run_id: String,
+    stream: OutputStream,
+    line: String,
+  },
+}
+
+impl ProjectEvent {
+  pub(crate) fn kind(&self) -> &'static str {
+    match self {
+      ProjectEvent::ProjectSaved { .. } => "projectSaved",
+      ProjectEvent::PagesChanged { .. } => "pagesChanged",
+      ProjectEvent::AssetAdded { .. } => "assetAdded",
+      ProjectEvent::ProjectRenamed { .. } => "projectRenamed",
+      ProjectEvent::ProjectDuplicated { .. } => "projectDuplicated",
+      ProjectEvent::ProjectShelved { .. } => "projectShelved",
+      ProjectEvent::ProjectRestored { .. } => "projectRestored",
+      ProjectEvent::ProjectDeleted { .. } => "projectDeleted",
+      ProjectEvent::ServerStatusChanged { .. } => "serverStatusChanged",
+      ProjectEvent::CollabOperations { .. } => "collabOperations",
+      ProjectEvent::CollabPresence { .. } => "collabPresence",
+      ProjectEvent::PagePresence { .. } => "pagePresence",
+      ProjectEvent::ProposalChanged { .. } => "proposalChanged",
+      ProjectEvent::AgentRunChanged { .. } => "agentRunChanged",
+      ProjectEvent::AgentRunOutput { .. } => "agentRunOutput",
+    }
+  }
+
+  pub(crate) fn is_server_stopped(&self) -> bool {
+    matches!(self, ProjectEvent::ServerStatusChanged { status } if !status.running)
+  }
+}
+
+/// Fan-out channel for change notifications. Cloned into the remote API state so desktop
+/// and remote mutations reach every listener.
+#[derive(Clone)]
+pub(crate) struct EventHub {
+  sender: broadcast::Sender<ProjectEvent>,
+}
+
+impl Default for EventHub {
+  fn default() -> Self {
+    let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+    Self { sender }
+  }
+}
+
+impl EventHub {
+  pub(crate) fn publish(&self, event: ProjectEvent) {
+    // No subscribers is the normal case when neither the window nor a remote client listens.
+    let _ = self.sender.send(event);
+  }
+
+  pub(crate) fn subscribe(&self) -> broadcast::Receiver<ProjectEvent> {
+    self.sender.subscribe()
+  }
+}
+
+pub(crate) async fn forward_events_to_window(app: AppHandle, hub: EventHub) {
+  let mut receiver = hub.subscribe();
+  loop {
+    match receiver.recv().await {
+      Ok(event) => {
+        if let Err(err) = app.emit(PROJECT_EVENT_NAME, &event) {
+          log::warn!("failed emitting {} event: {}", event.kind(), err);
+        }
+      }
+      Err(broadcast::error::RecvError::Lagged(skipped)) => {
+        log::warn!("project event forwarder skipped {} events", skipped);
+      }
+      Err(broadcast::error::RecvError::Closed) => break,
+    }
+  }
+}
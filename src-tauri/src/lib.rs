@@ -1,22 +1,73 @@
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Mutex;
+use std::time::Duration;
 
 use axum::body::{Body, Bytes};
-use axum::extract::{OriginalUri, State};
+use axum::extract::{DefaultBodyLimit, OriginalUri, Query, State};
 use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::Response;
 use axum::routing::{any, get, post};
 use axum::{Json, Router};
 use chrono::Utc;
+use futures_util::Stream;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tokio::sync::broadcast;
 use tower_http::cors::CorsLayer;
+use ts_rs::TS;
+
+mod activity;
+mod agent;
+mod archive;
+mod assets;
+mod bindings;
+pub mod cli;
+mod collab;
+mod error;
+mod events;
+mod locks;
+mod mcp;
+mod openapi;
+mod policy;
+mod project_index;
+mod proposals;
+mod rest;
+mod runner;
+mod schema;
+mod shelf;
+mod templates;
+mod validation;
+
+use activity::{ActivityEntry, ActivityOperation};
+use agent::{dispatch_agent_command, AgentResponse};
+use collab::{CollabChanges, CollabHub, CollabSnapshot, DocOperation, Participant, SubmitOutcome};
+use error::{AppError, JsonBody};
+use events::{ChangeSource, EventHub, ProjectEvent};
+use locks::{ClientLockGuard, PageLockHub, PagePresence, ProjectWriteLocks};
+use openapi::HealthDoc;
+use policy::{AgentAccessMode, AgentOperation, AgentPolicyHub, AgentSessionPolicy, PolicyDenial};
+use project_index::{ProjectIndex, ProjectPage, ProjectQuery};
+use proposals::{PatchProposal, ProposalHub, ProposalOutcome};
+use runner::{AgentRunInfo, AgentRunRequest, AgentRunnerHub};
+use schema::ProjectFilesReport;
 
 const PROJECT_META_FILE: &str = "project.json";
+/// Layout of the files in a project directory, recorded in `project.json`. Archive imports
+/// migrate older projects up to it.
+const PROJECT_FORMAT_VERSION: u32 = 1;
 const SITE_FILE: &str = "site.json";
 const SITEMAP_FILE: &str = "sitemap.json";
 const PAGES_DIR: &str = "pages";
+const THEME_FILE: &str = "theme.json";
+const DESKTOP_CLIENT_ID: &str = "desktop";
+const EVENT_STREAM_HEARTBEAT: Duration = Duration::from_secs(20);
+const MAX_PROJECT_TAGS: usize = 20;
+const MAX_TAG_CHARS: usize = 40;
+const MAX_CLIENT_NAME_CHARS: usize = 120;
 
 #[derive(Default)]
 struct RemoteServerState {
@@ -30,7 +81,7 @@ struct RemoteServerHandle {
   shutdown: Option<tokio::sync::oneshot::Sender<()>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct RemoteServerStatus {
   running: bool,
@@ -39,19 +90,85 @@ struct RemoteServerStatus {
   server_url: String,
 }
 
+/// Live-editing hubs shared by Tauri commands and the remote router.
+#[derive(Clone)]
+struct LiveServices {
+  events: EventHub,
+  collab: CollabHub,
+  locks: PageLockHub,
+  proposals: ProposalHub,
+  policy: AgentPolicyHub,
+  runner: AgentRunnerHub,
+  index: ProjectIndex,
+  writes: ProjectWriteLocks,
+}
+
+impl LiveServices {
+  fn new() -> Self {
+    let events = EventHub::default();
+    let writes = ProjectWriteLocks::default();
+    Self {
+      collab: CollabHub::new(events.clone(), writes.clone()),
+      locks: PageLockHub::new(events.clone()),
+      proposals: ProposalHub::new(events.clone()),
+      policy: AgentPolicyHub::default(),
+      runner: AgentRunnerHub::new(events.clone()),
+      index: ProjectIndex::default(),
+      writes,
+      events,
+    }
+  }
+
+  /// Points every live session at a renamed project directory. Agent runs are keyed by the
+  /// canonical path, so the old directory's is passed in as `resolved_from`.
+  fn move_project(&self, from: &Path, resolved_from: &Path, to: &Path) -> Result<(), AppError> {
+    self.collab.move_project(from, to)?;
+    self.locks.move_project(from, to)?;
+    self.proposals.move_project(from, to)?;
+    self.policy.move_project(from, to)?;
+    let resolved_to = to
+      .canonicalize()
+      .map_err(|err| AppError::fs("Failed resolving project", to, err))?;
+    self.runner.move_project(resolved_from, &resolved_to)
+  }
+}
+
 #[derive(Clone)]
 struct RemoteApiState {
   token: String,
   workspace_root: String,
+  frontend_dist: PathBuf,
+  services: LiveServices,
+}
+
+/// A bound listener and its router, not yet serving. Shared by the desktop command and the
+/// headless `manifold serve` process.
+struct BoundRemoteServer {
+  listener: tokio::net::TcpListener,
+  app: Router,
+  status: RemoteServerStatus,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct RemoteContextDoc {
   workspace_root: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Acknowledgement returned by remote routes that have nothing else to report.
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+struct RemoteOk {
+  ok: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+struct SaveProjectOutcome {
+  ok: bool,
+  #[ts(type = "number")]
+  revision: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 struct ProjectMetadata {
   name: String,
@@ -59,45 +176,92 @@ struct ProjectMetadata {
   site_url: String,
   created_at: String,
   updated_at: String,
+  #[serde(default)]
+  revision: u64,
+  #[serde(default)]
+  tags: Vec<String>,
+  #[serde(default)]
+  client_name: String,
+  #[serde(default)]
+  pinned: bool,
+  #[serde(default)]
+  color: ProjectColor,
+  /// `PROJECT_FORMAT_VERSION` when the project was created; `0` before it was recorded.
+  #[serde(default)]
+  format_version: u32,
+}
+
+/// Launcher color label.
+#[derive(
+  Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS, clap::ValueEnum,
+)]
+#[serde(rename_all = "camelCase")]
+enum ProjectColor {
+  #[default]
+  None,
+  Red,
+  Orange,
+  Yellow,
+  Green,
+  Blue,
+  Purple,
+  Gray,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct ProjectRecord {
   id: String,
   name: String,
+  /// The project directory is `<slug>.manifold`; REST routes address projects by it.
+  slug: String,
   path: String,
+  created_at: String,
   updated_at: String,
   site_url: String,
+  tags: Vec<String>,
+  /// Empty when the project is not filed under a client.
+  client_name: String,
+  pinned: bool,
+  color: ProjectColor,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct SiteDoc {
   site_name: String,
   base_url: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct SitemapDoc {
+  /// Page ids in navigation order.
   page_order: Vec<String>,
   root_page_id: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct PageSeoDoc {
   title: String,
   description: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Viewport key (`default`, `mobile`, ...) to style values.
+type ViewportStyles = HashMap<String, HashMap<String, String>>;
+/// Viewport key to interaction state (`hover`) to style values.
+type ViewportStateStyles = HashMap<String, HashMap<String, HashMap<String, String>>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(optional_fields = nullable)]
 struct BlockStyleDoc {
   variant: String,
   margin_top: Option<String>,
+  margin_right: Option<String>,
   margin_bottom: Option<String>,
+  margin_left: Option<String>,
   padding_top: Option<String>,
   padding_right: Option<String>,
   padding_bottom: Option<String>,
@@ -107,25 +271,35 @@ struct BlockStyleDoc {
   border_color: Option<String>,
   border_radius: Option<String>,
   background_color: Option<String>,
+  background_image: Option<String>,
   text_color: Option<String>,
   font_size: Option<String>,
+  translate_x: Option<String>,
+  translate_y: Option<String>,
   primitive_styles: Option<HashMap<String, HashMap<String, String>>>,
+  viewport_styles: Option<ViewportStyles>,
+  primitive_viewport_styles: Option<HashMap<String, ViewportStyles>>,
+  state_viewport_styles: Option<ViewportStateStyles>,
+  primitive_state_viewport_styles: Option<HashMap<String, ViewportStateStyles>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct BlockDoc {
   id: String,
+  /// Catalog block type, e.g. `hero`.
   #[serde(rename = "type")]
   block_type: String,
   props: serde_json::Value,
+  /// `visible` or `hidden`.
   visibility: String,
   style_overrides: BlockStyleDoc,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct PageDoc {
+  /// Also the file name under `pages/`.
   id: String,
   title: String,
   route: String,
@@ -133,7 +307,7 @@ struct PageDoc {
   blocks: Vec<BlockDoc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct BuilderProjectDoc {
   site: SiteDoc,
@@ -142,42 +316,266 @@ struct BuilderProjectDoc {
   selected_page_id: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct WorkspaceRootInput {
   workspace_root: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct CreateProjectInput {
   workspace_root: String,
   name: String,
   slug: String,
   site_url: String,
+  /// Template id from `/list-project-templates`; a single empty Home page when omitted.
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  template: String,
+  /// Saved as the project's client and fills `{{clientName}}` in the template, where it
+  /// defaults to the project name.
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  client_name: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct DuplicateProjectInput {
+  project_path: String,
+  new_name: String,
+  new_slug: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct RenameProjectInput {
+  project_path: String,
+  new_name: String,
+  /// The directory becomes `<newSlug>.manifold`; pass the current slug to keep it.
+  new_slug: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct QueryProjectsInput {
+  workspace_root: String,
+  #[serde(default)]
+  #[ts(optional)]
+  query: Option<ProjectQuery>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct ListShelvedInput {
+  workspace_root: String,
+  /// Both shelves when omitted.
+  #[serde(default)]
+  #[ts(optional)]
+  shelf: Option<shelf::ProjectShelf>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct DeleteProjectInput {
+  project_path: String,
+  /// From `/request-project-deletion`.
+  confirmation_token: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct ProjectActivityInput {
+  project_path: String,
+  /// Newest entries to return; 100 when omitted.
+  #[serde(default)]
+  #[ts(as = "Option<u32>", optional)]
+  limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct ProjectPathInput {
   project_path: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Launcher details to change; omitted fields keep their value.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct ProjectDetailsPatch {
+  /// Replaces every tag; an empty list clears them.
+  #[serde(default)]
+  #[ts(optional)]
+  tags: Option<Vec<String>>,
+  /// An empty name removes the project from its client.
+  #[serde(default)]
+  #[ts(optional)]
+  client_name: Option<String>,
+  #[serde(default)]
+  #[ts(optional)]
+  pinned: Option<bool>,
+  #[serde(default)]
+  #[ts(optional)]
+  color: Option<ProjectColor>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct UpdateProjectDetailsInput {
+  project_path: String,
+  details: ProjectDetailsPatch,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct UpdateSiteUrlInput {
   project_path: String,
   site_url: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 struct SaveBuilderProjectInput {
   project_path: String,
   document: BuilderProjectDoc,
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  client_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct OpenPageInput {
+  project_path: String,
+  client_id: String,
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  display_name: String,
+  page_id: String,
+  #[serde(default)]
+  #[ts(as = "Option<bool>", optional)]
+  lock: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct CollabJoinInput {
+  project_path: String,
+  client_id: String,
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  display_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct CollabSubmitInput {
+  project_path: String,
+  client_id: String,
+  operations: Vec<DocOperation>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct CollabChangesInput {
+  project_path: String,
+  client_id: String,
+  #[ts(type = "number")]
+  since: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct CollabPresenceInput {
+  project_path: String,
+  client_id: String,
+  page_id: Option<String>,
+  block_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct CollabClientInput {
+  project_path: String,
+  client_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct ProposalSubmitInput {
+  project_path: String,
+  #[ts(type = "JsonPatch")]
+  #[schemars(with = "Vec<serde_json::Value>")]
+  patch: json_patch::Patch,
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  summary: String,
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  client_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct ProposalResolveInput {
+  project_path: String,
+  proposal_id: String,
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  client_id: String,
+  #[serde(default)]
+  #[ts(optional)]
+  reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct ProposalRevertInput {
+  project_path: String,
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  client_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct AgentSessionStartInput {
+  project_path: String,
+  mode: AgentAccessMode,
+  #[serde(default)]
+  #[ts(as = "Option<Vec<AgentOperation>>", optional)]
+  operations: Vec<AgentOperation>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct AgentSessionUpdateInput {
+  session_id: String,
+  #[serde(default)]
+  #[ts(optional)]
+  mode: Option<AgentAccessMode>,
+  #[serde(default)]
+  #[ts(optional)]
+  operations: Option<Vec<AgentOperation>>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+struct AgentSessionInput {
+  session_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteEventsQuery {
+  #[serde(default)]
+  token: String,
+  #[serde(default)]
+  client_id: String,
 }
 
-fn require_remote_token(headers: &HeaderMap, expected: &str) -> Result<(), (StatusCode, String)> {
+fn require_remote_token(headers: &HeaderMap, expected: &str) -> Result<(), AppError> {
   let provided = headers
     .get("x-manifold-token")
     .and_then(|value| value.to_str().ok())
@@ -185,194 +583,834 @@ fn require_remote_token(headers: &HeaderMap, expected: &str) -> Result<(), (Stat
   if provided == expected {
     Ok(())
   } else {
-    Err((StatusCode::UNAUTHORIZED, "Unauthorized remote token.".to_string()))
+    Err(AppError::unauthorized("Unauthorized remote token."))
   }
 }
 
 async fn remote_health(
   State(api): State<RemoteApiState>,
   headers: HeaderMap,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+) -> Result<Json<HealthDoc>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  Ok(Json(openapi::health()))
+}
+
+async fn remote_openapi(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
   require_remote_token(&headers, &api.token)?;
-  Ok(Json(serde_json::json!({
-    "status": "ok"
-  })))
+  Ok(Json(openapi::openapi_document()))
 }
 
 async fn remote_list_projects(
   State(api): State<RemoteApiState>,
   headers: HeaderMap,
-  Json(input): Json<WorkspaceRootInput>,
-) -> Result<Json<Vec<ProjectRecord>>, (StatusCode, String)> {
+  JsonBody(input): JsonBody<WorkspaceRootInput>,
+) -> Result<Json<Vec<ProjectRecord>>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let workspace_root = if input.workspace_root.trim().is_empty() {
+    api.workspace_root.clone()
+  } else {
+    input.workspace_root
+  };
+  query_workspace_projects(&api.services.index, &workspace_root, &ProjectQuery::default())
+    .map(|page| Json(page.projects))
+}
+
+async fn remote_query_projects(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<QueryProjectsInput>,
+) -> Result<Json<ProjectPage>, AppError> {
   require_remote_token(&headers, &api.token)?;
   let workspace_root = if input.workspace_root.trim().is_empty() {
     api.workspace_root.clone()
   } else {
     input.workspace_root
   };
-  list_projects(workspace_root)
+  query_workspace_projects(&api.services.index, &workspace_root, &input.query.unwrap_or_default())
     .map(Json)
-    .map_err(|err| (StatusCode::BAD_REQUEST, err))
 }
 
 async fn remote_create_project(
   State(api): State<RemoteApiState>,
   headers: HeaderMap,
-  Json(input): Json<CreateProjectInput>,
-) -> Result<Json<ProjectRecord>, (StatusCode, String)> {
+  JsonBody(input): JsonBody<CreateProjectInput>,
+) -> Result<Json<ProjectRecord>, AppError> {
   require_remote_token(&headers, &api.token)?;
   let workspace_root = if input.workspace_root.trim().is_empty() {
     api.workspace_root.clone()
   } else {
     input.workspace_root
   };
-  create_project(workspace_root, input.name, input.slug, input.site_url)
-    .map(Json)
-    .map_err(|err| (StatusCode::BAD_REQUEST, err))
+  create_project(
+    workspace_root,
+    input.name,
+    input.slug,
+    input.site_url,
+    Some(input.template),
+    Some(input.client_name),
+  )
+  .map(Json)
 }
 
-async fn remote_update_project_site_url(
+async fn remote_list_project_templates(
   State(api): State<RemoteApiState>,
   headers: HeaderMap,
-  Json(input): Json<UpdateSiteUrlInput>,
-) -> Result<Json<ProjectRecord>, (StatusCode, String)> {
+  JsonBody(input): JsonBody<WorkspaceRootInput>,
+) -> Result<Json<Vec<templates::ProjectTemplate>>, AppError> {
   require_remote_token(&headers, &api.token)?;
-  update_project_site_url(input.project_path, input.site_url)
+  let workspace_root = if input.workspace_root.trim().is_empty() {
+    api.workspace_root.clone()
+  } else {
+    input.workspace_root
+  };
+  list_project_templates(workspace_root)
     .map(Json)
-    .map_err(|err| (StatusCode::BAD_REQUEST, err))
 }
 
-async fn remote_load_builder_project(
+async fn remote_duplicate_project(
   State(api): State<RemoteApiState>,
   headers: HeaderMap,
-  Json(input): Json<ProjectPathInput>,
-) -> Result<Json<BuilderProjectDoc>, (StatusCode, String)> {
+  JsonBody(input): JsonBody<DuplicateProjectInput>,
+) -> Result<Json<ProjectRecord>, AppError> {
   require_remote_token(&headers, &api.token)?;
-  load_builder_project(input.project_path)
+  duplicate_project_dir(
+    &api.services,
+    Path::new(&input.project_path),
+    &input.new_name,
+    &input.new_slug,
+    ChangeSource::Remote,
+  )
+  .map(Json)
+}
+
+async fn remote_rename_project(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<RenameProjectInput>,
+) -> Result<Json<ProjectRecord>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  rename_project_dir(
+    &api.services,
+    Path::new(&input.project_path),
+    &input.new_name,
+    &input.new_slug,
+    ChangeSource::Remote,
+  )
+  .map(Json)
+}
+
+async fn remote_archive_project(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProjectPathInput>,
+) -> Result<Json<shelf::ShelvedProject>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let project_dir = Path::new(&input.project_path);
+  shelf::shelve_project(&api.services, project_dir, shelf::ProjectShelf::Archive, ChangeSource::Remote)
     .map(Json)
-    .map_err(|err| (StatusCode::BAD_REQUEST, err))
 }
 
-async fn remote_save_builder_project(
+async fn remote_trash_project(
   State(api): State<RemoteApiState>,
   headers: HeaderMap,
-  Json(input): Json<SaveBuilderProjectInput>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+  JsonBody(input): JsonBody<ProjectPathInput>,
+) -> Result<Json<shelf::ShelvedProject>, AppError> {
   require_remote_token(&headers, &api.token)?;
-  save_builder_project(input.project_path, input.document)
-    .map(|_| Json(serde_json::json!({ "ok": true })))
-    .map_err(|err| (StatusCode::BAD_REQUEST, err))
+  let project_dir = Path::new(&input.project_path);
+  shelf::shelve_project(&api.services, project_dir, shelf::ProjectShelf::Trash, ChangeSource::Remote)
+    .map(Json)
 }
 
-async fn remote_context(
+async fn remote_restore_project(
   State(api): State<RemoteApiState>,
   headers: HeaderMap,
-) -> Result<Json<RemoteContextDoc>, (StatusCode, String)> {
+  JsonBody(input): JsonBody<ProjectPathInput>,
+) -> Result<Json<ProjectRecord>, AppError> {
   require_remote_token(&headers, &api.token)?;
-  Ok(Json(RemoteContextDoc {
-    workspace_root: api.workspace_root.clone(),
-  }))
+  shelf::restore_project(&api.services, Path::new(&input.project_path), ChangeSource::Remote)
+    .map(Json)
 }
 
-async fn remote_frontend_proxy(
-  State(_api): State<RemoteApiState>,
-  _method: axum::http::Method,
-  original_uri: OriginalUri,
-  _headers: HeaderMap,
-  _body: Bytes,
-) -> Result<Response, (StatusCode, String)> {
-  // Serve static frontend files directly from dist for deterministic remote behavior.
-  let dist_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../src-ui/dist");
-  if !dist_root.exists() {
-    let msg = "Frontend dist missing. Run `npm --prefix src-ui run build` before starting remote server.";
-    return Response::builder()
-      .status(StatusCode::SERVICE_UNAVAILABLE)
-      .header("content-type", "text/plain; charset=utf-8")
-      .body(Body::from(msg.as_bytes().to_vec()))
-      .map_err(|err| {
-        (
-          StatusCode::INTERNAL_SERVER_ERROR,
-          format!("Static response build failed: {}", err),
-        )
-      });
-  }
-  let uri_path = original_uri.0.path();
-  let normalized_path = if uri_path == "/" {
-    "index.html".to_string()
+async fn remote_list_shelved_projects(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ListShelvedInput>,
+) -> Result<Json<Vec<shelf::ShelvedProject>>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let workspace_root = if input.workspace_root.trim().is_empty() {
+    api.workspace_root.clone()
   } else {
-    uri_path.trim_start_matches('/').to_string()
+    input.workspace_root
   };
-  let requested = dist_root.join(&normalized_path);
-  let fallback = dist_root.join("index.html");
-  let file_path = if requested.is_file() { requested } else { fallback };
-  let bytes = fs::read(&file_path).map_err(|err| {
-    (
-      StatusCode::BAD_GATEWAY,
-      format!("Frontend static file read failed: {}", err),
-    )
-  })?;
+  list_shelved_projects(workspace_root, input.shelf)
+    .map(Json)
+}
 
-  let content_type = if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
-    match ext {
-      "html" => "text/html; charset=utf-8",
-      "js" => "application/javascript; charset=utf-8",
-      "css" => "text/css; charset=utf-8",
-      "json" => "application/json; charset=utf-8",
-      "svg" => "image/svg+xml",
-      "png" => "image/png",
-      "jpg" | "jpeg" => "image/jpeg",
-      "webp" => "image/webp",
-      _ => "application/octet-stream",
-    }
-  } else {
-    "application/octet-stream"
-  };
+async fn remote_request_project_deletion(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProjectPathInput>,
+) -> Result<Json<shelf::DeletionRequest>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  shelf::request_deletion(Path::new(&input.project_path))
+    .map(Json)
+}
 
-  Response::builder()
-    .status(StatusCode::OK)
-    .header("content-type", content_type)
-    .body(Body::from(bytes))
-    .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, format!("Static response build failed: {}", err)))
+async fn remote_delete_project(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<DeleteProjectInput>,
+) -> Result<Json<RemoteOk>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  shelf::delete_project(
+    &api.services,
+    Path::new(&input.project_path),
+    &input.confirmation_token,
+    ChangeSource::Remote,
+  )
+  .map(|_| Json(RemoteOk { ok: true }))
 }
 
-fn stopped_remote_status() -> RemoteServerStatus {
-  RemoteServerStatus {
-    running: false,
-    host: "".to_string(),
-    port: 0,
-    server_url: "".to_string(),
-  }
+async fn remote_update_project_site_url(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<UpdateSiteUrlInput>,
+) -> Result<Json<ProjectRecord>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  set_project_site_url(Path::new(&input.project_path), &input.site_url, ChangeSource::Remote)
+    .map(Json)
 }
 
-fn advertised_remote_url(bind_host: &str, addr: std::net::SocketAddr) -> String {
-  if bind_host == "0.0.0.0" {
-    if let Ok(ip) = local_ip_address::local_ip() {
-      return format!("http://{}:{}", ip, addr.port());
-    }
-  }
-  format!("http://{}:{}", addr.ip(), addr.port())
+async fn remote_update_project_details(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<UpdateProjectDetailsInput>,
+) -> Result<Json<ProjectRecord>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  set_project_details(Path::new(&input.project_path), input.details, ChangeSource::Remote)
+    .map(Json)
 }
 
-fn now_iso() -> String {
-  Utc::now().to_rfc3339()
+async fn remote_project_activity(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProjectActivityInput>,
+) -> Result<Json<Vec<ActivityEntry>>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  get_project_activity(input.project_path, input.limit)
+    .map(Json)
 }
 
-fn normalize_slug(raw: &str) -> String {
-  let mut slug = raw.trim().to_lowercase();
-  if slug.is_empty() {
-    slug = "new-project".to_string();
-  }
+async fn remote_load_builder_project(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProjectPathInput>,
+) -> Result<Json<BuilderProjectDoc>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  load_builder_project(input.project_path)
+    .map(Json)
+}
 
-  let mut sanitized = String::new();
-  for ch in slug.chars() {
-    if ch.is_ascii_alphanumeric() || ch == '-' {
-      sanitized.push(ch);
-    } else if ch.is_ascii_whitespace() || ch == '_' {
-      sanitized.push('-');
-    }
-  }
+async fn remote_project_schemas(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+) -> Result<Json<BTreeMap<String, serde_json::Value>>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  Ok(Json(schema::project_schemas()))
+}
 
-  while sanitized.contains("--") {
+async fn remote_validate_project_files(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProjectPathInput>,
+) -> Result<Json<ProjectFilesReport>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  schema::validate_project_files(Path::new(&input.project_path)).map(Json)
+}
+
+async fn remote_save_builder_project(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<SaveBuilderProjectInput>,
+) -> Result<Json<SaveProjectOutcome>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let project_dir = PathBuf::from(input.project_path);
+  if !project_dir.is_dir() {
+    return Err(AppError::project_not_found(&project_dir));
+  }
+  let services = &api.services;
+  let revision = services.writes.run(&project_dir, || {
+    let revision = save_builder_doc(
+      &project_dir,
+      &input.document,
+      &services.events,
+      &services.locks,
+      &input.client_id,
+      ChangeSource::Remote,
+      ActivityOperation::Save,
+    )?;
+    services.collab.reload(&project_dir)?;
+    Ok(revision)
+  })?;
+  Ok(Json(SaveProjectOutcome { ok: true, revision }))
+}
+
+async fn remote_agent_command(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(request): JsonBody<serde_json::Value>,
+) -> Result<Json<AgentResponse>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  Ok(Json(dispatch_agent_command(request, &api.services)))
+}
+
+async fn remote_start_agent_session(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<AgentSessionStartInput>,
+) -> Result<Json<AgentSessionPolicy>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let policy = &api.services.policy;
+  policy.check_remote_change(None, Some(input.mode), None)?;
+  policy
+    .start_session(Path::new(&input.project_path), input.mode, input.operations)
+    .map(Json)
+}
+
+async fn remote_update_agent_session(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<AgentSessionUpdateInput>,
+) -> Result<Json<AgentSessionPolicy>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let policy = &api.services.policy;
+  policy.check_remote_change(Some(&input.session_id), input.mode, input.operations.as_deref())?;
+  policy
+    .update_session(&input.session_id, input.mode, input.operations)
+    .map(Json)
+}
+
+async fn remote_end_agent_session(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<AgentSessionInput>,
+) -> Result<Json<RemoteOk>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .policy
+    .end_session(&input.session_id)
+    .map(|_| Json(RemoteOk { ok: true }))
+}
+
+async fn remote_agent_denials(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProjectPathInput>,
+) -> Result<Json<Vec<PolicyDenial>>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .policy
+    .denials(Some(Path::new(&input.project_path)))
+    .map(Json)
+}
+
+async fn remote_open_page(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<OpenPageInput>,
+) -> Result<Json<Vec<PagePresence>>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .locks
+    .open_page(
+      Path::new(&input.project_path),
+      &input.client_id,
+      &input.display_name,
+      &input.page_id,
+      input.lock,
+    )
+    .map(Json)
+}
+
+async fn remote_close_page(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<CollabClientInput>,
+) -> Result<Json<RemoteOk>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .locks
+    .close_page(Path::new(&input.project_path), &input.client_id)
+    .map(|_| Json(RemoteOk { ok: true }))
+}
+
+async fn remote_page_presence(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProjectPathInput>,
+) -> Result<Json<Vec<PagePresence>>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .locks
+    .presence(Path::new(&input.project_path))
+    .map(Json)
+}
+
+async fn remote_collab_join(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<CollabJoinInput>,
+) -> Result<Json<CollabSnapshot>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .collab
+    .join(Path::new(&input.project_path), &input.client_id, &input.display_name)
+    .map(Json)
+}
+
+async fn remote_collab_submit(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<CollabSubmitInput>,
+) -> Result<Json<SubmitOutcome>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .collab
+    .submit(
+      Path::new(&input.project_path),
+      &input.client_id,
+      input.operations,
+      ChangeSource::Remote,
+    )
+    .map(Json)
+}
+
+async fn remote_collab_changes(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<CollabChangesInput>,
+) -> Result<Json<CollabChanges>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .collab
+    .changes_since(Path::new(&input.project_path), &input.client_id, input.since)
+    .map(Json)
+}
+
+async fn remote_collab_presence(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<CollabPresenceInput>,
+) -> Result<Json<Vec<Participant>>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .collab
+    .update_presence(
+      Path::new(&input.project_path),
+      &input.client_id,
+      input.page_id,
+      input.block_id,
+    )
+    .map(Json)
+}
+
+async fn remote_collab_leave(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<CollabClientInput>,
+) -> Result<Json<RemoteOk>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .collab
+    .leave(Path::new(&input.project_path), &input.client_id)
+    .map(|_| Json(RemoteOk { ok: true }))
+}
+
+async fn remote_submit_proposal(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProposalSubmitInput>,
+) -> Result<Json<PatchProposal>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .proposals
+    .submit(
+      Path::new(&input.project_path),
+      input.patch,
+      &input.summary,
+      &input.client_id,
+      ChangeSource::Remote,
+    )
+    .map(Json)
+}
+
+async fn remote_list_proposals(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProjectPathInput>,
+) -> Result<Json<Vec<PatchProposal>>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .proposals
+    .list(Path::new(&input.project_path))
+    .map(Json)
+}
+
+async fn remote_approve_proposal(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProposalResolveInput>,
+) -> Result<Json<ProposalOutcome>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .proposals
+    .approve(
+      &api.services,
+      Path::new(&input.project_path),
+      &input.proposal_id,
+      &input.client_id,
+      ChangeSource::Remote,
+    )
+    .map(Json)
+}
+
+async fn remote_reject_proposal(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProposalResolveInput>,
+) -> Result<Json<PatchProposal>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .proposals
+    .reject(
+      Path::new(&input.project_path),
+      &input.proposal_id,
+      &input.client_id,
+      input.reason,
+    )
+    .map(Json)
+}
+
+async fn remote_revert_last_change(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  JsonBody(input): JsonBody<ProposalRevertInput>,
+) -> Result<Json<ProposalOutcome>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  api
+    .services
+    .proposals
+    .revert_last(
+      &api.services,
+      Path::new(&input.project_path),
+      &input.client_id,
+      ChangeSource::Remote,
+    )
+    .map(Json)
+}
+
+async fn remote_events(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  Query(query): Query<RemoteEventsQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+  // Browsers cannot set headers on EventSource, so the token may also arrive as a query param.
+  if query.token != api.token {
+    require_remote_token(&headers, &api.token)?;
+  }
+  let receiver = api.services.events.subscribe();
+  // A client id ties the stream to that client's open pages; they are released on disconnect.
+  let guard = if query.client_id.trim().is_empty() {
+    None
+  } else {
+    Some(ClientLockGuard::new(api.services.locks.clone(), query.client_id))
+  };
+  let initial = (receiver, guard, false);
+  let stream = futures_util::stream::unfold(initial, |(mut receiver, guard, finished)| async move {
+    if finished {
+      return None;
+    }
+    loop {
+      // Touch on every wake-up, not just quiet heartbeats, so a busy stream keeps its pages too.
+      if let Some(guard) = guard.as_ref() {
+        guard.touch();
+      }
+      let Ok(received) = tokio::time::timeout(EVENT_STREAM_HEARTBEAT, receiver.recv()).await else {
+        continue;
+      };
+      match received {
+        Ok(event) => {
+          // End the stream once the server reports itself stopped so graceful shutdown can finish.
+          let stopped = event.is_server_stopped();
+          let sse_event = Event::default().event(event.kind()).json_data(&event);
+          return Some((sse_event, (receiver, guard, stopped)));
+        }
+        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+        Err(broadcast::error::RecvError::Closed) => return None,
+      }
+    }
+  });
+  Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+async fn remote_context(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+) -> Result<Json<RemoteContextDoc>, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  Ok(Json(RemoteContextDoc {
+    workspace_root: api.workspace_root.clone(),
+  }))
+}
+
+async fn remote_frontend_proxy(
+  State(api): State<RemoteApiState>,
+  _method: axum::http::Method,
+  original_uri: OriginalUri,
+  _headers: HeaderMap,
+  _body: Bytes,
+) -> Result<Response, AppError> {
+  // Serve static frontend files directly from dist for deterministic remote behavior.
+  let dist_root = &api.frontend_dist;
+  if !dist_root.exists() {
+    return Err(
+      AppError::unavailable(
+        "Frontend dist missing. Run `npm --prefix src-ui run build` before starting remote server.",
+      )
+      .with_path(dist_root),
+    );
+  }
+  let uri_path = original_uri.0.path();
+  let normalized_path = if uri_path == "/" {
+    "index.html".to_string()
+  } else {
+    uri_path.trim_start_matches('/').to_string()
+  };
+  let requested = dist_root.join(&normalized_path);
+  let fallback = dist_root.join("index.html");
+  let file_path = if requested.is_file() { requested } else { fallback };
+  let bytes =
+    fs::read(&file_path).map_err(|err| AppError::fs("Failed reading frontend file", &file_path, err))?;
+
+  let content_type = if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
+    match ext {
+      "html" => "text/html; charset=utf-8",
+      "js" => "application/javascript; charset=utf-8",
+      "css" => "text/css; charset=utf-8",
+      "json" => "application/json; charset=utf-8",
+      "svg" => "image/svg+xml",
+      "png" => "image/png",
+      "jpg" | "jpeg" => "image/jpeg",
+      "webp" => "image/webp",
+      _ => "application/octet-stream",
+    }
+  } else {
+    "application/octet-stream"
+  };
+
+  Response::builder()
+    .status(StatusCode::OK)
+    .header("content-type", content_type)
+    .body(Body::from(bytes))
+    .map_err(|err| AppError::internal(format!("Static response build failed: {}", err)))
+}
+
+fn default_frontend_dist() -> PathBuf {
+  PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../src-ui/dist")
+}
+
+fn validate_remote_settings(token: &str, workspace_root: &str) -> Result<(), AppError> {
+  if token.trim().is_empty() {
+    return Err(AppError::invalid("Remote server token is required.").with_field("token"));
+  }
+  if workspace_root.trim().is_empty() {
+    return Err(
+      AppError::invalid("Workspace root is required before starting remote server.").with_field("workspaceRoot"),
+    );
+  }
+  let workspace = PathBuf::from(workspace_root.trim());
+  if !workspace.exists() {
+    return Err(
+      AppError::not_found(format!(
+        "Workspace root does not exist: {}",
+        workspace.display()
+      ))
+      .with_path(&workspace),
+    );
+  }
+  if !workspace.is_dir() {
+    return Err(
+      AppError::invalid("Workspace root must be a directory before starting remote server.")
+        .with_field("workspaceRoot"),
+    );
+  }
+  Ok(())
+}
+
+/// Routes served under every API prefix; keep `openapi::describe_routes` in step.
+fn remote_api_routes() -> Router<RemoteApiState> {
+  Router::new()
+    .route("/remote-context", post(remote_context))
+    .route("/list-projects", post(remote_list_projects))
+    .route("/query-projects", post(remote_query_projects))
+    .route("/create-project", post(remote_create_project))
+    .route("/list-project-templates", post(remote_list_project_templates))
+    .route("/duplicate-project", post(remote_duplicate_project))
+    .route("/rename-project", post(remote_rename_project))
+    .route("/archive-project", post(remote_archive_project))
+    .route("/trash-project", post(remote_trash_project))
+    .route("/restore-project", post(remote_restore_project))
+    .route("/list-shelved-projects", post(remote_list_shelved_projects))
+    .route("/request-project-deletion", post(remote_request_project_deletion))
+    .route("/delete-project", post(remote_delete_project))
+    .route("/update-project-site-url", post(remote_update_project_site_url))
+    .route("/update-project-details", post(remote_update_project_details))
+    .route("/project-activity", post(remote_project_activity))
+    .route("/load-builder-project", post(remote_load_builder_project))
+    .route("/save-builder-project", post(remote_save_builder_project))
+    .route("/project-schemas", post(remote_project_schemas))
+    .route("/validate-project-files", post(remote_validate_project_files))
+    .route("/events", get(remote_events))
+    .route("/agent", post(remote_agent_command))
+    .route("/agent/sessions/start", post(remote_start_agent_session))
+    .route("/agent/sessions/update", post(remote_update_agent_session))
+    .route("/agent/sessions/end", post(remote_end_agent_session))
+    .route("/agent/denials", post(remote_agent_denials))
+    .route("/pages/open", post(remote_open_page))
+    .route("/pages/close", post(remote_close_page))
+    .route("/pages/presence", post(remote_page_presence))
+    .route("/collab/join", post(remote_collab_join))
+    .route("/collab/submit", post(remote_collab_submit))
+    .route("/collab/changes", post(remote_collab_changes))
+    .route("/collab/presence", post(remote_collab_presence))
+    .route("/collab/leave", post(remote_collab_leave))
+    .route("/proposals/submit", post(remote_submit_proposal))
+    .route("/proposals/list", post(remote_list_proposals))
+    .route("/proposals/approve", post(remote_approve_proposal))
+    .route("/proposals/reject", post(remote_reject_proposal))
+    .route("/proposals/revert", post(remote_revert_last_change))
+    .route("/openapi.json", get(remote_openapi))
+    .route("/projects", get(rest::list_projects_resource))
+    .route("/projects/{slug}", get(rest::get_project))
+    .route("/projects/{slug}/site", get(rest::get_site).patch(rest::patch_site))
+    .route("/projects/{slug}/pages/{pageId}", get(rest::get_page).put(rest::put_page))
+    .route("/projects/{slug}/archive", get(archive::download_archive))
+    .route(
+      "/projects/{slug}/assets",
+      post(assets::upload_asset).layer(DefaultBodyLimit::max(assets::MAX_UPLOAD_BYTES)),
+    )
+}
+
+fn remote_router(api_state: RemoteApiState) -> Router {
+  let api = remote_api_routes();
+  Router::new()
+    .route("/health", get(remote_health))
+    .nest(openapi::API_PREFIX, api.clone())
+    // Unversioned paths predate `/api/v1`; older remote clients still call them.
+    .nest("/api", api)
+    .fallback(any(remote_frontend_proxy))
+    .layer(CorsLayer::very_permissive())
+    .with_state(api_state)
+}
+
+async fn bind_remote_server(
+  host: &str,
+  port: u16,
+  api_state: RemoteApiState,
+) -> Result<BoundRemoteServer, AppError> {
+  let bind_host = if host.trim().is_empty() {
+    "0.0.0.0".to_string()
+  } else {
+    host.trim().to_string()
+  };
+
+  let listener = tokio::net::TcpListener::bind((bind_host.as_str(), port))
+    .await
+    .map_err(|err| bind_error(err, port))?;
+  let addr = listener
+    .local_addr()
+    .map_err(|err| AppError::internal(format!("Failed reading server address: {}", err)))?;
+
+  let status = RemoteServerStatus {
+    running: true,
+    host: bind_host.clone(),
+    port: addr.port(),
+    server_url: advertised_remote_url(&bind_host, addr),
+  };
+  Ok(BoundRemoteServer {
+    listener,
+    app: remote_router(api_state),
+    status,
+  })
+}
+
+fn bind_error(err: std::io::Error, port: u16) -> AppError {
+  let message = format!("Failed binding remote server: {}", err);
+  match err.kind() {
+    std::io::ErrorKind::AddrInUse => AppError::conflict(message).with_field("port"),
+    std::io::ErrorKind::AddrNotAvailable => AppError::invalid(message).with_field("host"),
+    std::io::ErrorKind::PermissionDenied if port < 1024 => {
+      AppError::permission_denied(message).with_field("port")
+    }
+    _ => AppError::internal(message),
+  }
+}
+
+fn stopped_remote_status() -> RemoteServerStatus {
+  RemoteServerStatus {
+    running: false,
+    host: "".to_string(),
+    port: 0,
+    server_url: "".to_string(),
+  }
+}
+
+fn advertised_remote_url(bind_host: &str, addr: std::net::SocketAddr) -> String {
+  if bind_host == "0.0.0.0" {
+    if let Ok(ip) = local_ip_address::local_ip() {
+      return format!("http://{}:{}", ip, addr.port());
+    }
+  }
+  format!("http://{}:{}", addr.ip(), addr.port())
+}
+
+fn now_iso() -> String {
+  Utc::now().to_rfc3339()
+}
+
+fn normalize_slug(raw: &str) -> String {
+  let mut slug = raw.trim().to_lowercase();
+  if slug.is_empty() {
+    slug = "new-project".to_string();
+  }
+
+  let mut sanitized = String::new();
+  for ch in slug.chars() {
+    if ch.is_ascii_alphanumeric() || ch == '-' {
+      sanitized.push(ch);
+    } else if ch.is_ascii_whitespace() || ch == '_' {
+      sanitized.push('-');
+    }
+  }
+
+  while sanitized.contains("--") {
     sanitized = sanitized.replace("--", "-");
   }
 
@@ -390,47 +1428,64 @@ fn normalize_site_url(raw: &str) -> String {
   format!("https://{}", trimmed)
 }
 
-fn read_project_metadata(project_dir: &Path) -> Result<ProjectMetadata, String> {
-  let meta_path = project_dir.join(PROJECT_META_FILE);
-  let content = fs::read_to_string(&meta_path)
-    .map_err(|err| format!("Failed reading {}: {}", meta_path.display(), err))?;
-  serde_json::from_str::<ProjectMetadata>(&content)
-    .map_err(|err| format!("Failed parsing {}: {}", meta_path.display(), err))
+fn read_project_metadata(project_dir: &Path) -> Result<ProjectMetadata, AppError> {
+  read_json_file::<ProjectMetadata>(&project_dir.join(PROJECT_META_FILE))
 }
 
-fn write_project_metadata(project_dir: &Path, metadata: &ProjectMetadata) -> Result<(), String> {
-  let meta_path = project_dir.join(PROJECT_META_FILE);
-  let content =
-    serde_json::to_string_pretty(metadata).map_err(|err| format!("Failed serializing metadata: {}", err))?;
-  fs::write(&meta_path, content)
-    .map_err(|err| format!("Failed writing {}: {}", meta_path.display(), err))
+fn write_project_metadata(project_dir: &Path, metadata: &ProjectMetadata) -> Result<(), AppError> {
+  write_json_file(&project_dir.join(PROJECT_META_FILE), metadata)
 }
 
-fn project_record_from_dir(project_dir: &Path) -> Result<ProjectRecord, String> {
+fn project_record_from_dir(project_dir: &Path) -> Result<ProjectRecord, AppError> {
   let metadata = read_project_metadata(project_dir)?;
-  let path = project_dir
-    .to_str()
-    .ok_or_else(|| "Project path is not valid UTF-8".to_string())?
-    .to_string();
+  let path = project_path_string(project_dir)?;
   Ok(ProjectRecord {
     id: path.clone(),
     name: metadata.name,
+    slug: metadata.slug,
     path,
+    created_at: metadata.created_at,
     updated_at: metadata.updated_at,
     site_url: metadata.site_url,
+    tags: metadata.tags,
+    client_name: metadata.client_name,
+    pinned: metadata.pinned,
+    color: metadata.color,
   })
 }
 
-fn write_json_file<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
-  let content =
-    serde_json::to_string_pretty(value).map_err(|err| format!("Failed serializing {}: {}", path.display(), err))?;
-  fs::write(path, content).map_err(|err| format!("Failed writing {}: {}", path.display(), err))
+/// Bumps `updated_at` and records the change in the activity log. Document saves do the same
+/// as part of their revision bump.
+fn touch_project(
+  project_dir: &Path,
+  operation: ActivityOperation,
+  pages: Vec<String>,
+  source: ChangeSource,
+) -> Result<(), AppError> {
+  let mut metadata = read_project_metadata(project_dir)?;
+  metadata.updated_at = now_iso();
+  write_project_metadata(project_dir, &metadata)?;
+  activity::record(project_dir, &metadata.updated_at, operation, pages, source);
+  Ok(())
+}
+
+fn write_json_file<T: Serialize>(path: &Path, value: &T) -> Result<(), AppError> {
+  let content = serde_json::to_string_pretty(value).map_err(|err| {
+    AppError::internal(format!("Failed serializing {}: {}", path.display(), err)).with_path(path)
+  })?;
+  fs::write(path, content).map_err(|err| AppError::fs("Failed writing", path, err))
+}
+
+fn read_json_file<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, AppError> {
+  let content = fs::read_to_string(path).map_err(|err| AppError::fs("Failed reading", path, err))?;
+  serde_json::from_str::<T>(&content).map_err(|err| AppError::corrupt(path, err))
 }
 
-fn read_json_file<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, String> {
-  let content =
-    fs::read_to_string(path).map_err(|err| format!("Failed reading {}: {}", path.display(), err))?;
-  serde_json::from_str::<T>(&content).map_err(|err| format!("Failed parsing {}: {}", path.display(), err))
+fn project_path_string(project_dir: &Path) -> Result<String, AppError> {
+  project_dir
+    .to_str()
+    .map(|value| value.to_string())
+    .ok_or_else(|| AppError::invalid("Project path is not valid UTF-8").with_field("projectPath"))
 }
 
 fn page_doc_path(project_dir: &Path, page: &PageDoc) -> PathBuf {
@@ -574,11 +1629,10 @@ fn normalize_builder_doc(doc: BuilderProjectDoc) -> BuilderProjectDoc {
   }
 }
 
-fn persist_builder_doc(project_dir: &Path, doc: &BuilderProjectDoc) -> Result<(), String> {
+fn persist_builder_doc(project_dir: &Path, doc: &BuilderProjectDoc) -> Result<(), AppError> {
   let normalized = normalize_builder_doc(doc.clone());
   let pages_dir = project_dir.join(PAGES_DIR);
-  fs::create_dir_all(&pages_dir)
-    .map_err(|err| format!("Failed creating pages dir {}: {}", pages_dir.display(), err))?;
+  fs::create_dir_all(&pages_dir).map_err(|err| AppError::fs("Failed creating pages dir", &pages_dir, err))?;
 
   write_json_file(&project_dir.join(SITE_FILE), &normalized.site)?;
   write_json_file(&project_dir.join(SITEMAP_FILE), &normalized.sitemap)?;
@@ -590,8 +1644,8 @@ fn persist_builder_doc(project_dir: &Path, doc: &BuilderProjectDoc) -> Result<()
     write_json_file(&page_path, page)?;
   }
 
-  let existing = fs::read_dir(&pages_dir)
-    .map_err(|err| format!("Failed reading pages dir {}: {}", pages_dir.display(), err))?;
+  let existing =
+    fs::read_dir(&pages_dir).map_err(|err| AppError::fs("Failed reading pages dir", &pages_dir, err))?;
   for entry in existing {
     let Ok(entry) = entry else {
       continue;
@@ -608,24 +1662,10 @@ fn persist_builder_doc(project_dir: &Path, doc: &BuilderProjectDoc) -> Result<()
   Ok(())
 }
 
-fn load_builder_doc(project_dir: &Path) -> Result<BuilderProjectDoc, String> {
-  let metadata = read_project_metadata(project_dir)?;
-  let site_path = project_dir.join(SITE_FILE);
-  let sitemap_path = project_dir.join(SITEMAP_FILE);
-  let pages_dir = project_dir.join(PAGES_DIR);
-
-  if !site_path.exists() || !sitemap_path.exists() || !pages_dir.exists() {
-    let doc = default_builder_doc(&metadata);
-    persist_builder_doc(project_dir, &doc)?;
-    return Ok(doc);
-  }
-
-  let site = read_json_file::<SiteDoc>(&site_path)?;
-  let sitemap = read_json_file::<SitemapDoc>(&sitemap_path)?;
+fn read_page_docs(pages_dir: &Path) -> Result<HashMap<String, PageDoc>, AppError> {
   let mut pages_by_id: HashMap<String, PageDoc> = HashMap::new();
-
   let entries =
-    fs::read_dir(&pages_dir).map_err(|err| format!("Failed reading pages dir: {}", err))?;
+    fs::read_dir(pages_dir).map_err(|err| AppError::fs("Failed reading pages dir", pages_dir, err))?;
   for entry in entries {
     let Ok(entry) = entry else {
       continue;
@@ -638,6 +1678,144 @@ fn load_builder_doc(project_dir: &Path) -> Result<BuilderProjectDoc, String> {
       pages_by_id.insert(page.id.clone(), page);
     }
   }
+  Ok(pages_by_id)
+}
+
+/// Persists an editor save, bumps the project revision and notifies listeners. Saves that
+/// touch a page locked by another client are rejected before anything is written. Callers
+/// hold the project's `ProjectWriteLocks` entry.
+fn save_builder_doc(
+  project_dir: &Path,
+  doc: &BuilderProjectDoc,
+  events: &EventHub,
+  locks: &PageLockHub,
+  client_id: &str,
+  source: ChangeSource,
+  operation: ActivityOperation,
+) -> Result<u64, AppError> {
+  let normalized = normalize_builder_doc(doc.clone());
+  let changes = page_changes(project_dir, &normalized)?;
+  locks.check_save(project_dir, client_id, &changes.touched())?;
+  commit_builder_doc(project_dir, &normalized, changes, events, source, operation)
+}
+
+/// Pages a document write adds, changes or removes relative to what is on disk.
+struct PageChanges {
+  added: Vec<String>,
+  updated: Vec<String>,
+  removed: Vec<String>,
+}
+
+impl PageChanges {
+  fn touched(&self) -> Vec<String> {
+    self
+      .added
+      .iter()
+      .chain(&self.updated)
+      .chain(&self.removed)
+      .cloned()
+      .collect()
+  }
+}
+
+fn page_changes(project_dir: &Path, doc: &BuilderProjectDoc) -> Result<PageChanges, AppError> {
+  let pages_dir = project_dir.join(PAGES_DIR);
+  let previous_pages = if pages_dir.is_dir() {
+    read_page_docs(&pages_dir)?
+  } else {
+    HashMap::new()
+  };
+
+  let mut added = Vec::new();
+  let mut updated = Vec::new();
+  for page in &doc.pages {
+    match previous_pages.get(&page.id) {
+      None => added.push(page.id.clone()),
+      Some(previous) if previous != page => updated.push(page.id.clone()),
+      Some(_) => {}
+    }
+  }
+  let mut removed: Vec<String> = previous_pages
+    .keys()
+    .filter(|page_id| !doc.pages.iter().any(|page| &page.id == *page_id))
+    .cloned()
+    .collect();
+  removed.sort();
+  Ok(PageChanges {
+    added,
+    updated,
+    removed,
+  })
+}
+
+/// Bookkeeping shared by every document write: persists it, bumps the revision and
+/// `updated_at`, records activity and publishes `projectSaved`/`pagesChanged`. Callers have
+/// already checked page locks.
+fn commit_builder_doc(
+  project_dir: &Path,
+  doc: &BuilderProjectDoc,
+  changes: PageChanges,
+  events: &EventHub,
+  source: ChangeSource,
+  operation: ActivityOperation,
+) -> Result<u64, AppError> {
+  persist_builder_doc(project_dir, doc)?;
+  let revision = commit_revision(project_dir, events, source, operation, changes.touched())?;
+  let PageChanges {
+    added,
+    updated,
+    removed,
+  } = changes;
+  if !added.is_empty() || !updated.is_empty() || !removed.is_empty() {
+    events.publish(ProjectEvent::PagesChanged {
+      project_path: project_path_string(project_dir)?,
+      revision,
+      source,
+      added,
+      updated,
+      removed,
+    });
+  }
+  Ok(revision)
+}
+
+/// Bumps the revision and `updated_at` after project content was written, records activity and
+/// publishes `projectSaved`. Document saves and `theme.json` writes both end here.
+fn commit_revision(
+  project_dir: &Path,
+  events: &EventHub,
+  source: ChangeSource,
+  operation: ActivityOperation,
+  pages: Vec<String>,
+) -> Result<u64, AppError> {
+  let mut metadata = read_project_metadata(project_dir)?;
+  metadata.revision += 1;
+  metadata.updated_at = now_iso();
+  write_project_metadata(project_dir, &metadata)?;
+  activity::record(project_dir, &metadata.updated_at, operation, pages, source);
+  events.publish(ProjectEvent::ProjectSaved {
+    project_path: project_path_string(project_dir)?,
+    revision: metadata.revision,
+    source,
+  });
+  Ok(metadata.revision)
+}
+
+fn load_builder_doc(project_dir: &Path) -> Result<BuilderProjectDoc, AppError> {
+  let metadata = read_project_metadata(project_dir)?;
+  let site_path = project_dir.join(SITE_FILE);
+  let sitemap_path = project_dir.join(SITEMAP_FILE);
+  let pages_dir = project_dir.join(PAGES_DIR);
+
+  if !site_path.exists() || !sitemap_path.exists() || !pages_dir.exists() {
+    let doc = default_builder_doc(&metadata);
+    persist_builder_doc(project_dir, &doc)?;
+    return Ok(doc);
+  }
+
+  let site = read_json_file::<SiteDoc>(&site_path)?;
+  let sitemap = read_json_file::<SitemapDoc>(&sitemap_path)?;
+  let mut pages_by_id = read_page_docs(&pages_dir)?;
 
   if pages_by_id.is_empty() {
     let doc = default_builder_doc(&metadata);
@@ -665,47 +1843,42 @@ fn load_builder_doc(project_dir: &Path) -> Result<BuilderProjectDoc, String> {
   Ok(loaded)
 }
 
-#[tauri::command]
-fn list_projects(workspace_root: String) -> Result<Vec<ProjectRecord>, String> {
+/// Projects in a workspace matching `query`, served from the listing cache. A workspace that
+/// does not exist yet is simply empty.
+fn query_workspace_projects(
+  index: &ProjectIndex,
+  workspace_root: &str,
+  query: &ProjectQuery,
+) -> Result<ProjectPage, AppError> {
   if workspace_root.trim().is_empty() {
-    return Err("Workspace root is required.".to_string());
+    return Err(AppError::invalid("Workspace root is required.").with_field("workspaceRoot"));
   }
-  let workspace = PathBuf::from(workspace_root);
+  let workspace = Path::new(workspace_root);
   if !workspace.exists() {
-    return Ok(Vec::new());
+    return Ok(ProjectPage::default());
   }
   if !workspace.is_dir() {
-    return Err("Workspace root must be a directory".to_string());
+    return Err(AppError::invalid("Workspace root must be a directory").with_field("workspaceRoot"));
   }
+  index.query(workspace, query)
+}
 
-  let mut projects = Vec::new();
-  let entries =
-    fs::read_dir(&workspace).map_err(|err| format!("Failed reading workspace: {}", err))?;
-
-  for entry in entries {
-    let Ok(entry) = entry else {
-      continue;
-    };
-    let path = entry.path();
-    if !path.is_dir() {
-      continue;
-    }
-    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
-      continue;
-    };
-    if !name.ends_with(".manifold") {
-      continue;
-    }
-    if !path.join(PROJECT_META_FILE).exists() {
-      continue;
-    }
-    if let Ok(record) = project_record_from_dir(&path) {
-      projects.push(record);
-    }
-  }
+#[tauri::command]
+fn list_projects(
+  services: tauri::State<'_, LiveServices>,
+  workspace_root: String,
+) -> Result<Vec<ProjectRecord>, AppError> {
+  query_workspace_projects(&services.index, &workspace_root, &ProjectQuery::default())
+    .map(|page| page.projects)
+}
 
-  projects.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-  Ok(projects)
+#[tauri::command]
+fn query_projects(
+  services: tauri::State<'_, LiveServices>,
+  workspace_root: String,
+  query: Option<ProjectQuery>,
+) -> Result<ProjectPage, AppError> {
+  query_workspace_projects(&services.index, &workspace_root, &query.unwrap_or_default())
 }
 
 #[tauri::command]
@@ -714,27 +1887,32 @@ fn create_project(
   name: String,
   slug: String,
   site_url: String,
-) -> Result<ProjectRecord, String> {
+  template: Option<String>,
+  client_name: Option<String>,
+) -> Result<ProjectRecord, AppError> {
+  let template = template.map(|id| id.trim().to_string()).filter(|id| !id.is_empty());
   let workspace = PathBuf::from(workspace_root);
   if !workspace.exists() {
-    fs::create_dir_all(&workspace)
-      .map_err(|err| format!("Failed creating workspace {}: {}", workspace.display(), err))?;
+    fs::create_dir_all(&workspace).map_err(|err| AppError::fs("Failed creating workspace", &workspace, err))?;
   }
   if !workspace.is_dir() {
-    return Err("Workspace root must be a directory".to_string());
+    return Err(AppError::invalid("Workspace root must be a directory").with_field("workspaceRoot"));
   }
 
   let normalized_slug = normalize_slug(&slug);
   if normalized_slug.is_empty() {
-    return Err("Project slug is required".to_string());
+    return Err(AppError::invalid("Project slug is required").with_field("slug"));
   }
   let project_dir = workspace.join(format!("{}.manifold", normalized_slug));
   if project_dir.exists() {
-    return Err(format!("Project {} already exists", project_dir.display()));
+    return Err(
+      AppError::conflict(format!("Project {} already exists", project_dir.display())).with_path(&project_dir),
+    );
   }
 
-  fs::create_dir_all(&project_dir)
-    .map_err(|err| format!("Failed creating project {}: {}", project_dir.display(), err))?;
+  let client_name = normalize_client_name(client_name.as_deref().unwrap_or_default())?;
+
+  fs::create_dir_all(&project_dir).map_err(|err| AppError::fs("Failed creating project", &project_dir, err))?;
 
   let timestamp = now_iso();
   let metadata = ProjectMetadata {
@@ -743,24 +1921,418 @@ fn create_project(
     site_url: normalize_site_url(&site_url),
     created_at: timestamp.clone(),
     updated_at: timestamp,
+    revision: 0,
+    tags: Vec::new(),
+    client_name,
+    pinned: false,
+    color: ProjectColor::None,
+    format_version: PROJECT_FORMAT_VERSION,
   };
   write_project_metadata(&project_dir, &metadata)?;
-  let builder_doc = default_builder_doc(&metadata);
-  persist_builder_doc(&project_dir, &builder_doc)?;
+  let Some(template) = template else {
+    let builder_doc = default_builder_doc(&metadata);
+    persist_builder_doc(&project_dir, &builder_doc)?;
+    schema::write_schemas(&project_dir.join(schema::SCHEMAS_DIR))?;
+    return project_record_from_dir(&project_dir);
+  };
+
+  let placeholders = templates::Placeholders {
+    project_name: &metadata.name,
+    site_url: &metadata.site_url,
+    client_name: if metadata.client_name.is_empty() {
+      &metadata.name
+    } else {
+      &metadata.client_name
+    },
+  };
+  if let Err(err) = templates::apply_template(&workspace, &template, &project_dir, &placeholders) {
+    let _ = fs::remove_dir_all(&project_dir);
+    return Err(err);
+  }
   project_record_from_dir(&project_dir)
 }
 
+/// A fresh `site` project in `workspace`, for tests that need one on disk.
+#[cfg(test)]
+pub(crate) fn test_project(workspace: &Path) -> PathBuf {
+  let record = create_project(
+    workspace.to_string_lossy().into_owned(),
+    "Site".to_string(),
+    "site".to_string(),
+    String::new(),
+    None,
+    None,
+  )
+  .expect("test project");
+  PathBuf::from(record.path)
+}
+
+/// Templates `create_project` accepts: the built-ins plus `<workspace>/templates/*.manifold`.
+#[tauri::command]
+fn list_project_templates(workspace_root: String) -> Result<Vec<templates::ProjectTemplate>, AppError> {
+  if workspace_root.trim().is_empty() {
+    return Err(AppError::invalid("Workspace root is required.").with_field("workspaceRoot"));
+  }
+  templates::list_templates(Path::new(&workspace_root))
+}
+
+/// Renumbers every block `block-1`, `block-2`, ... in page order, so ids are unique within the
+/// project and copying the same project twice gives the same ids. They are not unique across
+/// projects: a source that was itself renumbered, such as a template instance or an earlier
+/// copy, keeps its ids. Nothing refers to a block outside its own project.
+fn renumber_block_ids(doc: &mut BuilderProjectDoc) {
+  let blocks = doc.pages.iter_mut().flat_map(|page| page.blocks.iter_mut());
+  for (index, block) in blocks.enumerate() {
+    block.id = format!("block-{}", index + 1);
+  }
+}
+
+#[tauri::command]
+fn duplicate_project(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  new_name: String,
+  new_slug: String,
+) -> Result<ProjectRecord, AppError> {
+  duplicate_project_dir(
+    &services,
+    Path::new(&project_path),
+    &new_name,
+    &new_slug,
+    ChangeSource::Desktop,
+  )
+}
+
+/// Copies a project into a sibling `<new_slug>.manifold` as a fresh project: new name and
+/// timestamps, revision back to 0, renumbered block ids. Pages, theme, content and assets come
+/// along; exports and other machine-local files do not. The copy's activity log starts with
+/// the duplication and listeners get a `projectDuplicated` event.
+fn duplicate_project_dir(
+  services: &LiveServices,
+  source_dir: &Path,
+  new_name: &str,
+  new_slug: &str,
+  source: ChangeSource,
+) -> Result<ProjectRecord, AppError> {
+  if !source_dir.join(PROJECT_META_FILE).is_file() {
+    return Err(AppError::project_not_found(source_dir));
+  }
+  let name = new_name.trim().to_string();
+  if name.is_empty() {
+    return Err(AppError::invalid("Project name is required").with_field("newName"));
+  }
+  let slug = normalize_slug(new_slug);
+  if slug.is_empty() {
+    return Err(AppError::invalid("Project slug is required").with_field("newSlug"));
+  }
+  let workspace = source_dir
+    .parent()
+    .ok_or_else(|| AppError::invalid("Project has no parent directory").with_path(source_dir))?;
+  let project_dir = workspace.join(format!("{}.manifold", slug));
+  if project_dir.exists() {
+    return Err(
+      AppError::conflict(format!("Project {} already exists", project_dir.display())).with_path(&project_dir),
+    );
+  }
+
+  let original = read_project_metadata(source_dir)?;
+  let mut doc = load_builder_doc(source_dir)?;
+  doc.site.site_name = name.clone();
+  doc.selected_page_id = doc.sitemap.root_page_id.clone();
+  renumber_block_ids(&mut doc);
+  let timestamp = now_iso();
+  let metadata = ProjectMetadata {
+    name,
+    slug,
+    site_url: original.site_url,
+    created_at: timestamp.clone(),
+    updated_at: timestamp,
+    revision: 0,
+    tags: original.tags,
+    client_name: original.client_name,
+    pinned: false,
+    color: original.color,
+    format_version: PROJECT_FORMAT_VERSION,
+  };
+
+  fs::create_dir_all(&project_dir).map_err(|err| AppError::fs("Failed creating project", &project_dir, err))?;
+  let written = write_duplicate(source_dir, &project_dir, &metadata, &doc);
+  if let Err(err) = written {
+    let _ = fs::remove_dir_all(&project_dir);
+    return Err(err);
+  }
+
+  activity::record(&project_dir, &metadata.updated_at, ActivityOperation::Duplicate, Vec::new(), source);
+  let record = project_record_from_dir(&project_dir)?;
+  services.events.publish(ProjectEvent::ProjectDuplicated {
+    project_path: project_path_string(source_dir)?,
+    project: record.clone(),
+    source,
+  });
+  Ok(record)
+}
+
+fn write_duplicate(
+  source_dir: &Path,
+  project_dir: &Path,
+  metadata: &ProjectMetadata,
+  doc: &BuilderProjectDoc,
+) -> Result<(), AppError> {
+  write_project_metadata(project_dir, metadata)?;
+  persist_builder_doc(project_dir, doc)?;
+  // Metadata, site, sitemap and pages were just rewritten; copy the rest as-is.
+  let rewritten = [PROJECT_META_FILE, SITE_FILE, SITEMAP_FILE];
+  for (relative, source) in archive::project_files(source_dir)? {
+    if rewritten.contains(&relative.as_str()) || relative.starts_with(&format!("{}/", PAGES_DIR)) {
+      continue;
+    }
+    let target = project_dir.join(&relative);
+    if let Some(parent) = target.parent() {
+      fs::create_dir_all(parent).map_err(|err| AppError::fs("Failed creating", parent, err))?;
+    }
+    fs::copy(&source, &target).map_err(|err| AppError::fs("Failed copying", &source, err))?;
+  }
+  schema::write_schemas(&project_dir.join(schema::SCHEMAS_DIR))
+}
+
+/// Moves a project directory with a single rename, so it is never left half-moved, and points
+/// live sessions at the new path. Refused while an agent run is working inside the project,
+/// since its process runs there.
+fn move_project_dir(services: &LiveServices, from: &Path, to: &Path) -> Result<(), AppError> {
+  if to.exists() {
+    return Err(AppError::conflict(format!("Project {} already exists", to.display())).with_path(to));
+  }
+  if services.runner.has_running(from)? {
+    return Err(AppError::conflict(
+      "An agent run is working in this project; cancel it or wait for it to finish first.",
+    ));
+  }
+  let resolved_from = from
+    .canonicalize()
+    .map_err(|err| AppError::fs("Failed resolving project", from, err))?;
+  fs::rename(from, to).map_err(|err| AppError::fs("Failed moving project", from, err))?;
+  services.move_project(from, &resolved_from, to)
+}
+
+/// Renames a project and re-slugs its `<slug>.manifold` directory. Live sessions follow the
+/// directory and listeners get a `projectRenamed` event.
+fn rename_project_dir(
+  services: &LiveServices,
+  project_dir: &Path,
+  new_name: &str,
+  new_slug: &str,
+  source: ChangeSource,
+) -> Result<ProjectRecord, AppError> {
+  if !project_dir.join(PROJECT_META_FILE).is_file() {
+    return Err(AppError::project_not_found(project_dir));
+  }
+  let name = new_name.trim().to_string();
+  if name.is_empty() {
+    return Err(AppError::invalid("Project name is required").with_field("newName"));
+  }
+  let slug = normalize_slug(new_slug);
+  if slug.is_empty() {
+    return Err(AppError::invalid("Project slug is required").with_field("newSlug"));
+  }
+  let workspace = project_dir
+    .parent()
+    .ok_or_else(|| AppError::invalid("Project has no parent directory").with_path(project_dir))?;
+  let dir_name = format!("{}.manifold", slug);
+  let target = workspace.join(&dir_name);
+
+  let previous = read_project_metadata(project_dir)?;
+  let metadata = ProjectMetadata {
+    name,
+    slug,
+    updated_at: now_iso(),
+    ..previous.clone()
+  };
+  write_project_metadata(project_dir, &metadata)?;
+  if project_dir.file_name() != Some(dir_name.as_ref()) {
+    if let Err(err) = move_project_dir(services, project_dir, &target) {
+      let _ = write_project_metadata(project_dir, &previous);
+      return Err(err);
+    }
+  }
+
+  activity::record(&target, &metadata.updated_at, ActivityOperation::Rename, Vec::new(), source);
+  let record = project_record_from_dir(&target)?;
+  services.events.publish(ProjectEvent::ProjectRenamed {
+    project_path: project_path_string(project_dir)?,
+    project: record.clone(),
+    source,
+  });
+  Ok(record)
+}
+
 #[tauri::command]
-fn update_project_site_url(project_path: String, site_url: String) -> Result<ProjectRecord, String> {
+fn rename_project(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  new_name: String,
+  new_slug: String,
+) -> Result<ProjectRecord, AppError> {
+  rename_project_dir(
+    &services,
+    Path::new(&project_path),
+    &new_name,
+    &new_slug,
+    ChangeSource::Desktop,
+  )
+}
+
+#[tauri::command]
+fn archive_project(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+) -> Result<shelf::ShelvedProject, AppError> {
+  let project_dir = Path::new(&project_path);
+  shelf::shelve_project(&services, project_dir, shelf::ProjectShelf::Archive, ChangeSource::Desktop)
+}
+
+#[tauri::command]
+fn trash_project(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+) -> Result<shelf::ShelvedProject, AppError> {
+  let project_dir = Path::new(&project_path);
+  shelf::shelve_project(&services, project_dir, shelf::ProjectShelf::Trash, ChangeSource::Desktop)
+}
+
+#[tauri::command]
+fn restore_project(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+) -> Result<ProjectRecord, AppError> {
+  shelf::restore_project(&services, Path::new(&project_path), ChangeSource::Desktop)
+}
+
+/// Archived and trashed projects, which `list_projects` leaves out.
+#[tauri::command]
+fn list_shelved_projects(
+  workspace_root: String,
+  shelf: Option<shelf::ProjectShelf>,
+) -> Result<Vec<shelf::ShelvedProject>, AppError> {
+  if workspace_root.trim().is_empty() {
+    return Err(AppError::invalid("Workspace root is required.").with_field("workspaceRoot"));
+  }
+  shelf::list_shelved_projects(Path::new(&workspace_root), shelf)
+}
+
+#[tauri::command]
+fn request_project_deletion(project_path: String) -> Result<shelf::DeletionRequest, AppError> {
+  shelf::request_deletion(Path::new(&project_path))
+}
+
+#[tauri::command]
+fn delete_project(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  confirmation_token: String,
+) -> Result<(), AppError> {
+  shelf::delete_project(
+    &services,
+    Path::new(&project_path),
+    &confirmation_token,
+    ChangeSource::Desktop,
+  )
+}
+
+#[tauri::command]
+fn update_project_site_url(project_path: String, site_url: String) -> Result<ProjectRecord, AppError> {
+  set_project_site_url(Path::new(&project_path), &site_url, ChangeSource::Desktop)
+}
+
+/// Recent changes to a project, newest first.
+#[tauri::command]
+fn get_project_activity(project_path: String, limit: Option<usize>) -> Result<Vec<ActivityEntry>, AppError> {
   let project_dir = PathBuf::from(project_path);
+  if !project_dir.join(PROJECT_META_FILE).is_file() {
+    return Err(AppError::project_not_found(&project_dir));
+  }
+  activity::read_activity(&project_dir, limit.unwrap_or(activity::DEFAULT_ACTIVITY_LIMIT))
+}
+
+#[tauri::command]
+fn update_project_details(project_path: String, details: ProjectDetailsPatch) -> Result<ProjectRecord, AppError> {
+  set_project_details(Path::new(&project_path), details, ChangeSource::Desktop)
+}
+
+fn set_project_details(
+  project_dir: &Path,
+  details: ProjectDetailsPatch,
+  source: ChangeSource,
+) -> Result<ProjectRecord, AppError> {
+  if !project_dir.join(PROJECT_META_FILE).is_file() {
+    return Err(AppError::project_not_found(project_dir));
+  }
+  let mut metadata = read_project_metadata(project_dir)?;
+  if let Some(tags) = details.tags {
+    metadata.tags = normalize_tags(&tags)?;
+  }
+  if let Some(client_name) = details.client_name {
+    metadata.client_name = normalize_client_name(&client_name)?;
+  }
+  if let Some(pinned) = details.pinned {
+    metadata.pinned = pinned;
+  }
+  if let Some(color) = details.color {
+    metadata.color = color;
+  }
+  metadata.updated_at = now_iso();
+  write_project_metadata(project_dir, &metadata)?;
+  activity::record(project_dir, &metadata.updated_at, ActivityOperation::UpdateDetails, Vec::new(), source);
+  project_record_from_dir(project_dir)
+}
+
+/// Trimmed tags without blanks or case-insensitive repeats, in the order given.
+fn normalize_tags(tags: &[String]) -> Result<Vec<String>, AppError> {
+  let mut normalized: Vec<String> = Vec::new();
+  for tag in tags {
+    let tag = tag.trim();
+    if tag.is_empty() || normalized.iter().any(|kept| kept.to_lowercase() == tag.to_lowercase()) {
+      continue;
+    }
+    if tag.chars().count() > MAX_TAG_CHARS {
+      return Err(
+        AppError::invalid(format!("Tags are limited to {} characters", MAX_TAG_CHARS)).with_field("tags"),
+      );
+    }
+    normalized.push(tag.to_string());
+  }
+  if normalized.len() > MAX_PROJECT_TAGS {
+    return Err(
+      AppError::invalid(format!("Projects are limited to {} tags", MAX_PROJECT_TAGS)).with_field("tags"),
+    );
+  }
+  Ok(normalized)
+}
+
+fn normalize_client_name(client_name: &str) -> Result<String, AppError> {
+  let client_name = client_name.trim();
+  if client_name.chars().count() > MAX_CLIENT_NAME_CHARS {
+    return Err(
+      AppError::invalid(format!("Client names are limited to {} characters", MAX_CLIENT_NAME_CHARS))
+        .with_field("clientName"),
+    );
+  }
+  Ok(client_name.to_string())
+}
+
+fn set_project_site_url(
+  project_dir: &Path,
+  site_url: &str,
+  source: ChangeSource,
+) -> Result<ProjectRecord, AppError> {
   if !project_dir.is_dir() {
-    return Err("Project path is invalid".to_string());
+    return Err(AppError::project_not_found(project_dir));
   }
-  let mut metadata = read_project_metadata(&project_dir)?;
-  metadata.site_url = normalize_site_url(&site_url);
+  let mut metadata = read_project_metadata(project_dir)?;
+  metadata.site_url = normalize_site_url(site_url);
   metadata.updated_at = now_iso();
-  write_project_metadata(&project_dir, &metadata)?;
-  project_record_from_dir(&project_dir)
+  write_project_metadata(project_dir, &metadata)?;
+  activity::record(project_dir, &metadata.updated_at, ActivityOperation::UpdateSiteUrl, Vec::new(), source);
+  project_record_from_dir(project_dir)
 }
 
 #[tauri::command]
@@ -772,53 +2344,307 @@ fn pick_workspace_directory() -> Option<String> {
 }
 
 #[tauri::command]
-fn load_builder_project(project_path: String) -> Result<BuilderProjectDoc, String> {
+fn load_builder_project(project_path: String) -> Result<BuilderProjectDoc, AppError> {
   let project_dir = PathBuf::from(project_path);
   if !project_dir.is_dir() {
-    return Err("Project path is invalid".to_string());
+    return Err(AppError::project_not_found(&project_dir));
   }
   load_builder_doc(&project_dir)
 }
 
 #[tauri::command]
-fn save_builder_project(project_path: String, document: BuilderProjectDoc) -> Result<(), String> {
+fn get_project_schemas() -> BTreeMap<String, serde_json::Value> {
+  schema::project_schemas()
+}
+
+#[tauri::command]
+fn validate_project_files(project_path: String) -> Result<ProjectFilesReport, AppError> {
+  schema::validate_project_files(Path::new(&project_path))
+}
+
+#[tauri::command]
+fn export_project_archive(
+  project_path: String,
+  destination: Option<String>,
+) -> Result<archive::ArchiveExport, AppError> {
+  archive::export_project_archive(Path::new(&project_path), destination.as_deref().map(Path::new))
+}
+
+#[tauri::command]
+fn import_project_archive(workspace_root: String, archive_path: String) -> Result<ProjectRecord, AppError> {
+  archive::import_project_archive(Path::new(&workspace_root), Path::new(&archive_path))
+}
+
+#[tauri::command]
+fn save_builder_project(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  document: BuilderProjectDoc,
+  client_id: Option<String>,
+) -> Result<(), AppError> {
   let project_dir = PathBuf::from(project_path);
   if !project_dir.is_dir() {
-    return Err("Project path is invalid".to_string());
+    return Err(AppError::project_not_found(&project_dir));
   }
-  persist_builder_doc(&project_dir, &document)
+  let client_id = client_id.unwrap_or_else(|| DESKTOP_CLIENT_ID.to_string());
+  services.writes.run(&project_dir, || {
+    save_builder_doc(
+      &project_dir,
+      &document,
+      &services.events,
+      &services.locks,
+      &client_id,
+      ChangeSource::Desktop,
+      ActivityOperation::Save,
+    )?;
+    services.collab.reload(&project_dir)
+  })
+}
+
+#[tauri::command]
+fn run_agent_command(
+  services: tauri::State<'_, LiveServices>,
+  request: serde_json::Value,
+) -> AgentResponse {
+  dispatch_agent_command(request, &services)
+}
+
+#[tauri::command]
+fn start_agent_session(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  mode: AgentAccessMode,
+  operations: Vec<AgentOperation>,
+) -> Result<AgentSessionPolicy, AppError> {
+  services
+    .policy
+    .start_session(Path::new(&project_path), mode, operations)
+}
+
+#[tauri::command]
+fn update_agent_session(
+  services: tauri::State<'_, LiveServices>,
+  session_id: String,
+  mode: Option<AgentAccessMode>,
+  operations: Option<Vec<AgentOperation>>,
+) -> Result<AgentSessionPolicy, AppError> {
+  services.policy.update_session(&session_id, mode, operations)
+}
+
+#[tauri::command]
+fn get_agent_session(
+  services: tauri::State<'_, LiveServices>,
+  session_id: String,
+) -> Result<AgentSessionPolicy, AppError> {
+  services.policy.session(&session_id)
+}
+
+#[tauri::command]
+fn end_agent_session(services: tauri::State<'_, LiveServices>, session_id: String) -> Result<(), AppError> {
+  services.policy.end_session(&session_id)
+}
+
+#[tauri::command]
+fn list_agent_denials(
+  services: tauri::State<'_, LiveServices>,
+  project_path: Option<String>,
+) -> Result<Vec<PolicyDenial>, AppError> {
+  services.policy.denials(project_path.as_deref().map(Path::new))
+}
+
+#[tauri::command]
+async fn start_agent_run(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  request: AgentRunRequest,
+) -> Result<AgentRunInfo, AppError> {
+  services.runner.start(Path::new(&project_path), request)
+}
+
+#[tauri::command]
+fn cancel_agent_run(services: tauri::State<'_, LiveServices>, run_id: String) -> Result<(), AppError> {
+  services.runner.cancel(&run_id)
+}
+
+#[tauri::command]
+fn list_agent_runs(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+) -> Result<Vec<AgentRunInfo>, AppError> {
+  services.runner.list(Path::new(&project_path))
+}
+
+#[tauri::command]
+fn open_builder_page(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  page_id: String,
+  lock: bool,
+  client_id: Option<String>,
+  display_name: Option<String>,
+) -> Result<Vec<PagePresence>, AppError> {
+  let client_id = client_id.unwrap_or_else(|| DESKTOP_CLIENT_ID.to_string());
+  services.locks.open_page(
+    Path::new(&project_path),
+    &client_id,
+    display_name.as_deref().unwrap_or(""),
+    &page_id,
+    lock,
+  )
+}
+
+#[tauri::command]
+fn close_builder_page(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  client_id: Option<String>,
+) -> Result<(), AppError> {
+  let client_id = client_id.unwrap_or_else(|| DESKTOP_CLIENT_ID.to_string());
+  services.locks.close_page(Path::new(&project_path), &client_id)
+}
+
+#[tauri::command]
+fn list_page_presence(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+) -> Result<Vec<PagePresence>, AppError> {
+  services.locks.presence(Path::new(&project_path))
+}
+
+#[tauri::command]
+fn collab_join(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  client_id: String,
+  display_name: String,
+) -> Result<CollabSnapshot, AppError> {
+  services.collab.join(Path::new(&project_path), &client_id, &display_name)
+}
+
+#[tauri::command]
+fn collab_submit(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  client_id: String,
+  operations: Vec<DocOperation>,
+) -> Result<SubmitOutcome, AppError> {
+  services
+    .collab
+    .submit(Path::new(&project_path), &client_id, operations, ChangeSource::Desktop)
+}
+
+#[tauri::command]
+fn collab_changes(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  client_id: String,
+  since: u64,
+) -> Result<CollabChanges, AppError> {
+  services.collab.changes_since(Path::new(&project_path), &client_id, since)
+}
+
+#[tauri::command]
+fn collab_update_presence(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  client_id: String,
+  page_id: Option<String>,
+  block_id: Option<String>,
+) -> Result<Vec<Participant>, AppError> {
+  services.collab.update_presence(Path::new(&project_path), &client_id, page_id, block_id)
+}
+
+#[tauri::command]
+fn collab_leave(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  client_id: String,
+) -> Result<(), AppError> {
+  services.collab.leave(Path::new(&project_path), &client_id)
+}
+
+#[tauri::command]
+fn submit_patch_proposal(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  patch: json_patch::Patch,
+  summary: Option<String>,
+  client_id: Option<String>,
+) -> Result<PatchProposal, AppError> {
+  let client_id = client_id.unwrap_or_else(|| DESKTOP_CLIENT_ID.to_string());
+  services.proposals.submit(
+    Path::new(&project_path),
+    patch,
+    summary.as_deref().unwrap_or(""),
+    &client_id,
+    ChangeSource::Desktop,
+  )
+}
+
+#[tauri::command]
+fn list_patch_proposals(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+) -> Result<Vec<PatchProposal>, AppError> {
+  services.proposals.list(Path::new(&project_path))
+}
+
+#[tauri::command]
+fn approve_patch_proposal(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  proposal_id: String,
+) -> Result<ProposalOutcome, AppError> {
+  services.proposals.approve(
+    &services,
+    Path::new(&project_path),
+    &proposal_id,
+    DESKTOP_CLIENT_ID,
+    ChangeSource::Desktop,
+  )
+}
+
+#[tauri::command]
+fn reject_patch_proposal(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+  proposal_id: String,
+  reason: Option<String>,
+) -> Result<PatchProposal, AppError> {
+  services
+    .proposals
+    .reject(Path::new(&project_path), &proposal_id, DESKTOP_CLIENT_ID, reason)
+}
+
+#[tauri::command]
+fn revert_last_change(
+  services: tauri::State<'_, LiveServices>,
+  project_path: String,
+) -> Result<ProposalOutcome, AppError> {
+  services.proposals.revert_last(
+    &services,
+    Path::new(&project_path),
+    DESKTOP_CLIENT_ID,
+    ChangeSource::Desktop,
+  )
 }
 
 #[tauri::command]
 async fn start_remote_server(
   state: tauri::State<'_, RemoteServerState>,
+  services: tauri::State<'_, LiveServices>,
   host: String,
   port: u16,
   token: String,
   workspace_root: String,
-) -> Result<RemoteServerStatus, String> {
-  if token.trim().is_empty() {
-    return Err("Remote server token is required.".to_string());
-  }
-  if workspace_root.trim().is_empty() {
-    return Err("Workspace root is required before starting remote server.".to_string());
-  }
-  let workspace = PathBuf::from(workspace_root.trim());
-  if !workspace.exists() {
-    return Err(format!(
-      "Workspace root does not exist: {}",
-      workspace.display()
-    ));
-  }
-  if !workspace.is_dir() {
-    return Err("Workspace root must be a directory before starting remote server.".to_string());
-  }
+) -> Result<RemoteServerStatus, AppError> {
+  validate_remote_settings(&token, &workspace_root)?;
 
   {
     let guard = state
       .handle
       .lock()
-      .map_err(|_| "Remote server state lock failed.".to_string())?;
+      .map_err(|_| AppError::lock("Remote server"))?;
     if let Some(active) = guard.as_ref() {
       return Ok(RemoteServerStatus {
         running: true,
@@ -829,34 +2655,17 @@ async fn start_remote_server(
     }
   }
 
-  let bind_host = if host.trim().is_empty() {
-    "0.0.0.0".to_string()
-  } else {
-    host.trim().to_string()
-  };
-
-  let listener = tokio::net::TcpListener::bind((bind_host.as_str(), port))
-    .await
-    .map_err(|err| format!("Failed binding remote server: {}", err))?;
-  let addr = listener
-    .local_addr()
-    .map_err(|err| format!("Failed reading server address: {}", err))?;
-
   let api_state = RemoteApiState {
     token,
     workspace_root: workspace_root.trim().to_string(),
+    frontend_dist: default_frontend_dist(),
+    services: services.inner().clone(),
   };
-  let app = Router::new()
-    .route("/health", get(remote_health))
-    .route("/api/remote-context", post(remote_context))
-    .route("/api/list-projects", post(remote_list_projects))
-    .route("/api/create-project", post(remote_create_project))
-    .route("/api/update-project-site-url", post(remote_update_project_site_url))
-    .route("/api/load-builder-project", post(remote_load_builder_project))
-    .route("/api/save-builder-project", post(remote_save_builder_project))
-    .fallback(any(remote_frontend_proxy))
-    .layer(CorsLayer::very_permissive())
-    .with_state(api_state);
+  let BoundRemoteServer {
+    listener,
+    app,
+    status,
+  } = bind_remote_server(&host, port, api_state).await?;
 
   let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
   tauri::async_runtime::spawn(async move {
@@ -868,33 +2677,37 @@ async fn start_remote_server(
     }
   });
 
-  let status = RemoteServerStatus {
-    running: true,
-    host: bind_host.clone(),
-    port: addr.port(),
-    server_url: advertised_remote_url(&bind_host, addr),
-  };
   let mut guard = state
     .handle
     .lock()
-    .map_err(|_| "Remote server state lock failed.".to_string())?;
+    .map_err(|_| AppError::lock("Remote server"))?;
   *guard = Some(RemoteServerHandle {
-    host: bind_host,
-    port: addr.port(),
+    host: status.host.clone(),
+    port: status.port,
     server_url: status.server_url.clone(),
     shutdown: Some(shutdown_tx),
   });
+  services.events.publish(ProjectEvent::ServerStatusChanged {
+    status: status.clone(),
+  });
   Ok(status)
 }
 
 #[tauri::command]
-fn stop_remote_server(state: tauri::State<'_, RemoteServerState>) -> Result<RemoteServerStatus, String> {
+fn stop_remote_server(
+  state: tauri::State<'_, RemoteServerState>,
+  services: tauri::State<'_, LiveServices>,
+) -> Result<RemoteServerStatus, AppError> {
   let mut guard = state
     .handle
     .lock()
-    .map_err(|_| "Remote server state lock failed.".to_string())?;
+    .map_err(|_| AppError::lock("Remote server"))?;
   let mut taken = guard.take();
   if let Some(handle) = taken.as_mut() {
+    // Announce the stop first: open event streams close on it, letting graceful shutdown finish.
+    services.events.publish(ProjectEvent::ServerStatusChanged {
+      status: stopped_remote_status(),
+    });
     if let Some(shutdown) = handle.shutdown.take() {
       let _ = shutdown.send(());
     }
@@ -903,11 +2716,13 @@ fn stop_remote_server(state: tauri::State<'_, RemoteServerState>) -> Result<Remo
 }
 
 #[tauri::command]
-fn get_remote_server_status(state: tauri::State<'_, RemoteServerState>) -> Result<RemoteServerStatus, String> {
+fn get_remote_server_status(
+  state: tauri::State<'_, RemoteServerState>,
+) -> Result<RemoteServerStatus, AppError> {
   let guard = state
     .handle
     .lock()
-    .map_err(|_| "Remote server state lock failed.".to_string())?;
+    .map_err(|_| AppError::lock("Remote server"))?;
   if let Some(active) = guard.as_ref() {
     return Ok(RemoteServerStatus {
       running: true,
@@ -924,13 +2739,57 @@ pub fn run() {
   tauri::Builder::default()
     .plugin(tauri_plugin_log::Builder::default().build())
     .manage(RemoteServerState::default())
+    .manage(LiveServices::new())
+    .setup(|app| {
+      let hub = app.state::<LiveServices>().events.clone();
+      tauri::async_runtime::spawn(events::forward_events_to_window(app.handle().clone(), hub));
+      Ok(())
+    })
     .invoke_handler(tauri::generate_handler![
       list_projects,
+      query_projects,
       create_project,
+      list_project_templates,
+      duplicate_project,
+      rename_project,
+      archive_project,
+      trash_project,
+      restore_project,
+      list_shelved_projects,
+      request_project_deletion,
+      delete_project,
       update_project_site_url,
+      update_project_details,
+      get_project_activity,
       pick_workspace_directory,
       load_builder_project,
+      get_project_schemas,
+      validate_project_files,
+      export_project_archive,
+      import_project_archive,
       save_builder_project,
+      run_agent_command,
+      start_agent_session,
+      update_agent_session,
+      get_agent_session,
+      end_agent_session,
+      list_agent_denials,
+      start_agent_run,
+      cancel_agent_run,
+      list_agent_runs,
+      open_builder_page,
+      close_builder_page,
+      list_page_presence,
+      collab_join,
+      collab_submit,
+      collab_changes,
+      collab_update_presence,
+      collab_leave,
+      submit_patch_proposal,
+      list_patch_proposals,
+      approve_patch_proposal,
+      reject_patch_proposal,
+      revert_last_change,
       start_remote_server,
       stop_remote_server,
       get_remote_server_status
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::error::AppError;
+use crate::events::{EventHub, ProjectEvent};
+use crate::{now_iso, project_path_string};
+
+/// Open pages are kept alive by re-opening (heartbeat) or by an open event stream.
+const PAGE_SESSION_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PagePresence {
+  client_id: String,
+  display_name: String,
+  page_id: String,
+  locked: bool,
+  since: String,
+  #[serde(skip)]
+  last_seen: Instant,
+}
+
+impl PagePresence {
+  fn holder_label(&self) -> String {
+    if self.display_name == self.client_id {
+      self.client_id.clone()
+    } else {
+      format!("{} ({})", self.display_name, self.client_id)
+    }
+  }
+}
+
+/// Advisory per-page locks and "who has which page open", keyed by project path and client id.
+/// Each client has at most one open page per project.
+#[derive(Clone)]
+pub(crate) struct PageLockHub {
+  projects: Arc<Mutex<HashMap<String, ProjectPages>>>,
+  /// Live `ClientLockGuard`s per client id. Tabs sharing a client id each hold one, and the
+  /// client's pages are released only when the last of them drops.
+  guards: Arc<Mutex<HashMap<String, usize>>>,
+  events: EventHub,
+}
+
+type ProjectPages = HashMap<String, PagePresence>;
+
+impl PageLockHub {
+  pub(crate) fn new(events: EventHub) -> Self {
+    Self {
+      projects: Arc::new(Mutex::new(HashMap::new())),
+      guards: Arc::new(Mutex::new(HashMap::new())),
+      events,
+    }
+  }
+
+  fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, ProjectPages>>, AppError> {
+    self.projects.lock().map_err(|_| AppError::lock("Page lock"))
+  }
+
+  fn publish(&self, project_path: &str, sessions: &HashMap<String, PagePresence>) {
+    self.events.publish(ProjectEvent::PagePresence {
+      project_path: project_path.to_string(),
+      presence: sorted_presence(sessions),
+    });
+  }
+
+  /// Records that a client has a page open, optionally taking its lock. Re-opening the same
+  /// page renews the session.
+  pub(crate) fn open_page(
+    &self,
+    project_dir: &Path,
+    client_id: &str,
+    display_name: &str,
+    page_id: &str,
+    lock: bool,
+  ) -> Result<Vec<PagePresence>, AppError> {
+    if client_id.trim().is_empty() {
+      return Err(AppError::invalid("Client id is required to open a page.").with_field("clientId"));
+    }
+    if page_id.trim().is_empty() {
+      return Err(AppError::invalid("Page id is required.").with_field("pageId"));
+    }
+    let project_path = project_path_string(project_dir)?;
+    let mut projects = self.lock()?;
+    let sessions = projects.entry(project_path.clone()).or_default();
+    prune_expired(sessions);
+
+    if lock {
+      if let Some(holder) = sessions
+        .values()
+        .find(|session| session.locked && session.page_id == page_id && session.client_id != client_id)
+      {
+        return Err(AppError::conflict(format!(
+          "Page {} is locked by {} since {}.",
+          page_id,
+          holder.holder_label(),
+          holder.since
+        )));
+      }
+    }
+
+    let display_name = if display_name.trim().is_empty() {
+      client_id.to_string()
+    } else {
+      display_name.trim().to_string()
+    };
+    let since = match sessions.get(client_id) {
+      Some(existing) if existing.page_id == page_id && existing.locked == lock => existing.since.clone(),
+      _ => now_iso(),
+    };
+    sessions.insert(
+      client_id.to_string(),
+      PagePresence {
+        client_id: client_id.to_string(),
+        display_name,
+        page_id: page_id.to_string(),
+        locked: lock,
+        since,
+        last_seen: Instant::now(),
+      },
+    );
+    self.publish(&project_path, sessions);
+    Ok(sorted_presence(sessions))
+  }
+
+  pub(crate) fn close_page(&self, project_dir: &Path, client_id: &str) -> Result<(), AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let mut projects = self.lock()?;
+    if let Some(sessions) = projects.get_mut(&project_path) {
+      if sessions.remove(client_id).is_some() {
+        self.publish(&project_path, sessions);
+      }
+      if sessions.is_empty() {
+        projects.remove(&project_path);
+      }
+    }
+    Ok(())
+  }
+
+  pub(crate) fn presence(&self, project_dir: &Path) -> Result<Vec<PagePresence>, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let mut projects = self.lock()?;
+    let Some(sessions) = projects.get_mut(&project_path) else {
+      return Ok(Vec::new());
+    };
+    prune_expired(sessions);
+    Ok(sorted_presence(sessions))
+  }
+
+  /// Drops every page a client holds, across projects. Called when its event stream closes.
+  pub(crate) fn release_client(&self, client_id: &str) {
+    let Ok(mut projects) = self.lock() else {
+      return;
+    };
+    for (project_path, sessions) in projects.iter_mut() {
+      if sessions.remove(client_id).is_some() {
+        self.publish(project_path, sessions);
+      }
+    }
+    projects.retain(|_, sessions| !sessions.is_empty());
+  }
+
+  /// Keeps a client's open page alive without changing it.
+  pub(crate) fn touch_client(&self, client_id: &str) {
+    let Ok(mut projects) = self.lock() else {
+      return;
+    };
+    for sessions in projects.values_mut() {
+      if let Some(session) = sessions.get_mut(client_id) {
+        session.last_seen = Instant::now();
+      }
+    }
+  }
+
+  /// Follows a renamed project directory; open pages and locks carry over.
+  pub(crate) fn move_project(&self, from: &Path, to: &Path) -> Result<(), AppError> {
+    let from_path = project_path_string(from)?;
+    let to_path = project_path_string(to)?;
+    let mut projects = self.lock()?;
+    if let Some(sessions) = projects.remove(&from_path) {
+      projects.insert(to_path, sessions);
+    }
+    Ok(())
+  }
+
+  /// Rejects a save touching pages another client has locked, naming every holder.
+  pub(crate) fn check_save(
+    &self,
+    project_dir: &Path,
+    client_id: &str,
+    changed_page_ids: &[String],
+  ) -> Result<(), AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let mut projects = self.lock()?;
+    let Some(sessions) = projects.get_mut(&project_path) else {
+      return Ok(());
+    };
+    prune_expired(sessions);
+    let mut conflicts: Vec<String> = sessions
+      .values()
+      .filter(|session| {
+        session.locked && session.client_id != client_id && changed_page_ids.contains(&session.page_id)
+      })
+      .map(|session| {
+        format!(
+          "page {} is locked by {} since {}",
+          session.page_id,
+          session.holder_label(),
+          session.since
+        )
+      })
+      .collect();
+    if conflicts.is_empty() {
+      return Ok(());
+    }
+    conflicts.sort();
+    Err(AppError::conflict(format!("Save rejected: {}.", conflicts.join("; "))))
+  }
+}
+
+fn prune_expired(sessions: &mut HashMap<String, PagePresence>) {
+  sessions.retain(|_, session| session.last_seen.elapsed() < PAGE_SESSION_TTL);
+}
+
+fn sorted_presence(sessions: &HashMap<String, PagePresence>) -> Vec<PagePresence> {
+  let mut presence: Vec<PagePresence> = sessions.values().cloned().collect();
+  presence.sort_by(|a, b| a.page_id.cmp(&b.page_id).then(a.client_id.cmp(&b.client_id)));
+  presence
+}
+
+/// Held by a client's event stream: keeps its pages alive while the stream runs and releases
+/// them when the client's last stream disconnects.
+pub(crate) struct ClientLockGuard {
+  locks: PageLockHub,
+  client_id: String,
+}
+
+impl ClientLockGuard {
+  pub(crate) fn new(locks: PageLockHub, client_id: String) -> Self {
+    if let Ok(mut guards) = locks.guards.lock() {
+      *guards.entry(client_id.clone()).or_default() += 1;
+    }
+    Self { locks, client_id }
+  }
+
+  pub(crate) fn touch(&self) {
+    self.locks.touch_client(&self.client_id);
+  }
+}
+
+impl Drop for ClientLockGuard {
+  fn drop(&mut self) {
+    let last = match self.locks.guards.lock() {
+      Ok(mut guards) => {
+        let count = guards.entry(self.client_id.clone()).or_default();
+        *count = count.saturating_sub(1);
+        let last = *count == 0;
+        if last {
+          guards.remove(&self.client_id);
+        }
+        last
+      }
+      Err(_) => true,
+    };
+    if last {
+      self.locks.release_client(&self.client_id);
+    }
+  }
+}
+
+/// Per-project mutexes held around every document write, together with whatever it read or
+/// checked first (an `If-Match`, a proposal's base revision), so writers on one project cannot
+/// interleave. Taken before any hub's own lock.
+#[derive(Clone, Default)]
+pub(crate) struct ProjectWriteLocks {
+  projects: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+}
+
+impl ProjectWriteLocks {
+  /// Runs `write` while holding the project's mutex.
+  pub(crate) fn run<T>(
+    &self,
+    project_dir: &Path,
+    write: impl FnOnce() -> Result<T, AppError>,
+  ) -> Result<T, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let project = self
+      .projects
+      .lock()
+      .map_err(|_| AppError::lock("Project write"))?
+      .entry(project_path)
+      .or_default()
+      .clone();
+    let _guard = project.lock().map_err(|_| AppError::lock("Project write"))?;
+    write()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn locked_by_other(locks: &PageLockHub, project: &Path) -> bool {
+    locks.check_save(project, "other", &["home".to_string()]).is_err()
+  }
+
+  #[test]
+  fn pages_stay_locked_until_the_last_stream_of_a_client_drops() {
+    let locks = PageLockHub::new(EventHub::default());
+    let project = Path::new("/workspace/site.manifold");
+    let first = ClientLockGuard::new(locks.clone(), "tab".to_string());
+    let second = ClientLockGuard::new(locks.clone(), "tab".to_string());
+    locks.open_page(project, "tab", "Tab", "home", true).unwrap();
+
+    drop(first);
+    assert!(locked_by_other(&locks, project));
+    drop(second);
+    assert!(!locked_by_other(&locks, project));
+  }
+
+  #[test]
+  fn touching_keeps_a_page_past_its_ttl() {
+    let locks = PageLockHub::new(EventHub::default());
+    let project = Path::new("/workspace/site.manifold");
+    let guard = ClientLockGuard::new(locks.clone(), "tab".to_string());
+    locks.open_page(project, "tab", "Tab", "home", true).unwrap();
+    {
+      let mut projects = locks.lock().unwrap();
+      let session = projects.get_mut("/workspace/site.manifold").unwrap().get_mut("tab").unwrap();
+      session.last_seen = Instant::now() - PAGE_SESSION_TTL - Duration::from_secs(1);
+    }
+    guard.touch();
+    // `check_save` prunes expired sessions before looking for holders.
+    assert!(locked_by_other(&locks, project));
+  }
+}
@@ -0,0 +1,314 @@
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::agent::{dispatch_agent_command, AgentError, AGENT_PROTOCOL_VERSION};
+use crate::error::AppError;
+use crate::policy::{AgentAccessMode, AgentOperation};
+use crate::{project_path_string, project_record_from_dir, LiveServices, PROJECT_META_FILE};
+
+const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcRequest {
+  #[serde(default)]
+  id: Option<serde_json::Value>,
+  method: String,
+  #[serde(default)]
+  params: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolCallParams {
+  name: String,
+  #[serde(default)]
+  arguments: serde_json::Map<String, serde_json::Value>,
+}
+
+struct ToolSpec {
+  name: &'static str,
+  description: &'static str,
+  handler: ToolHandler,
+  input_schema: fn() -> serde_json::Value,
+}
+
+fn no_arguments() -> serde_json::Value {
+  serde_json::json!({ "type": "object", "properties": {}, "additionalProperties": false })
+}
+
+fn page_arguments() -> serde_json::Value {
+  serde_json::json!({
+    "type": "object",
+    "properties": { "pageId": { "type": "string" } },
+    "required": ["pageId"],
+    "additionalProperties": false
+  })
+}
+
+fn add_block_arguments() -> serde_json::Value {
+  serde_json::json!({
+    "type": "object",
+    "properties": {
+      "pageId": { "type": "string" },
+      "blockType": { "type": "string", "enum": crate::validation::KNOWN_BLOCK_TYPES },
+      "props": { "type": "object" },
+      "index": { "type": "integer", "minimum": 0, "description": "Insert position; appends when omitted." }
+    },
+    "required": ["pageId", "blockType"],
+    "additionalProperties": false
+  })
+}
+
+fn update_block_arguments() -> serde_json::Value {
+  serde_json::json!({
+    "type": "object",
+    "properties": {
+      "pageId": { "type": "string" },
+      "blockId": { "type": "string" },
+      "props": { "type": "object", "description": "Merged into the block props; null removes a key." }
+    },
+    "required": ["pageId", "blockId", "props"],
+    "additionalProperties": false
+  })
+}
+
+fn update_seo_arguments() -> serde_json::Value {
+  serde_json::json!({
+    "type": "object",
+    "properties": {
+      "pageId": { "type": "string" },
+      "seoTitle": { "type": "string" },
+      "seoDescription": { "type": "string" }
+    },
+    "required": ["pageId"],
+    "additionalProperties": false
+  })
+}
+
+enum ToolHandler {
+  /// Agent command the tool maps to; it runs under the server's policy session.
+  Agent(&'static str),
+  /// Answered here without an agent command.
+  Local(fn(&Path) -> Result<serde_json::Value, AppError>),
+}
+
+/// The confined project as a one-entry listing.
+fn list_projects(project_dir: &Path) -> Result<serde_json::Value, AppError> {
+  let record = project_record_from_dir(project_dir)?;
+  Ok(serde_json::json!({ "projects": [record] }))
+}
+
+const TOOLS: &[ToolSpec] = &[
+  ToolSpec {
+    name: "list_projects",
+    description: "Describe the project this server is confined to.",
+    handler: ToolHandler::Local(list_projects),
+    input_schema: no_arguments,
+  },
+  ToolSpec {
+    name: "list_pages",
+    description: "List pages with their routes and block ids.",
+    handler: ToolHandler::Agent("pages.list"),
+    input_schema: no_arguments,
+  },
+  ToolSpec {
+    name: "get_page",
+    description: "Read one page including SEO, blocks, props and style overrides.",
+    handler: ToolHandler::Agent("page.get"),
+    input_schema: page_arguments,
+  },
+  ToolSpec {
+    name: "add_block",
+    description: "Add a catalog block to a page.",
+    handler: ToolHandler::Agent("block.add"),
+    input_schema: add_block_arguments,
+  },
+  ToolSpec {
+    name: "update_block",
+    description: "Merge new prop values into a block.",
+    handler: ToolHandler::Agent("content.update"),
+    input_schema: update_block_arguments,
+  },
+  ToolSpec {
+    name: "update_page_seo",
+    description: "Change a page's SEO title and/or description.",
+    handler: ToolHandler::Agent("page.update"),
+    input_schema: update_seo_arguments,
+  },
+  ToolSpec {
+    name: "validate_project",
+    description: "Run structural validation; errors block export.",
+    handler: ToolHandler::Agent("validate"),
+    input_schema: no_arguments,
+  },
+  ToolSpec {
+    name: "lint_project",
+    description: "Report SEO, alt text and link hygiene warnings.",
+    handler: ToolHandler::Agent("lint"),
+    input_schema: no_arguments,
+  },
+];
+
+/// Model Context Protocol server over stdio (newline-delimited JSON-RPC). Every tool is bound
+/// to the single project given at launch; arguments never carry paths. All calls run in one
+/// agent policy session with the given mode and operations.
+pub(crate) fn run_stdio(
+  project_dir: &Path,
+  mode: AgentAccessMode,
+  operations: Vec<AgentOperation>,
+) -> Result<(), AppError> {
+  let project_dir = confined_project_dir(project_dir)?;
+  let services = LiveServices::new();
+  let session = services.policy.start_session(&project_dir, mode, operations)?;
+  let session_id = session.session_id().to_string();
+  let stdin = std::io::stdin();
+  let mut stdout = std::io::stdout();
+  for line in stdin.lock().lines() {
+    let line = line.map_err(|err| AppError::internal(format!("Failed reading stdin: {}", err)))?;
+    if line.trim().is_empty() {
+      continue;
+    }
+    if let Some(response) = handle_message(&line, &project_dir, &session_id, &services) {
+      writeln!(stdout, "{}", response).map_err(stdout_error)?;
+      stdout.flush().map_err(stdout_error)?;
+    }
+  }
+  Ok(())
+}
+
+fn stdout_error(err: std::io::Error) -> AppError {
+  AppError::internal(format!("Failed writing stdout: {}", err))
+}
+
+fn confined_project_dir(project_dir: &Path) -> Result<PathBuf, AppError> {
+  let canonical = project_dir
+    .canonicalize()
+    .map_err(|err| AppError::fs("Failed resolving project", project_dir, err))?;
+  if !canonical.join(PROJECT_META_FILE).is_file() {
+    return Err(
+      AppError::not_found(format!("{} is not a Manifold project", canonical.display())).with_path(&canonical),
+    );
+  }
+  Ok(canonical)
+}
+
+fn handle_message(
+  line: &str,
+  project_dir: &Path,
+  session_id: &str,
+  services: &LiveServices,
+) -> Option<serde_json::Value> {
+  let request = match serde_json::from_str::<serde_json::Value>(line) {
+    Ok(value) => value,
+    Err(err) => return Some(error_response(serde_json::Value::Null, PARSE_ERROR, err.to_string())),
+  };
+  let request = match serde_json::from_value::<JsonRpcRequest>(request) {
+    Ok(request) => request,
+    Err(err) => return Some(error_response(serde_json::Value::Null, INVALID_REQUEST, err.to_string())),
+  };
+  // Notifications carry no id and never get a response.
+  let id = request.id?;
+  let result = match request.method.as_str() {
+    "initialize" => Ok(initialize_result(&request.params)),
+    "ping" => Ok(serde_json::json!({})),
+    "tools/list" => Ok(tools_list_result()),
+    "tools/call" => call_tool(request.params, project_dir, session_id, services),
+    other => Err((METHOD_NOT_FOUND, format!("Method {} not found", other))),
+  };
+  Some(match result {
+    Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+    Err((code, message)) => error_response(id, code, message),
+  })
+}
+
+fn error_response(id: serde_json::Value, code: i64, message: String) -> serde_json::Value {
+  serde_json::json!({
+    "jsonrpc": "2.0",
+    "id": id,
+    "error": { "code": code, "message": message }
+  })
+}
+
+fn initialize_result(params: &serde_json::Value) -> serde_json::Value {
+  let requested = params
+    .get("protocolVersion")
+    .and_then(|value| value.as_str())
+    .unwrap_or(MCP_PROTOCOL_VERSION);
+  let protocol_version = if SUPPORTED_PROTOCOL_VERSIONS.contains(&requested) {
+    requested
+  } else {
+    MCP_PROTOCOL_VERSION
+  };
+  serde_json::json!({
+    "protocolVersion": protocol_version,
+    "capabilities": { "tools": { "listChanged": false } },
+    "serverInfo": { "name": "manifold", "version": env!("CARGO_PKG_VERSION") }
+  })
+}
+
+fn tools_list_result() -> serde_json::Value {
+  let tools: Vec<serde_json::Value> = TOOLS
+    .iter()
+    .map(|tool| {
+      serde_json::json!({
+        "name": tool.name,
+        "description": tool.description,
+        "inputSchema": (tool.input_schema)(),
+      })
+    })
+    .collect();
+  serde_json::json!({ "tools": tools })
+}
+
+fn call_tool(
+  params: serde_json::Value,
+  project_dir: &Path,
+  session_id: &str,
+  services: &LiveServices,
+) -> Result<serde_json::Value, (i64, String)> {
+  let params =
+    serde_json::from_value::<ToolCallParams>(params).map_err(|err| (INVALID_PARAMS, err.to_string()))?;
+  let tool = TOOLS
+    .iter()
+    .find(|tool| tool.name == params.name)
+    .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool {}", params.name)))?;
+
+  let project_path = project_path_string(project_dir).map_err(|err| (INVALID_PARAMS, err.to_string()))?;
+  let outcome = match tool.handler {
+    ToolHandler::Local(handler) => {
+      handler(project_dir).map_err(|err| serde_json::to_value(AgentError::from(err)).unwrap_or_default())
+    }
+    ToolHandler::Agent(method) => dispatch_agent_command(
+      serde_json::json!({
+        "version": AGENT_PROTOCOL_VERSION,
+        "projectPath": project_path,
+        "method": method,
+        "params": params.arguments,
+        "sessionId": session_id,
+      }),
+      services,
+    )
+    .into_result()
+    .map_err(|err| serde_json::to_value(err).unwrap_or_default()),
+  };
+
+  // Tool failures are reported in-band so the model can read and react to them.
+  Ok(match outcome {
+    Ok(result) => serde_json::json!({
+      "content": [{ "type": "text", "text": serde_json::to_string_pretty(&result).unwrap_or_default() }],
+      "structuredContent": result,
+      "isError": false,
+    }),
+    Err(error) => serde_json::json!({
+      "content": [{ "type": "text", "text": serde_json::to_string_pretty(&error).unwrap_or_default() }],
+      "isError": true,
+    }),
+  })
+}
@@ -0,0 +1,465 @@
+//! The remote API surface in one table: every `/api/v1` route with its body and response types.
+//! The OpenAPI document and the TypeScript client are both generated from it, so a route added
+//! to `remote_router` without an entry here shows up as a missing client method.
+
+use std::any::TypeId;
+use std::collections::BTreeMap;
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+use ts_rs::TS;
+
+use crate::activity::ActivityEntry;
+use crate::agent::AgentResponse;
+use crate::archive::ARCHIVE_CONTENT_TYPE;
+use crate::assets::{StoredAsset, FILE_FIELD, MAX_ASSET_BYTES};
+use crate::collab::{CollabChanges, CollabSnapshot, Participant, SubmitOutcome};
+use crate::error::AppError;
+use crate::events::ProjectEvent;
+use crate::locks::PagePresence;
+use crate::policy::{AgentSessionPolicy, PolicyDenial};
+use crate::project_index::ProjectPage;
+use crate::proposals::{PatchProposal, ProposalOutcome};
+use crate::rest::{ProjectResource, SitePatch};
+use crate::schema::ProjectFilesReport;
+use crate::shelf::{DeletionRequest, ShelvedProject};
+use crate::templates::ProjectTemplate;
+use crate::{
+  AgentSessionInput, AgentSessionStartInput, AgentSessionUpdateInput, BuilderProjectDoc,
+  CollabChangesInput, CollabClientInput, CollabJoinInput, CollabPresenceInput, CollabSubmitInput,
+  CreateProjectInput, DeleteProjectInput, DuplicateProjectInput, ListShelvedInput, OpenPageInput,
+  PageDoc, ProjectActivityInput, ProjectPathInput, ProjectRecord, ProposalResolveInput,
+  ProposalRevertInput, ProposalSubmitInput, QueryProjectsInput, RemoteContextDoc, RemoteOk,
+  RenameProjectInput, SaveBuilderProjectInput, SaveProjectOutcome, SiteDoc,
+  UpdateProjectDetailsInput, UpdateSiteUrlInput, WorkspaceRootInput,
+};
+
+/// Prefix of the current API version. The unversioned `/api` mount is kept for older clients
+/// and serves the same routes.
+pub(crate) const API_PREFIX: &str = "/api/v1";
+pub(crate) const API_VERSIONS: &[&str] = &["v1"];
+
+/// Optional server features, reported by `/health` so clients can check before calling.
+pub(crate) const REMOTE_FEATURES: &[&str] = &[
+  "events",
+  "collab",
+  "pageLocks",
+  "proposals",
+  "agentSessions",
+  "projectSchemas",
+  "openapi",
+  "assetUpload",
+  "projectArchive",
+];
+
+/// Types that cross the remote API: serialized by serde, described by both generators.
+pub(crate) trait ApiType: TS + JsonSchema + 'static {}
+
+impl<T: TS + JsonSchema + 'static> ApiType for T {}
+
+/// Receives the route table. Paths are relative to [`API_PREFIX`].
+pub(crate) trait RouteSink {
+  /// `POST` with a JSON body of `I`, answering `R`.
+  fn post<I: ApiType, R: ApiType>(&mut self, path: &str, summary: &str);
+  /// `POST` that ignores its body.
+  fn post_without_input<R: ApiType>(&mut self, path: &str, summary: &str);
+  /// `GET` server-sent event stream of `E`.
+  fn stream<E: ApiType>(&mut self, path: &str, summary: &str);
+  /// ETag-versioned resource route; `I` is `()` for methods without a body. `name` is the
+  /// client method, since several methods share a path.
+  fn resource<I: ApiType, R: ApiType>(&mut self, method: &str, path: &str, name: &str, summary: &str);
+  /// `POST` of a `multipart/form-data` body whose `file` field is streamed to disk.
+  fn upload<R: ApiType>(&mut self, path: &str, name: &str, summary: &str);
+  /// `GET` of a binary file with the given content type.
+  fn download(&mut self, path: &str, name: &str, content_type: &str, summary: &str);
+}
+
+pub(crate) fn describe_routes(sink: &mut impl RouteSink) {
+  sink.post_without_input::<RemoteContextDoc>(
+    "/remote-context",
+    "Workspace the server was started with",
+  );
+  sink.post::<WorkspaceRootInput, Vec<ProjectRecord>>(
+    "/list-projects",
+    "List projects in a workspace",
+  );
+  sink.post::<QueryProjectsInput, ProjectPage>(
+    "/query-projects",
+    "Search, filter, sort and page projects in a workspace",
+  );
+  sink.post::<CreateProjectInput, ProjectRecord>("/create-project", "Create a project");
+  sink.post::<WorkspaceRootInput, Vec<ProjectTemplate>>(
+    "/list-project-templates",
+    "List templates for new projects",
+  );
+  sink.post::<DuplicateProjectInput, ProjectRecord>(
+    "/duplicate-project",
+    "Copy a project as a new one",
+  );
+  sink.post::<RenameProjectInput, ProjectRecord>(
+    "/rename-project",
+    "Rename a project and move its directory to the new slug",
+  );
+  sink.post::<ProjectPathInput, ShelvedProject>("/archive-project", "Move a project to the archive");
+  sink.post::<ProjectPathInput, ShelvedProject>("/trash-project", "Move a project to the trash");
+  sink.post::<ProjectPathInput, ProjectRecord>(
+    "/restore-project",
+    "Move an archived or trashed project back into the workspace",
+  );
+  sink.post::<ListShelvedInput, Vec<ShelvedProject>>(
+    "/list-shelved-projects",
+    "List archived and trashed projects",
+  );
+  sink.post::<ProjectPathInput, DeletionRequest>(
+    "/request-project-deletion",
+    "Get the confirmation token for deleting a trashed project",
+  );
+  sink.post::<DeleteProjectInput, RemoteOk>(
+    "/delete-project",
+    "Permanently delete a trashed project",
+  );
+  sink.post::<UpdateSiteUrlInput, ProjectRecord>(
+    "/update-project-site-url",
+    "Change a project's site URL",
+  );
+  sink.post::<UpdateProjectDetailsInput, ProjectRecord>(
+    "/update-project-details",
+    "Change a project's tags, client, pin or color label",
+  );
+  sink.post::<ProjectActivityInput, Vec<ActivityEntry>>(
+    "/project-activity",
+    "Recent changes to a project, newest first",
+  );
+  sink.post::<ProjectPathInput, BuilderProjectDoc>(
+    "/load-builder-project",
+    "Load the editor document",
+  );
+  sink.post::<SaveBuilderProjectInput, SaveProjectOutcome>(
+    "/save-builder-project",
+    "Save the editor document",
+  );
+  sink.post_without_input::<BTreeMap<String, Value>>(
+    "/project-schemas",
+    "JSON Schemas for project files",
+  );
+  sink.post::<ProjectPathInput, ProjectFilesReport>(
+    "/validate-project-files",
+    "Check project files against their schemas",
+  );
+  sink.stream::<ProjectEvent>("/events", "Project change events");
+  sink.post::<Value, AgentResponse>("/agent", "Run an agent protocol request");
+  sink.post::<AgentSessionStartInput, AgentSessionPolicy>(
+    "/agent/sessions/start",
+    "Start a read-only agent session; write sessions are granted on the desktop",
+  );
+  sink.post::<AgentSessionUpdateInput, AgentSessionPolicy>(
+    "/agent/sessions/update",
+    "Narrow an agent session's grants",
+  );
+  sink.post::<AgentSessionInput, RemoteOk>("/agent/sessions/end", "End an agent session");
+  sink.post::<ProjectPathInput, Vec<PolicyDenial>>("/agent/denials", "Recent agent policy denials");
+  sink.post::<OpenPageInput, Vec<PagePresence>>(
+    "/pages/open",
+    "Open a page, optionally locking it",
+  );
+  sink.post::<CollabClientInput, RemoteOk>("/pages/close", "Close the client's open page");
+  sink.post::<ProjectPathInput, Vec<PagePresence>>("/pages/presence", "Who has which page open");
+  sink.post::<CollabJoinInput, CollabSnapshot>("/collab/join", "Join a co-editing session");
+  sink.post::<CollabSubmitInput, SubmitOutcome>("/collab/submit", "Submit document operations");
+  sink.post::<CollabChangesInput, CollabChanges>("/collab/changes", "Operations since a version");
+  sink.post::<CollabPresenceInput, Vec<Participant>>(
+    "/collab/presence",
+    "Update the client's cursor",
+  );
+  sink.post::<CollabClientInput, RemoteOk>("/collab/leave", "Leave a co-editing session");
+  sink.post::<ProposalSubmitInput, PatchProposal>("/proposals/submit", "Propose a JSON Patch");
+  sink.post::<ProjectPathInput, Vec<PatchProposal>>("/proposals/list", "List proposals");
+  sink.post::<ProposalResolveInput, ProposalOutcome>("/proposals/approve", "Apply a proposal");
+  sink.post::<ProposalResolveInput, PatchProposal>("/proposals/reject", "Reject a proposal");
+  sink.post::<ProposalRevertInput, ProposalOutcome>(
+    "/proposals/revert",
+    "Revert the last applied change",
+  );
+  sink.resource::<(), Vec<ProjectRecord>>("get", "/projects", "getProjects", "List workspace projects");
+  sink.resource::<(), ProjectResource>("get", "/projects/{slug}", "getProject", "Project settings and pages");
+  sink.resource::<(), SiteDoc>("get", "/projects/{slug}/site", "getSite", "Site settings");
+  sink.resource::<SitePatch, SiteDoc>("patch", "/projects/{slug}/site", "patchSite", "Change site settings");
+  sink.resource::<(), PageDoc>("get", "/projects/{slug}/pages/{pageId}", "getPage", "One page");
+  sink.resource::<PageDoc, PageDoc>(
+    "put",
+    "/projects/{slug}/pages/{pageId}",
+    "putPage",
+    "Replace or add a page; 201 when added",
+  );
+  sink.download(
+    "/projects/{slug}/archive",
+    "downloadProjectArchive",
+    ARCHIVE_CONTENT_TYPE,
+    "The project as a .manifold.zip archive",
+  );
+  sink.upload::<StoredAsset>(
+    "/projects/{slug}/assets",
+    "uploadAsset",
+    "Upload an image into assets/; 201 when stored, 200 when already present",
+  );
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HealthDoc {
+  status: String,
+  /// Server build, e.g. `0.1.0`.
+  version: String,
+  /// Supported API versions, mounted at `/api/<version>`.
+  api_versions: Vec<String>,
+  features: Vec<String>,
+}
+
+pub(crate) fn health() -> HealthDoc {
+  HealthDoc {
+    status: "ok".to_string(),
+    version: env!("CARGO_PKG_VERSION").to_string(),
+    api_versions: API_VERSIONS.iter().map(|version| version.to_string()).collect(),
+    features: REMOTE_FEATURES.iter().map(|feature| feature.to_string()).collect(),
+  }
+}
+
+struct OpenApiBuilder {
+  generator: SchemaGenerator,
+  paths: serde_json::Map<String, Value>,
+}
+
+impl OpenApiBuilder {
+  fn schema<T: JsonSchema + ?Sized>(&mut self) -> Value {
+    let mut schema = self.generator.subschema_for::<T>();
+    for visitor in self.generator.visitors_mut() {
+      visitor.visit_schema(&mut schema);
+    }
+    schema_value(schema)
+  }
+
+  fn json_content(schema: Value) -> Value {
+    json!({ "application/json": { "schema": schema } })
+  }
+
+  fn operation(
+    &mut self,
+    path: &str,
+    method: &str,
+    summary: &str,
+    request: Option<Value>,
+    response: Value,
+  ) {
+    let error = Self::json_content(self.schema::<AppError>());
+    let mut operation = json!({
+      "summary": summary,
+      "operationId": operation_id(path),
+      "responses": {
+        "200": { "description": "OK", "content": response },
+        "default": { "description": "Error", "content": error },
+      },
+    });
+    if let Some(request) = request {
+      operation["requestBody"] = json!({ "required": true, "content": Self::json_content(request) });
+    }
+    let item = self.paths.entry(path.to_string()).or_insert_with(|| json!({}));
+    item[method] = operation;
+  }
+}
+
+impl RouteSink for OpenApiBuilder {
+  fn post<I: ApiType, R: ApiType>(&mut self, path: &str, summary: &str) {
+    let request = self.schema::<I>();
+    let response = Self::json_content(self.schema::<R>());
+    self.operation(path, "post", summary, Some(request), response);
+  }
+
+  fn post_without_input<R: ApiType>(&mut self, path: &str, summary: &str) {
+    let response = Self::json_content(self.schema::<R>());
+    self.operation(path, "post", summary, None, response);
+  }
+
+  fn stream<E: ApiType>(&mut self, path: &str, summary: &str) {
+    let response = json!({ "text/event-stream": { "schema": self.schema::<E>() } });
+    self.operation(path, "get", summary, None, response);
+    self.paths[path]["get"]["parameters"] = json!([
+      {
+        "name": "token",
+        "in": "query",
+        "description": "Alternative to the token header for EventSource clients.",
+        "schema": { "type": "string" },
+      },
+      {
+        "name": "clientId",
+        "in": "query",
+        "description": "Releases this client's page locks when the stream closes.",
+        "schema": { "type": "string" },
+      },
+    ]);
+  }
+
+  fn resource<I: ApiType, R: ApiType>(&mut self, method: &str, path: &str, name: &str, summary: &str) {
+    let request = (TypeId::of::<I>() != TypeId::of::<()>()).then(|| self.schema::<I>());
+    let response = Self::json_content(self.schema::<R>());
+    self.operation(path, method, summary, request, response);
+    let etag = json!({ "ETag": { "schema": { "type": "string" } } });
+    let operation = &mut self.paths[path][method];
+    operation["operationId"] = json!(name);
+    operation["parameters"] = resource_parameters(path, method);
+    operation["responses"]["200"]["headers"] = etag.clone();
+    if method == "get" {
+      operation["responses"]["304"] = json!({ "description": "Not modified", "headers": etag });
+    } else {
+      operation["responses"]["412"] = json!({ "description": "ETag mismatch" });
+    }
+    if method == "put" {
+      let created = operation["responses"]["200"].clone();
+      operation["responses"]["201"] = created;
+    }
+  }
+
+  fn upload<R: ApiType>(&mut self, path: &str, name: &str, summary: &str) {
+    let response = Self::json_content(self.schema::<R>());
+    self.operation(path, "post", summary, None, response);
+    let operation = &mut self.paths[path]["post"];
+    operation["operationId"] = json!(name);
+    operation["parameters"] = Value::Array(path_params(path).map(path_parameter).collect());
+    operation["requestBody"] = json!({
+      "required": true,
+      "content": {
+        "multipart/form-data": {
+          "schema": {
+            "type": "object",
+            "required": [FILE_FIELD],
+            "properties": {
+              FILE_FIELD: {
+                "type": "string",
+                "format": "binary",
+                "description": format!(
+                  "PNG, JPEG, GIF, WebP, AVIF, ICO or script-free SVG, at most {} bytes.",
+                  MAX_ASSET_BYTES
+                ),
+              },
+            },
+          },
+        },
+      },
+    });
+    let created = operation["responses"]["200"].clone();
+    operation["responses"]["201"] = created;
+    operation["responses"]["413"] = json!({ "description": "File exceeds the size limit" });
+  }
+
+  fn download(&mut self, path: &str, name: &str, content_type: &str, summary: &str) {
+    let response = json!({ content_type: { "schema": { "type": "string", "format": "binary" } } });
+    self.operation(path, "get", summary, None, response);
+    let operation = &mut self.paths[path]["get"];
+    operation["operationId"] = json!(name);
+    operation["parameters"] = Value::Array(path_params(path).map(path_parameter).collect());
+  }
+}
+
+fn path_parameter(name: &str) -> Value {
+  json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } })
+}
+
+fn resource_parameters(path: &str, method: &str) -> Value {
+  let mut parameters = path_params(path).map(path_parameter).collect::<Vec<_>>();
+  let (header, description) = if method == "get" {
+    ("If-None-Match", "ETag from an earlier read; answers 304 when unchanged.")
+  } else {
+    ("If-Match", "ETag from the last read; answers 412 when the resource changed since.")
+  };
+  parameters.push(json!({
+    "name": header,
+    "in": "header",
+    "description": description,
+    "schema": { "type": "string" },
+  }));
+  if method != "get" {
+    parameters.push(json!({
+      "name": "x-manifold-client-id",
+      "in": "header",
+      "description": "Caller's client id, so its own page locks do not block the write.",
+      "schema": { "type": "string" },
+    }));
+  }
+  Value::Array(parameters)
+}
+
+/// Names of `{param}` segments in a route path.
+pub(crate) fn path_params(path: &str) -> impl Iterator<Item = &str> {
+  path
+    .split('/')
+    .filter_map(|segment| segment.strip_prefix('{').and_then(|segment| segment.strip_suffix('}')))
+}
+
+/// OpenAPI 3.0 description of the `/api/v1` routes and `/health`.
+pub(crate) fn openapi_document() -> Value {
+  let mut builder = OpenApiBuilder {
+    generator: SchemaSettings::openapi3().into_generator(),
+    paths: serde_json::Map::new(),
+  };
+  describe_routes(&mut builder);
+  let health = OpenApiBuilder::json_content(builder.schema::<HealthDoc>());
+  builder.operation("/health", "get", "Server version and capabilities", None, health);
+  // `/health` lives outside the versioned prefix.
+  builder.paths["/health"]["servers"] = json!([{ "url": "/" }]);
+  builder.operation("/openapi.json", "get", "This document", None, json!({ "application/json": {} }));
+
+  let mut definitions = builder.generator.take_definitions();
+  for visitor in builder.generator.visitors_mut() {
+    for schema in definitions.values_mut() {
+      visitor.visit_schema(schema);
+    }
+  }
+  let schemas = definitions
+    .into_iter()
+    .map(|(name, schema)| (name, schema_value(schema)))
+    .collect::<serde_json::Map<_, _>>();
+
+  json!({
+    "openapi": "3.0.3",
+    "info": {
+      "title": "Manifold remote API",
+      "version": env!("CARGO_PKG_VERSION"),
+    },
+    "servers": [{ "url": API_PREFIX }],
+    "security": [{ "token": [] }],
+    "paths": builder.paths,
+    "components": {
+      "schemas": schemas,
+      "securitySchemes": {
+        "token": { "type": "apiKey", "in": "header", "name": "x-manifold-token" },
+      },
+    },
+  })
+}
+
+/// OpenAPI 3.0 has no boolean schemas; `true` (any value) becomes `{}`.
+fn schema_value(schema: Schema) -> Value {
+  match schema {
+    Schema::Bool(true) => json!({}),
+    other => serde_json::to_value(other).unwrap_or_else(|_| json!({})),
+  }
+}
+
+/// `/agent/sessions/start` -> `agentSessionsStart`; also camel-cases command names.
+pub(crate) fn operation_id(path: &str) -> String {
+  let mut out = String::with_capacity(path.len());
+  let mut upper = false;
+  for c in path.chars() {
+    if matches!(c, '/' | '-' | '.' | '_') {
+      upper = !out.is_empty();
+    } else if upper {
+      out.push(c.to_ascii_uppercase());
+      upper = false;
+    } else {
+      out.push(c);
+    }
+  }
+  out
+}
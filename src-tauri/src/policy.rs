@@ -0,0 +1,539 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Component, Path};
+use std::sync::{Arc, Mutex};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::error::AppError;
+use crate::{now_iso, project_path_string};
+
+/// Files and trees an agent may write, relative to the project root. `sitemap.json` is
+/// rewritten together with `site.json` by every document save. `exports/` stays out: it holds
+/// archives and agent transcripts that an agent must not be able to rewrite.
+const WRITABLE_FILES: &[&str] = &["project.json", "site.json", "sitemap.json", "theme.json"];
+const WRITABLE_DIRS: &[&str] = &["pages", "content", "assets", "blocks"];
+
+/// Path components that mark credential or keychain material anywhere in a path.
+const CREDENTIAL_NAMES: &[&str] = &[
+  ".ssh",
+  ".gnupg",
+  ".aws",
+  ".azure",
+  ".kube",
+  ".docker",
+  ".env",
+  ".netrc",
+  ".npmrc",
+  ".pypirc",
+  ".git-credentials",
+  "credentials",
+  "credentials.json",
+  "keychains",
+  "id_rsa",
+  "id_ed25519",
+];
+const CREDENTIAL_EXTENSIONS: &[&str] = &["pem", "key", "p12", "pfx", "keychain", "keychain-db"];
+
+const SYSTEM_ROOTS: &[&str] = &[
+  "/bin", "/boot", "/dev", "/etc", "/lib", "/proc", "/sbin", "/sys", "/usr", "/System", "/Library",
+  "c:\\windows", "c:\\program files",
+];
+
+const DENIAL_LOG_LIMIT: usize = 200;
+
+/// Sessions without explicit write mode can read everything in the project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AgentAccessMode {
+  ReadOnly,
+  Write,
+}
+
+/// Operation families the user switches on per session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS, clap::ValueEnum)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AgentOperation {
+  /// Manifests, pages, content and theme.
+  EditContent,
+  EditAssets,
+  /// Validate, lint and export tooling.
+  RunTools,
+}
+
+/// What a method needs before it may run.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MethodRequirement {
+  pub(crate) operation: Option<AgentOperation>,
+  pub(crate) writes: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentSessionPolicy {
+  session_id: String,
+  project_path: String,
+  mode: AgentAccessMode,
+  operations: Vec<AgentOperation>,
+  created_at: String,
+  updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PolicyDenial {
+  pub(crate) code: &'static str,
+  pub(crate) message: String,
+  project_path: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  session_id: Option<String>,
+  method: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  path: Option<String>,
+  at: String,
+}
+
+impl AgentSessionPolicy {
+  pub(crate) fn session_id(&self) -> &str {
+    &self.session_id
+  }
+}
+
+/// A checked request: the session it ran under, used to attribute later path denials.
+#[derive(Debug, Clone)]
+pub(crate) struct PolicyGrant {
+  project_path: String,
+  session_id: Option<String>,
+  method: String,
+}
+
+/// Gatekeeper for agent-initiated operations. Requests without a session run read-only with
+/// tooling enabled; denials are logged and kept for review.
+#[derive(Clone, Default)]
+pub(crate) struct AgentPolicyHub {
+  sessions: Arc<Mutex<HashMap<String, AgentSessionPolicy>>>,
+  denials: Arc<Mutex<VecDeque<PolicyDenial>>>,
+  next_session: Arc<Mutex<u64>>,
+}
+
+impl AgentPolicyHub {
+  fn lock_sessions(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, AgentSessionPolicy>>, AppError> {
+    self.sessions.lock().map_err(|_| AppError::lock("Agent policy"))
+  }
+
+  pub(crate) fn start_session(
+    &self,
+    project_dir: &Path,
+    mode: AgentAccessMode,
+    operations: Vec<AgentOperation>,
+  ) -> Result<AgentSessionPolicy, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    check_project_root(project_dir)
+      .map_err(|message| AppError::permission_denied(message).with_path(project_dir))?;
+    let session_number = {
+      let mut next = self
+        .next_session
+        .lock()
+        .map_err(|_| AppError::lock("Agent policy"))?;
+      *next += 1;
+      *next
+    };
+    let now = now_iso();
+    let session = AgentSessionPolicy {
+      session_id: format!("agent-session-{}", session_number),
+      project_path,
+      mode,
+      operations: dedup_operations(operations),
+      created_at: now.clone(),
+      updated_at: now,
+    };
+    self
+      .lock_sessions()?
+      .insert(session.session_id.clone(), session.clone());
+    log::info!(
+      "agent session {} started for {} in {:?} mode",
+      session.session_id,
+      session.project_path,
+      session.mode
+    );
+    Ok(session)
+  }
+
+  pub(crate) fn update_session(
+    &self,
+    session_id: &str,
+    mode: Option<AgentAccessMode>,
+    operations: Option<Vec<AgentOperation>>,
+  ) -> Result<AgentSessionPolicy, AppError> {
+    let mut sessions = self.lock_sessions()?;
+    let session = sessions
+      .get_mut(session_id)
+      .ok_or_else(|| missing_session(session_id))?;
+    if let Some(mode) = mode {
+      session.mode = mode;
+    }
+    if let Some(operations) = operations {
+      session.operations = dedup_operations(operations);
+    }
+    session.updated_at = now_iso();
+    Ok(session.clone())
+  }
+
+  /// Refuses changes a remote client may not make on its own. Remote routes authenticate with
+  /// the shared token only, so they can start read-only sessions and narrow existing ones, while
+  /// granting write access or adding operations to a write session takes the desktop app.
+  pub(crate) fn check_remote_change(
+    &self,
+    session_id: Option<&str>,
+    mode: Option<AgentAccessMode>,
+    operations: Option<&[AgentOperation]>,
+  ) -> Result<(), AppError> {
+    if mode == Some(AgentAccessMode::Write) {
+      return Err(
+        AppError::permission_denied("Write sessions can only be granted from the desktop app.")
+          .with_field("mode"),
+      );
+    }
+    let Some(session_id) = session_id else {
+      return Ok(());
+    };
+    let session = self.session(session_id)?;
+    let widens = operations.is_some_and(|operations| {
+      operations
+        .iter()
+        .any(|operation| !session.operations.contains(operation))
+    });
+    if session.mode == AgentAccessMode::Write && widens {
+      return Err(
+        AppError::permission_denied(
+          "Operations can only be added to a write session from the desktop app.",
+        )
+        .with_field("operations"),
+      );
+    }
+    Ok(())
+  }
+
+  pub(crate) fn end_session(&self, session_id: &str) -> Result<(), AppError> {
+    self.lock_sessions()?.remove(session_id);
+    Ok(())
+  }
+
+  pub(crate) fn session(&self, session_id: &str) -> Result<AgentSessionPolicy, AppError> {
+    self
+      .lock_sessions()?
+      .get(session_id)
+      .cloned()
+      .ok_or_else(|| missing_session(session_id))
+  }
+
+  /// Re-pins sessions, and re-files denials, from a renamed project directory.
+  pub(crate) fn move_project(&self, from: &Path, to: &Path) -> Result<(), AppError> {
+    let from_path = project_path_string(from)?;
+    let to_path = project_path_string(to)?;
+    for session in self.lock_sessions()?.values_mut() {
+      if session.project_path == from_path {
+        session.project_path = to_path.clone();
+      }
+    }
+    let mut denials = self
+      .denials
+      .lock()
+      .map_err(|_| AppError::lock("Agent policy"))?;
+    for denial in denials.iter_mut() {
+      if denial.project_path == from_path {
+        denial.project_path = to_path.clone();
+      }
+    }
+    Ok(())
+  }
+
+  /// Most recent denials first, optionally limited to one project.
+  pub(crate) fn denials(&self, project_dir: Option<&Path>) -> Result<Vec<PolicyDenial>, AppError> {
+    let project_path = project_dir.map(project_path_string).transpose()?;
+    let denials = self
+      .denials
+      .lock()
+      .map_err(|_| AppError::lock("Agent policy"))?;
+    Ok(
+      denials
+        .iter()
+        .rev()
+        .filter(|denial| match &project_path {
+          Some(path) => &denial.project_path == path,
+          None => true,
+        })
+        .cloned()
+        .collect(),
+    )
+  }
+
+  fn record(&self, denial: PolicyDenial) -> PolicyDenial {
+    log::warn!(
+      "agent policy denied {} on {} ({}): {}",
+      denial.method,
+      denial.project_path,
+      denial.code,
+      denial.message
+    );
+    if let Ok(mut denials) = self.denials.lock() {
+      denials.push_back(denial.clone());
+      while denials.len() > DENIAL_LOG_LIMIT {
+        denials.pop_front();
+      }
+    }
+    denial
+  }
+
+  /// Checks a method call against the session's mode and toggles.
+  pub(crate) fn authorize(
+    &self,
+    session_id: Option<&str>,
+    project_dir: &Path,
+    method: &str,
+    requirement: MethodRequirement,
+  ) -> Result<PolicyGrant, Box<PolicyDenial>> {
+    let grant = PolicyGrant {
+      project_path: project_dir.to_string_lossy().to_string(),
+      session_id: session_id.map(str::to_string),
+      method: method.to_string(),
+    };
+    let session = match session_id {
+      Some(session_id) => match self.lock_sessions().map(|sessions| sessions.get(session_id).cloned()) {
+        Ok(Some(session)) => Some(session),
+        Ok(None) => {
+          return Err(self.deny(
+            &grant,
+            "unknown_session",
+            format!("Agent session {} does not exist.", session_id),
+            None,
+          ))
+        }
+        Err(err) => return Err(self.deny(&grant, "policy_unavailable", err.to_string(), None)),
+      },
+      None => None,
+    };
+    if let Some(session) = &session {
+      if project_path_string(project_dir).ok().as_deref() != Some(session.project_path.as_str()) {
+        return Err(self.deny(
+          &grant,
+          "project_mismatch",
+          format!("Agent session is pinned to {}.", session.project_path),
+          None,
+        ));
+      }
+    }
+    if let Err(message) = check_project_root(project_dir) {
+      return Err(self.deny(&grant, "path_denied", message, None));
+    }
+
+    let (mode, operations) = match &session {
+      Some(session) => (session.mode, session.operations.clone()),
+      None => (AgentAccessMode::ReadOnly, vec![AgentOperation::RunTools]),
+    };
+    if let Some(operation) = requirement.operation {
+      if !operations.contains(&operation) {
+        return Err(self.deny(
+          &grant,
+          "operation_disabled",
+          format!("{} requires the {} operation, which is off for this session.", method, operation_label(operation)),
+          None,
+        ));
+      }
+    }
+    if requirement.writes && mode != AgentAccessMode::Write {
+      return Err(self.deny(
+        &grant,
+        "read_only",
+        format!("{} writes to the project; switch the agent session to write mode first.", method),
+        None,
+      ));
+    }
+    Ok(grant)
+  }
+
+  /// Checks one file a granted method is about to write.
+  pub(crate) fn authorize_write(
+    &self,
+    grant: &PolicyGrant,
+    project_dir: &Path,
+    target: &Path,
+  ) -> Result<(), Box<PolicyDenial>> {
+    check_write_path(project_dir, target).map_err(|message| {
+      self.deny(
+        grant,
+        "path_denied",
+        message,
+        Some(target.to_string_lossy().to_string()),
+      )
+    })
+  }
+
+  fn deny(
+    &self,
+    grant: &PolicyGrant,
+    code: &'static str,
+    message: String,
+    path: Option<String>,
+  ) -> Box<PolicyDenial> {
+    Box::new(self.record(PolicyDenial {
+      code,
+      message,
+      project_path: grant.project_path.clone(),
+      session_id: grant.session_id.clone(),
+      method: grant.method.clone(),
+      path,
+      at: now_iso(),
+    }))
+  }
+}
+
+fn missing_session(session_id: &str) -> AppError {
+  AppError::not_found(format!("Agent session {} does not exist.", session_id)).with_field("sessionId")
+}
+
+fn dedup_operations(mut operations: Vec<AgentOperation>) -> Vec<AgentOperation> {
+  let mut unique = Vec::new();
+  for operation in operations.drain(..) {
+    if !unique.contains(&operation) {
+      unique.push(operation);
+    }
+  }
+  unique
+}
+
+fn operation_label(operation: AgentOperation) -> &'static str {
+  match operation {
+    AgentOperation::EditContent => "edit manifests/content/theme",
+    AgentOperation::EditAssets => "edit assets",
+    AgentOperation::RunTools => "run validate/lint/export",
+  }
+}
+
+fn home_dir() -> Option<std::path::PathBuf> {
+  std::env::var_os("HOME")
+    .or_else(|| std::env::var_os("USERPROFILE"))
+    .map(std::path::PathBuf::from)
+}
+
+/// Projects may not live in the home root itself or under OS directories.
+fn check_project_root(project_dir: &Path) -> Result<(), String> {
+  let root = project_dir
+    .canonicalize()
+    .map_err(|err| format!("Project path {} is invalid: {}", project_dir.display(), err))?;
+  if home_dir().and_then(|home| home.canonicalize().ok()).is_some_and(|home| home == root) {
+    return Err("Agents cannot work in the home directory root.".to_string());
+  }
+  let normalized = root.to_string_lossy().trim_start_matches(r"\\?\").to_string();
+  let is_system = SYSTEM_ROOTS.iter().any(|system| {
+    let comparable = if system.contains('\\') {
+      normalized.to_ascii_lowercase()
+    } else {
+      normalized.clone()
+    };
+    comparable == *system || comparable.starts_with(&format!("{}{}", system, std::path::MAIN_SEPARATOR))
+  });
+  if root.parent().is_none() || is_system {
+    return Err(format!("{} is a system path.", root.display()));
+  }
+  Ok(())
+}
+
+fn is_credential_component(name: &str) -> bool {
+  let lower = name.to_ascii_lowercase();
+  if CREDENTIAL_NAMES.contains(&lower.as_str()) || lower.starts_with(".env.") {
+    return true;
+  }
+  Path::new(&lower)
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .is_some_and(|ext| CREDENTIAL_EXTENSIONS.contains(&ext))
+}
+
+/// Allow-list check for a write target inside the project. Symlinks are resolved so a link
+/// under `pages/` cannot point elsewhere.
+pub(crate) fn check_write_path(project_dir: &Path, target: &Path) -> Result<(), String> {
+  let relative = target
+    .strip_prefix(project_dir)
+    .map_err(|_| format!("{} is outside the project.", target.display()))?;
+  let mut parts = Vec::new();
+  for component in relative.components() {
+    match component {
+      Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+      Component::CurDir => {}
+      _ => return Err(format!("{} escapes the project.", relative.display())),
+    }
+  }
+  if parts.iter().any(|part| is_credential_component(part)) {
+    return Err(format!("{} is a credential file.", relative.display()));
+  }
+  let allowed = match parts.as_slice() {
+    [file] => WRITABLE_FILES.contains(&file.as_str()),
+    [dir, _, ..] => WRITABLE_DIRS.contains(&dir.as_str()),
+    [] => false,
+  };
+  if !allowed {
+    return Err(format!("{} is not a writable studio path.", relative.display()));
+  }
+
+  let root = project_dir
+    .canonicalize()
+    .map_err(|err| format!("Project path {} is invalid: {}", project_dir.display(), err))?;
+  let existing = target
+    .ancestors()
+    .find(|ancestor| ancestor.exists())
+    .unwrap_or(project_dir);
+  let resolved = existing
+    .canonicalize()
+    .map_err(|err| format!("Failed resolving {}: {}", existing.display(), err))?;
+  if !resolved.starts_with(&root) {
+    return Err(format!("{} resolves outside the project.", relative.display()));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn agents_cannot_write_exports() {
+    let project = tempfile::tempdir().unwrap();
+    let root = project.path();
+    assert!(check_write_path(root, &root.join("pages/home.json")).is_ok());
+    assert!(check_write_path(root, &root.join("exports/agent-transcripts/run-1.jsonl")).is_err());
+  }
+
+  #[test]
+  fn remote_clients_cannot_grant_write_access() {
+    let project = tempfile::tempdir().unwrap();
+    let policy = AgentPolicyHub::default();
+    assert!(policy
+      .check_remote_change(None, Some(AgentAccessMode::Write), None)
+      .is_err());
+    assert!(policy
+      .check_remote_change(None, Some(AgentAccessMode::ReadOnly), None)
+      .is_ok());
+
+    let read_only = policy
+      .start_session(project.path(), AgentAccessMode::ReadOnly, vec![AgentOperation::RunTools])
+      .unwrap();
+    assert!(policy
+      .check_remote_change(Some(read_only.session_id()), Some(AgentAccessMode::Write), None)
+      .is_err());
+
+    let write = policy
+      .start_session(project.path(), AgentAccessMode::Write, vec![AgentOperation::EditContent])
+      .unwrap();
+    let narrower: &[AgentOperation] = &[];
+    assert!(policy
+      .check_remote_change(Some(write.session_id()), None, Some(narrower))
+      .is_ok());
+    assert!(policy
+      .check_remote_change(Some(write.session_id()), None, Some(&[AgentOperation::EditAssets]))
+      .is_err());
+  }
+}
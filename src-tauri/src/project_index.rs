@@ -0,0 +1,393 @@
+//! Cached project listing for a workspace. A listing re-reads the workspace directory only when
+//! its mtime moved and re-parses a `project.json` only when its size or mtime changed, so
+//! filtering and paging a workspace of hundreds of projects costs one `stat` per project.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::error::AppError;
+use crate::{project_record_from_dir, ProjectRecord, PROJECT_META_FILE};
+
+#[derive(
+  Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS, clap::ValueEnum,
+)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ProjectSort {
+  Name,
+  Created,
+  #[default]
+  Updated,
+  /// Grouped by client A–Z, projects without one last, then by name.
+  Client,
+}
+
+/// Filters and paging for `query_projects`. Every field is optional; the default lists every
+/// project, most recently updated first.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProjectQuery {
+  /// Case-insensitive substring of the name, slug, site URL or client name.
+  #[serde(default)]
+  #[ts(as = "Option<String>", optional)]
+  pub(crate) search: String,
+  /// Only projects carrying every one of these tags, compared case-insensitively.
+  #[serde(default)]
+  #[ts(as = "Option<Vec<String>>", optional)]
+  pub(crate) tags: Vec<String>,
+  /// Only this client's projects, compared case-insensitively.
+  #[serde(default)]
+  #[ts(optional)]
+  pub(crate) client_name: Option<String>,
+  /// Only pinned projects when true, only unpinned ones when false.
+  #[serde(default)]
+  #[ts(optional)]
+  pub(crate) pinned: Option<bool>,
+  #[serde(default)]
+  #[ts(as = "Option<ProjectSort>", optional)]
+  pub(crate) sort: ProjectSort,
+  /// Reverses the default order, which is A–Z for names and clients and newest first for dates.
+  #[serde(default)]
+  #[ts(as = "Option<bool>", optional)]
+  pub(crate) reverse: bool,
+  #[serde(default)]
+  #[ts(as = "Option<u32>", optional)]
+  pub(crate) limit: Option<usize>,
+  #[serde(default)]
+  #[ts(as = "Option<u32>", optional)]
+  pub(crate) offset: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProjectPage {
+  pub(crate) projects: Vec<ProjectRecord>,
+  /// Matching projects before `offset` and `limit` were applied.
+  #[ts(type = "number")]
+  pub(crate) total: usize,
+  #[ts(type = "number")]
+  pub(crate) offset: usize,
+}
+
+/// What a cached record was parsed from; a different stamp means `project.json` was rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+  modified: Option<SystemTime>,
+  len: u64,
+}
+
+impl FileStamp {
+  fn of(path: &Path) -> Option<Self> {
+    let meta = fs::metadata(path).ok()?;
+    Some(Self {
+      modified: meta.modified().ok(),
+      len: meta.len(),
+    })
+  }
+}
+
+#[derive(Default)]
+struct WorkspaceIndex {
+  /// Workspace directory mtime when `dirs` was read. Creating, renaming, shelving or deleting
+  /// a project adds or removes an entry and moves it.
+  modified: Option<SystemTime>,
+  dirs: Vec<PathBuf>,
+  records: HashMap<PathBuf, (FileStamp, ProjectRecord)>,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct ProjectIndex {
+  workspaces: Arc<Mutex<HashMap<PathBuf, WorkspaceIndex>>>,
+}
+
+impl ProjectIndex {
+  /// Projects in `workspace` matching `query`. The caller has checked it is a directory.
+  pub(crate) fn query(&self, workspace: &Path, query: &ProjectQuery) -> Result<ProjectPage, AppError> {
+    let mut projects = self.records(workspace)?;
+    let search = query.search.trim().to_lowercase();
+    let tags: Vec<String> = query
+      .tags
+      .iter()
+      .map(|tag| tag.trim().to_lowercase())
+      .filter(|tag| !tag.is_empty())
+      .collect();
+    let client = query.client_name.as_deref().map(|client| client.trim().to_lowercase());
+    projects.retain(|project| {
+      matches_search(project, &search)
+        && has_tags(project, &tags)
+        && client.as_ref().map_or(true, |client| project.client_name.to_lowercase() == *client)
+        && query.pinned.map_or(true, |pinned| project.pinned == pinned)
+    });
+
+    projects.sort_by(|a, b| {
+      let natural = match query.sort {
+        ProjectSort::Name => compare_names(a, b),
+        ProjectSort::Created => b.created_at.cmp(&a.created_at),
+        ProjectSort::Updated => b.updated_at.cmp(&a.updated_at),
+        ProjectSort::Client => compare_clients(a, b).then_with(|| compare_names(a, b)),
+      };
+      let ordering = if query.reverse { natural.reverse() } else { natural };
+      ordering.then_with(|| a.path.cmp(&b.path))
+    });
+
+    let total = projects.len();
+    let projects = projects
+      .into_iter()
+      .skip(query.offset)
+      .take(query.limit.unwrap_or(usize::MAX))
+      .collect();
+    Ok(ProjectPage {
+      projects,
+      total,
+      offset: query.offset,
+    })
+  }
+
+  /// Every readable project record in `workspace`, refreshing whatever changed on disk.
+  fn records(&self, workspace: &Path) -> Result<Vec<ProjectRecord>, AppError> {
+    let mut workspaces = self.lock()?;
+    let index = workspaces.entry(workspace.to_path_buf()).or_default();
+
+    let modified = fs::metadata(workspace)
+      .and_then(|meta| meta.modified())
+      .map_err(|err| AppError::fs("Failed reading workspace", workspace, err))?;
+    if index.modified != Some(modified) {
+      index.dirs = project_dirs(workspace)?;
+      index.modified = Some(modified);
+      let dirs = &index.dirs;
+      index.records.retain(|dir, _| dirs.contains(dir));
+    }
+
+    let mut projects = Vec::with_capacity(index.dirs.len());
+    for dir in &index.dirs {
+      let Some(stamp) = FileStamp::of(&dir.join(PROJECT_META_FILE)) else {
+        index.records.remove(dir);
+        continue;
+      };
+      match index.records.get(dir) {
+        Some((cached, record)) if *cached == stamp => projects.push(record.clone()),
+        _ => match project_record_from_dir(dir) {
+          Ok(record) => {
+            index.records.insert(dir.clone(), (stamp, record.clone()));
+            projects.push(record);
+          }
+          Err(_) => {
+            index.records.remove(dir);
+          }
+        },
+      }
+    }
+    Ok(projects)
+  }
+
+  fn lock(&self) -> Result<MutexGuard<'_, HashMap<PathBuf, WorkspaceIndex>>, AppError> {
+    self.workspaces.lock().map_err(|_| AppError::lock("Project index"))
+  }
+}
+
+/// `*.manifold` directories directly inside the workspace. Shelved projects live one level
+/// down and are not listed.
+fn project_dirs(workspace: &Path) -> Result<Vec<PathBuf>, AppError> {
+  let entries =
+    fs::read_dir(workspace).map_err(|err| AppError::fs("Failed reading workspace", workspace, err))?;
+  let mut dirs = Vec::new();
+  for entry in entries {
+    let Ok(entry) = entry else {
+      continue;
+    };
+    let path = entry.path();
+    let is_project = path.is_dir()
+      && path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(".manifold"));
+    if is_project {
+      dirs.push(path);
+    }
+  }
+  Ok(dirs)
+}
+
+fn matches_search(project: &ProjectRecord, search: &str) -> bool {
+  search.is_empty()
+    || [&project.name, &project.slug, &project.site_url, &project.client_name]
+      .iter()
+      .any(|field| field.to_lowercase().contains(search))
+}
+
+fn has_tags(project: &ProjectRecord, tags: &[String]) -> bool {
+  tags
+    .iter()
+    .all(|wanted| project.tags.iter().any(|tag| tag.to_lowercase() == *wanted))
+}
+
+fn compare_clients(a: &ProjectRecord, b: &ProjectRecord) -> Ordering {
+  match (a.client_name.is_empty(), b.client_name.is_empty()) {
+    (false, true) => Ordering::Less,
+    (true, false) => Ordering::Greater,
+    _ => a.client_name.to_lowercase().cmp(&b.client_name.to_lowercase()),
+  }
+}
+
+fn compare_names(a: &ProjectRecord, b: &ProjectRecord) -> Ordering {
+  a.name
+    .to_lowercase()
+    .cmp(&b.name.to_lowercase())
+    .then_with(|| a.slug.cmp(&b.slug))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  /// Writes `<slug>.manifold/project.json` with `fields` over a minimal set of metadata.
+  fn write_project(workspace: &Path, slug: &str, fields: serde_json::Value) {
+    let mut metadata = json!({
+      "name": slug,
+      "slug": slug,
+      "siteUrl": "",
+      "createdAt": "2026-01-01T00:00:00Z",
+      "updatedAt": "2026-01-01T00:00:00Z"
+    });
+    if let (Some(metadata), Some(fields)) = (metadata.as_object_mut(), fields.as_object()) {
+      metadata.extend(fields.clone());
+    }
+    let dir = workspace.join(format!("{}.manifold", slug));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(PROJECT_META_FILE), metadata.to_string()).unwrap();
+  }
+
+  fn workspace() -> tempfile::TempDir {
+    let workspace = tempfile::tempdir().unwrap();
+    let path = workspace.path();
+    write_project(
+      path,
+      "alpha",
+      json!({ "name": "Alpha", "clientName": "Acme", "tags": ["web"], "pinned": true,
+              "createdAt": "2026-01-01T00:00:00Z", "updatedAt": "2026-01-05T00:00:00Z" }),
+    );
+    write_project(
+      path,
+      "beta",
+      json!({ "name": "Beta", "clientName": "acme", "tags": ["Web", "shop"],
+              "createdAt": "2026-01-02T00:00:00Z", "updatedAt": "2026-01-04T00:00:00Z" }),
+    );
+    write_project(
+      path,
+      "gamma",
+      json!({ "name": "Gamma", "tags": ["web"], "siteUrl": "https://gamma.dev",
+              "createdAt": "2026-01-03T00:00:00Z", "updatedAt": "2026-01-03T00:00:00Z" }),
+    );
+    write_project(
+      path,
+      "delta",
+      json!({ "name": "Delta", "clientName": "Zen", "tags": ["print"], "pinned": true,
+              "createdAt": "2026-01-04T00:00:00Z", "updatedAt": "2026-01-02T00:00:00Z" }),
+    );
+    workspace
+  }
+
+  fn slugs(page: &ProjectPage) -> Vec<&str> {
+    page.projects.iter().map(|project| project.slug.as_str()).collect()
+  }
+
+  fn query(index: &ProjectIndex, workspace: &Path, query: ProjectQuery) -> ProjectPage {
+    index.query(workspace, &query).unwrap()
+  }
+
+  #[test]
+  fn filters_sort_and_page_together() {
+    let workspace = workspace();
+    let (index, path) = (ProjectIndex::default(), workspace.path());
+
+    let page = query(
+      &index,
+      path,
+      ProjectQuery {
+        tags: vec!["WEB".to_string()],
+        sort: ProjectSort::Name,
+        reverse: true,
+        limit: Some(1),
+        offset: 1,
+        ..ProjectQuery::default()
+      },
+    );
+    assert_eq!(slugs(&page), ["beta"]);
+    assert_eq!((page.total, page.offset), (3, 1));
+
+    let acme = ProjectQuery {
+      client_name: Some(" ACME ".to_string()),
+      pinned: Some(true),
+      ..ProjectQuery::default()
+    };
+    assert_eq!(slugs(&query(&index, path, acme)), ["alpha"]);
+    let search = ProjectQuery {
+      search: "gamma.DEV".to_string(),
+      ..ProjectQuery::default()
+    };
+    assert_eq!(slugs(&query(&index, path, search)), ["gamma"]);
+  }
+
+  #[test]
+  fn each_sort_has_its_natural_order() {
+    let workspace = workspace();
+    let (index, path) = (ProjectIndex::default(), workspace.path());
+    let sorted = |sort, reverse| {
+      let page = query(
+        &index,
+        path,
+        ProjectQuery {
+          sort,
+          reverse,
+          ..ProjectQuery::default()
+        },
+      );
+      slugs(&page).into_iter().map(str::to_string).collect::<Vec<_>>()
+    };
+
+    assert_eq!(sorted(ProjectSort::Updated, false), ["alpha", "beta", "gamma", "delta"]);
+    assert_eq!(sorted(ProjectSort::Created, false), ["delta", "gamma", "beta", "alpha"]);
+    assert_eq!(sorted(ProjectSort::Created, true), ["alpha", "beta", "gamma", "delta"]);
+    assert_eq!(sorted(ProjectSort::Name, false), ["alpha", "beta", "delta", "gamma"]);
+    // Clients A–Z ignoring case, projects without one last.
+    assert_eq!(sorted(ProjectSort::Client, false), ["alpha", "beta", "delta", "gamma"]);
+    assert_eq!(sorted(ProjectSort::Client, true), ["gamma", "delta", "beta", "alpha"]);
+  }
+
+  #[test]
+  fn a_rewritten_project_json_shows_up_in_the_next_query() {
+    let workspace = workspace();
+    let (index, path) = (ProjectIndex::default(), workspace.path());
+    let by_name = || ProjectQuery {
+      sort: ProjectSort::Name,
+      ..ProjectQuery::default()
+    };
+    assert_eq!(query(&index, path, by_name()).projects[0].name, "Alpha");
+
+    write_project(path, "alpha", json!({ "name": "Zulu Alpha" }));
+    let page = query(&index, path, by_name());
+    assert_eq!(slugs(&page), ["beta", "delta", "gamma", "alpha"]);
+    assert_eq!(page.projects[3].name, "Zulu Alpha");
+  }
+
+  #[test]
+  fn added_and_removed_projects_show_up_in_the_next_query() {
+    let workspace = workspace();
+    let (index, path) = (ProjectIndex::default(), workspace.path());
+    assert_eq!(query(&index, path, ProjectQuery::default()).total, 4);
+
+    write_project(path, "epsilon", json!({ "updatedAt": "2026-02-01T00:00:00Z" }));
+    fs::remove_dir_all(path.join("beta.manifold")).unwrap();
+    let page = query(&index, path, ProjectQuery::default());
+    assert_eq!(slugs(&page), ["epsilon", "alpha", "gamma", "delta"]);
+    assert_eq!(page.total, 4);
+  }
+}
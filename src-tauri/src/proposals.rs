@@ -0,0 +1,703 @@
+//! Review queue for agent-proposed document changes. Proposals, their history and the revert
+//! stack are held in memory by the running app or server only: they are lost on restart, and an
+//! agent resubmits whatever was still pending.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::activity::ActivityOperation;
+use crate::error::AppError;
+use crate::events::{ChangeSource, EventHub, ProjectEvent};
+use crate::{
+  load_builder_doc, normalize_builder_doc, now_iso, project_path_string, read_project_metadata,
+  save_builder_doc, BlockDoc, BuilderProjectDoc, LiveServices, PageDoc,
+};
+
+/// Applied changes kept per project for revert; older entries fall off.
+const APPLIED_STACK_LIMIT: usize = 50;
+/// Resolved proposals kept per project for review history.
+const RESOLVED_HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ProposalStatus {
+  Pending,
+  Applied,
+  Rejected,
+  Reverted,
+  /// The project was saved after submission; the patch has to be resubmitted against the
+  /// newer document.
+  Stale,
+}
+
+/// One human-readable change between two documents, located by JSON pointer into the
+/// document it refers to (the proposed one, or the current one for removals).
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DocChange {
+  path: String,
+  summary: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PatchProposal {
+  id: String,
+  project_path: String,
+  summary: String,
+  author: String,
+  source: ChangeSource,
+  status: ProposalStatus,
+  #[ts(type = "number")]
+  base_revision: u64,
+  #[ts(type = "JsonPatch")]
+  #[schemars(with = "Vec<serde_json::Value>")]
+  patch: json_patch::Patch,
+  changes: Vec<DocChange>,
+  created_at: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  resolved_at: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  resolved_by: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProposalOutcome {
+  proposal: PatchProposal,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(type = "number", optional)]
+  revision: Option<u64>,
+}
+
+struct AppliedChange {
+  proposal_id: String,
+  before: BuilderProjectDoc,
+  /// Revision the project must still be at for this change to be reverted.
+  revision: u64,
+}
+
+#[derive(Default)]
+struct ProjectProposals {
+  next_id: u64,
+  proposals: Vec<PatchProposal>,
+  applied: Vec<AppliedChange>,
+}
+
+impl ProjectProposals {
+  fn find_mut(&mut self, proposal_id: &str) -> Result<&mut PatchProposal, AppError> {
+    self
+      .proposals
+      .iter_mut()
+      .find(|proposal| proposal.id == proposal_id)
+      .ok_or_else(|| missing_proposal(proposal_id))
+  }
+
+  fn prune_resolved(&mut self) {
+    let resolved = self
+      .proposals
+      .iter()
+      .filter(|proposal| proposal.status != ProposalStatus::Pending)
+      .count();
+    let mut excess = resolved.saturating_sub(RESOLVED_HISTORY_LIMIT);
+    self.proposals.retain(|proposal| {
+      if excess > 0 && proposal.status != ProposalStatus::Pending {
+        excess -= 1;
+        return false;
+      }
+      true
+    });
+  }
+}
+
+/// Review queue for document changes: a JSON Patch (RFC 6902) is held with its semantic diff
+/// until approved or rejected, and approved changes stack up so the latest can be reverted.
+/// Nothing here is written to disk; see the module docs.
+#[derive(Clone)]
+pub(crate) struct ProposalHub {
+  projects: Arc<Mutex<HashMap<String, ProjectProposals>>>,
+  events: EventHub,
+}
+
+impl ProposalHub {
+  pub(crate) fn new(events: EventHub) -> Self {
+    Self {
+      projects: Arc::new(Mutex::new(HashMap::new())),
+      events,
+    }
+  }
+
+  fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, ProjectProposals>>, AppError> {
+    self.projects.lock().map_err(|_| AppError::lock("Proposal"))
+  }
+
+  fn publish(&self, proposal: &PatchProposal) {
+    self.events.publish(ProjectEvent::ProposalChanged {
+      project_path: proposal.project_path.clone(),
+      proposal_id: proposal.id.clone(),
+      status: proposal.status,
+    });
+  }
+
+  /// Checks the patch against the current document and queues it for review. Nothing is
+  /// written to disk.
+  pub(crate) fn submit(
+    &self,
+    project_dir: &Path,
+    patch: json_patch::Patch,
+    summary: &str,
+    author: &str,
+    source: ChangeSource,
+  ) -> Result<PatchProposal, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let current = load_builder_doc(project_dir)?;
+    let base_revision = read_project_metadata(project_dir)?.revision;
+    let proposed = apply_patch(&current, &patch)?;
+    let changes = describe_changes(&current, &proposed);
+    if changes.is_empty() {
+      return Err(AppError::invalid("Patch does not change the project.").with_field("patch"));
+    }
+
+    let mut projects = self.lock()?;
+    let state = projects.entry(project_path.clone()).or_default();
+    state.next_id += 1;
+    let proposal = PatchProposal {
+      id: format!("proposal-{}", state.next_id),
+      project_path,
+      summary: summary.trim().to_string(),
+      author: author.to_string(),
+      source,
+      status: ProposalStatus::Pending,
+      base_revision,
+      patch,
+      changes,
+      created_at: now_iso(),
+      resolved_at: None,
+      resolved_by: None,
+      reason: None,
+    };
+    state.proposals.push(proposal.clone());
+    self.publish(&proposal);
+    Ok(proposal)
+  }
+
+  pub(crate) fn list(&self, project_dir: &Path) -> Result<Vec<PatchProposal>, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let projects = self.lock()?;
+    Ok(
+      projects
+        .get(&project_path)
+        .map(|state| state.proposals.clone())
+        .unwrap_or_default(),
+    )
+  }
+
+  /// Applies a pending proposal to the document it was reviewed against. When the project was
+  /// saved since submission the proposal is marked stale and the call fails with a conflict,
+  /// so an approval never writes changes the reviewer did not see.
+  pub(crate) fn approve(
+    &self,
+    services: &LiveServices,
+    project_dir: &Path,
+    proposal_id: &str,
+    client_id: &str,
+    source: ChangeSource,
+  ) -> Result<ProposalOutcome, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    services.writes.run(project_dir, || {
+      let mut projects = self.lock()?;
+      let state = projects
+        .get_mut(&project_path)
+        .ok_or_else(|| missing_proposal(proposal_id))?;
+      let proposal = state.find_mut(proposal_id)?;
+      if proposal.status != ProposalStatus::Pending {
+        return Err(AppError::conflict(format!(
+          "Proposal {} is already {}.",
+          proposal_id,
+          status_label(proposal.status)
+        )));
+      }
+
+      let current_revision = read_project_metadata(project_dir)?.revision;
+      if current_revision != proposal.base_revision {
+        let message = format!(
+          "Project changed since proposal {} was submitted (revision {} is now {}); resubmit it.",
+          proposal_id, proposal.base_revision, current_revision
+        );
+        proposal.status = ProposalStatus::Stale;
+        proposal.resolved_at = Some(now_iso());
+        proposal.reason = Some(message.clone());
+        let proposal = proposal.clone();
+        state.prune_resolved();
+        self.publish(&proposal);
+        return Err(AppError::conflict(message));
+      }
+
+      let current = load_builder_doc(project_dir)?;
+      let proposed = apply_patch(&current, &proposal.patch)
+        .map_err(|err| AppError::conflict(format!("Proposal {} no longer applies: {}", proposal_id, err)))?;
+      let revision = save_builder_doc(
+        project_dir,
+        &proposed,
+        &services.events,
+        &services.locks,
+        client_id,
+        source,
+        ActivityOperation::ApplyProposal,
+      )?;
+      services.collab.reload(project_dir)?;
+
+      proposal.status = ProposalStatus::Applied;
+      proposal.resolved_at = Some(now_iso());
+      proposal.resolved_by = Some(client_id.to_string());
+      let proposal = proposal.clone();
+      state.applied.push(AppliedChange {
+        proposal_id: proposal.id.clone(),
+        before: current,
+        revision,
+      });
+      if state.applied.len() > APPLIED_STACK_LIMIT {
+        state.applied.remove(0);
+      }
+      state.prune_resolved();
+      self.publish(&proposal);
+      Ok(ProposalOutcome {
+        proposal,
+        revision: Some(revision),
+      })
+    })
+  }
+
+  pub(crate) fn reject(
+    &self,
+    project_dir: &Path,
+    proposal_id: &str,
+    client_id: &str,
+    reason: Option<String>,
+  ) -> Result<PatchProposal, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    let mut projects = self.lock()?;
+    let state = projects
+      .get_mut(&project_path)
+      .ok_or_else(|| missing_proposal(proposal_id))?;
+    let proposal = state.find_mut(proposal_id)?;
+    if proposal.status != ProposalStatus::Pending {
+      return Err(AppError::conflict(format!(
+        "Proposal {} is already {}.",
+        proposal_id,
+        status_label(proposal.status)
+      )));
+    }
+    proposal.status = ProposalStatus::Rejected;
+    proposal.resolved_at = Some(now_iso());
+    proposal.resolved_by = Some(client_id.to_string());
+    proposal.reason = reason.filter(|reason| !reason.trim().is_empty());
+    let proposal = proposal.clone();
+    state.prune_resolved();
+    self.publish(&proposal);
+    Ok(proposal)
+  }
+
+  /// Restores the document from before the most recently applied proposal. Refused when the
+  /// project was saved since, so later edits are never silently discarded.
+  pub(crate) fn revert_last(
+    &self,
+    services: &LiveServices,
+    project_dir: &Path,
+    client_id: &str,
+    source: ChangeSource,
+  ) -> Result<ProposalOutcome, AppError> {
+    let project_path = project_path_string(project_dir)?;
+    services.writes.run(project_dir, || {
+      let mut projects = self.lock()?;
+      let state = projects
+        .get_mut(&project_path)
+        .filter(|state| !state.applied.is_empty())
+        .ok_or_else(nothing_to_revert)?;
+      let last = state
+        .applied
+        .last()
+        .ok_or_else(nothing_to_revert)?;
+      let current_revision = read_project_metadata(project_dir)?.revision;
+      if current_revision != last.revision {
+        return Err(AppError::conflict(format!(
+          "Project changed since proposal {} was applied (revision {} is now {}); revert would discard later edits.",
+          last.proposal_id, last.revision, current_revision
+        )));
+      }
+
+      let revision = save_builder_doc(
+        project_dir,
+        &last.before,
+        &services.events,
+        &services.locks,
+        client_id,
+        source,
+        ActivityOperation::RevertProposal,
+      )?;
+      services.collab.reload(project_dir)?;
+      let last = state
+        .applied
+        .pop()
+        .ok_or_else(nothing_to_revert)?;
+      // The revert is the newest save now; the entry below it stays revertible from here.
+      if let Some(previous) = state.applied.last_mut() {
+        previous.revision = revision;
+      }
+
+      let proposal = state.find_mut(&last.proposal_id)?;
+      proposal.status = ProposalStatus::Reverted;
+      proposal.resolved_at = Some(now_iso());
+      proposal.resolved_by = Some(client_id.to_string());
+      let proposal = proposal.clone();
+      self.publish(&proposal);
+      Ok(ProposalOutcome {
+        proposal,
+        revision: Some(revision),
+      })
+    })
+  }
+
+  /// Follows a renamed project directory: the queue, its history and the revert stack carry
+  /// over.
+  pub(crate) fn move_project(&self, from: &Path, to: &Path) -> Result<(), AppError> {
+    let from_path = project_path_string(from)?;
+    let to_path = project_path_string(to)?;
+    let mut projects = self.lock()?;
+    if let Some(mut state) = projects.remove(&from_path) {
+      for proposal in state.proposals.iter_mut() {
+        proposal.project_path = to_path.clone();
+      }
+      projects.insert(to_path, state);
+    }
+    Ok(())
+  }
+}
+
+fn missing_proposal(proposal_id: &str) -> AppError {
+  AppError::not_found(format!("Proposal {} does not exist.", proposal_id)).with_field("proposalId")
+}
+
+fn nothing_to_revert() -> AppError {
+  AppError::conflict("There is no applied change to revert.")
+}
+
+fn status_label(status: ProposalStatus) -> &'static str {
+  match status {
+    ProposalStatus::Pending => "pending",
+    ProposalStatus::Applied => "applied",
+    ProposalStatus::Rejected => "rejected",
+    ProposalStatus::Reverted => "reverted",
+    ProposalStatus::Stale => "stale",
+  }
+}
+
+/// Applies the patch to the serialized document; the result must still be a valid document.
+fn apply_patch(doc: &BuilderProjectDoc, patch: &json_patch::Patch) -> Result<BuilderProjectDoc, AppError> {
+  let mut value = serde_json::to_value(doc)
+    .map_err(|err| AppError::internal(format!("Failed serializing project: {}", err)))?;
+  json_patch::patch(&mut value, patch)
+    .map_err(|err| AppError::invalid(format!("Patch failed: {}", err)).with_field("patch"))?;
+  let patched = serde_json::from_value::<BuilderProjectDoc>(value)
+    .map_err(|err| AppError::invalid(format!("Patched document is invalid: {}", err)).with_field("patch"))?;
+  Ok(normalize_builder_doc(patched))
+}
+
+fn escape_pointer(token: &str) -> String {
+  token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Semantic diff between two documents, ordered site, sitemap, then pages.
+pub(crate) fn describe_changes(before: &BuilderProjectDoc, after: &BuilderProjectDoc) -> Vec<DocChange> {
+  let mut changes = Vec::new();
+  let mut change = |path: String, summary: String| changes.push(DocChange { path, summary });
+
+  if before.site.site_name != after.site.site_name {
+    change(
+      "/site/siteName".to_string(),
+      format!("Site name: \"{}\" → \"{}\"", before.site.site_name, after.site.site_name),
+    );
+  }
+  if before.site.base_url != after.site.base_url {
+    change(
+      "/site/baseUrl".to_string(),
+      format!("Base URL: \"{}\" → \"{}\"", before.site.base_url, after.site.base_url),
+    );
+  }
+  if before.sitemap.root_page_id != after.sitemap.root_page_id {
+    change(
+      "/sitemap/rootPageId".to_string(),
+      format!(
+        "Home page: {} → {}",
+        before.sitemap.root_page_id, after.sitemap.root_page_id
+      ),
+    );
+  }
+
+  for (index, page) in before.pages.iter().enumerate() {
+    if !after.pages.iter().any(|other| other.id == page.id) {
+      change(
+        format!("/pages/{}", index),
+        format!("Removed page \"{}\" ({})", page.title, page.route),
+      );
+    }
+  }
+  for (index, page) in after.pages.iter().enumerate() {
+    let path = format!("/pages/{}", index);
+    match before.pages.iter().find(|other| other.id == page.id) {
+      None => change(path, format!("Added page \"{}\" ({})", page.title, page.route)),
+      Some(previous) => describe_page_changes(previous, page, &path, &mut change),
+    }
+  }
+
+  let shared_before: Vec<&String> = before
+    .sitemap
+    .page_order
+    .iter()
+    .filter(|page_id| after.sitemap.page_order.contains(page_id))
+    .collect();
+  let shared_after: Vec<&String> = after
+    .sitemap
+    .page_order
+    .iter()
+    .filter(|page_id| before.sitemap.page_order.contains(page_id))
+    .collect();
+  if shared_before != shared_after {
+    change(
+      "/sitemap/pageOrder".to_string(),
+      format!(
+        "Reordered pages: {}",
+        after.sitemap.page_order.join(", ")
+      ),
+    );
+  }
+  changes
+}
+
+fn describe_page_changes(
+  before: &PageDoc,
+  after: &PageDoc,
+  path: &str,
+  change: &mut impl FnMut(String, String),
+) {
+  let label = &after.title;
+  if before.title != after.title {
+    change(
+      format!("{}/title", path),
+      format!("Page title: \"{}\" → \"{}\"", before.title, after.title),
+    );
+  }
+  if before.route != after.route {
+    change(
+      format!("{}/route", path),
+      format!("Route of \"{}\": {} → {}", label, before.route, after.route),
+    );
+  }
+  if before.seo.title != after.seo.title {
+    change(
+      format!("{}/seo/title", path),
+      format!("SEO title of \"{}\": \"{}\" → \"{}\"", label, before.seo.title, after.seo.title),
+    );
+  }
+  if before.seo.description != after.seo.description {
+    change(
+      format!("{}/seo/description", path),
+      format!("SEO description of \"{}\" changed", label),
+    );
+  }
+
+  for (index, block) in before.blocks.iter().enumerate() {
+    if !after.blocks.iter().any(|other| other.id == block.id) {
+      change(
+        format!("{}/blocks/{}", path, index),
+        format!("Removed {} block {} from \"{}\"", block.block_type, block.id, label),
+      );
+    }
+  }
+  for (index, block) in after.blocks.iter().enumerate() {
+    let block_path = format!("{}/blocks/{}", path, index);
+    match before.blocks.iter().find(|other| other.id == block.id) {
+      None => change(
+        block_path,
+        format!("Added {} block {} to \"{}\"", block.block_type, block.id, label),
+      ),
+      Some(previous) => describe_block_changes(previous, block, &block_path, label, change),
+    }
+  }
+
+  let kept_before: Vec<&str> = before
+    .blocks
+    .iter()
+    .filter(|block| after.blocks.iter().any(|other| other.id == block.id))
+    .map(|block| block.id.as_str())
+    .collect();
+  let kept_after: Vec<&str> = after
+    .blocks
+    .iter()
+    .filter(|block| before.blocks.iter().any(|other| other.id == block.id))
+    .map(|block| block.id.as_str())
+    .collect();
+  if kept_before != kept_after {
+    change(
+      format!("{}/blocks", path),
+      format!("Reordered blocks on \"{}\"", label),
+    );
+  }
+}
+
+fn describe_block_changes(
+  before: &BlockDoc,
+  after: &BlockDoc,
+  path: &str,
+  page_label: &str,
+  change: &mut impl FnMut(String, String),
+) {
+  let label = format!("{} on \"{}\"", after.id, page_label);
+  if before.block_type != after.block_type {
+    change(
+      format!("{}/type", path),
+      format!("Block {} type: {} → {}", label, before.block_type, after.block_type),
+    );
+  }
+  if before.visibility != after.visibility {
+    change(
+      format!("{}/visibility", path),
+      format!("Block {} is now {}", label, after.visibility),
+    );
+  }
+  if before.style_overrides != after.style_overrides {
+    change(
+      format!("{}/styleOverrides", path),
+      format!("Style overrides of block {} changed", label),
+    );
+  }
+
+  match (before.props.as_object(), after.props.as_object()) {
+    (Some(old_props), Some(new_props)) => {
+      let mut keys: Vec<&String> = old_props.keys().chain(new_props.keys()).collect();
+      keys.sort();
+      keys.dedup();
+      for key in keys {
+        let prop_path = format!("{}/props/{}", path, escape_pointer(key));
+        match (old_props.get(key), new_props.get(key)) {
+          (None, Some(value)) => change(prop_path, format!("Block {} set {} = {}", label, key, value)),
+          (Some(_), None) => change(prop_path, format!("Block {} removed {}", label, key)),
+          (Some(old), Some(new)) if old != new => {
+            change(prop_path, format!("Block {} {}: {} → {}", label, key, old, new))
+          }
+          _ => {}
+        }
+      }
+    }
+    _ if before.props != after.props => {
+      change(format!("{}/props", path), format!("Props of block {} replaced", label));
+    }
+    _ => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::error::ErrorCode;
+
+  fn rename_site(name: &str) -> json_patch::Patch {
+    serde_json::from_value(serde_json::json!([
+      { "op": "replace", "path": "/site/siteName", "value": name }
+    ]))
+    .unwrap()
+  }
+
+  #[test]
+  fn approving_after_a_save_marks_the_proposal_stale() {
+    let workspace = tempfile::tempdir().unwrap();
+    let project_dir = crate::test_project(workspace.path());
+    let services = LiveServices::new();
+    let proposals = &services.proposals;
+    let proposal = proposals
+      .submit(&project_dir, rename_site("Proposed"), "Rename", "agent", ChangeSource::Agent)
+      .unwrap();
+
+    let mut doc = load_builder_doc(&project_dir).unwrap();
+    doc.site.site_name = "Edited".to_string();
+    save_builder_doc(
+      &project_dir,
+      &doc,
+      &services.events,
+      &services.locks,
+      "desktop",
+      ChangeSource::Desktop,
+      ActivityOperation::Save,
+    )
+    .unwrap();
+
+    let err = proposals
+      .approve(&services, &project_dir, &proposal.id, "desktop", ChangeSource::Desktop)
+      .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Conflict);
+    assert_eq!(load_builder_doc(&project_dir).unwrap().site.site_name, "Edited");
+    let listed = proposals.list(&project_dir).unwrap();
+    assert_eq!(listed[0].status, ProposalStatus::Stale);
+  }
+
+  #[test]
+  fn approving_keeps_the_reviewed_changes() {
+    let workspace = tempfile::tempdir().unwrap();
+    let project_dir = crate::test_project(workspace.path());
+    let services = LiveServices::new();
+    let proposal = services
+      .proposals
+      .submit(&project_dir, rename_site("Proposed"), "Rename", "agent", ChangeSource::Agent)
+      .unwrap();
+
+    let outcome = services
+      .proposals
+      .approve(&services, &project_dir, &proposal.id, "desktop", ChangeSource::Desktop)
+      .unwrap();
+    assert_eq!(outcome.proposal.status, ProposalStatus::Applied);
+    assert_eq!(outcome.proposal.changes.len(), proposal.changes.len());
+    assert_eq!(load_builder_doc(&project_dir).unwrap().site.site_name, "Proposed");
+  }
+
+  #[test]
+  fn reverting_walks_back_through_every_applied_proposal() {
+    let workspace = tempfile::tempdir().unwrap();
+    let project_dir = crate::test_project(workspace.path());
+    let services = LiveServices::new();
+    let proposals = &services.proposals;
+    let original = load_builder_doc(&project_dir).unwrap().site.site_name;
+    for name in ["First", "Second"] {
+      let proposal = proposals
+        .submit(&project_dir, rename_site(name), "Rename", "agent", ChangeSource::Agent)
+        .unwrap();
+      proposals
+        .approve(&services, &project_dir, &proposal.id, "desktop", ChangeSource::Desktop)
+        .unwrap();
+    }
+
+    let second = proposals
+      .revert_last(&services, &project_dir, "desktop", ChangeSource::Desktop)
+      .unwrap();
+    assert_eq!(second.proposal.id, "proposal-2");
+    assert_eq!(load_builder_doc(&project_dir).unwrap().site.site_name, "First");
+    let first = proposals
+      .revert_last(&services, &project_dir, "desktop", ChangeSource::Desktop)
+      .unwrap();
+    assert_eq!(first.proposal.id, "proposal-1");
+    assert_eq!(load_builder_doc(&project_dir).unwrap().site.site_name, original);
+    let err = proposals
+      .revert_last(&services, &project_dir, "desktop", ChangeSource::Desktop)
+      .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Conflict);
+  }
+}
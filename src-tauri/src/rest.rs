@@ -0,0 +1,423 @@
+//! Resource routes under `/api/v1/projects`, addressed by project slug instead of a raw path.
+//! Every response carries an ETag of its body; `If-None-Match` answers 304 and `If-Match` on
+//! writes answers 412 when the resource changed since it was read.
+
+use std::path::{Path, PathBuf};
+
+use axum::extract::{self, State};
+use axum::http::header::{ETAG, IF_MATCH, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ts_rs::TS;
+
+use crate::activity::ActivityOperation;
+use crate::error::{AppError, JsonBody};
+use crate::events::ChangeSource;
+use crate::{
+  canonical_page_id_from_route, load_builder_doc, normalize_site_url, normalize_slug,
+  project_record_from_dir, query_workspace_projects, require_remote_token, save_builder_doc,
+  BuilderProjectDoc, PageDoc,
+  ProjectQuery, ProjectRecord, RemoteApiState, SiteDoc, SitemapDoc, PROJECT_META_FILE,
+};
+
+/// Identifies the caller for page locks, like `clientId` in the POST routes.
+const CLIENT_ID_HEADER: &str = "x-manifold-client-id";
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PageSummary {
+  id: String,
+  title: String,
+  route: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProjectResource {
+  project: ProjectRecord,
+  site: SiteDoc,
+  sitemap: SitemapDoc,
+  /// In sitemap order; fetch each one from `pages/{pageId}`.
+  pages: Vec<PageSummary>,
+}
+
+/// Fields to change; omitted fields keep their value.
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SitePatch {
+  #[serde(default)]
+  #[ts(optional)]
+  site_name: Option<String>,
+  #[serde(default)]
+  #[ts(optional)]
+  base_url: Option<String>,
+}
+
+fn etag_of<T: Serialize>(value: &T) -> Result<String, AppError> {
+  let bytes = serde_json::to_vec(value)
+    .map_err(|err| AppError::internal(format!("Failed serializing response: {}", err)))?;
+  let digest = Sha256::digest(&bytes);
+  let hex = digest[..16].iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+  Ok(format!("\"{}\"", hex))
+}
+
+/// True when an `If-Match`/`If-None-Match` list names `etag` (or is `*`).
+fn header_matches(headers: &HeaderMap, name: HeaderName, etag: &str) -> Option<bool> {
+  let value = headers.get(name)?.to_str().ok()?;
+  Some(
+    value
+      .split(',')
+      .map(|candidate| candidate.trim().trim_start_matches("W/"))
+      .any(|candidate| candidate == "*" || candidate == etag),
+  )
+}
+
+fn check_if_match(headers: &HeaderMap, etag: &str) -> Result<(), AppError> {
+  match header_matches(headers, IF_MATCH, etag) {
+    Some(false) => Err(AppError::precondition_failed(
+      "Resource changed since it was read; fetch it again and retry.",
+    )),
+    _ => Ok(()),
+  }
+}
+
+fn tagged<T: Serialize>(headers: &HeaderMap, body: &T) -> Result<Response, AppError> {
+  let etag = etag_of(body)?;
+  let etag_header =
+    HeaderValue::from_str(&etag).map_err(|err| AppError::internal(format!("Invalid ETag: {}", err)))?;
+  if header_matches(headers, IF_NONE_MATCH, &etag) == Some(true) {
+    return Ok((StatusCode::NOT_MODIFIED, [(ETAG, etag_header)]).into_response());
+  }
+  Ok(([(ETAG, etag_header)], Json(body)).into_response())
+}
+
+fn client_id(headers: &HeaderMap) -> String {
+  headers
+    .get(CLIENT_ID_HEADER)
+    .and_then(|value| value.to_str().ok())
+    .unwrap_or_default()
+    .to_string()
+}
+
+/// `<workspace>/<slug>.manifold`, rejecting anything that is not already a normalized slug.
+pub(crate) fn project_dir(api: &RemoteApiState, slug: &str) -> Result<PathBuf, AppError> {
+  if slug.is_empty() || normalize_slug(slug) != slug {
+    return Err(AppError::invalid(format!("Invalid project slug {}", slug)).with_field("slug"));
+  }
+  let project_dir = PathBuf::from(&api.workspace_root).join(format!("{}.manifold", slug));
+  if !project_dir.join(PROJECT_META_FILE).is_file() {
+    return Err(AppError::not_found(format!("Project {} not found", slug)).with_field("slug"));
+  }
+  Ok(project_dir)
+}
+
+fn missing_page(page_id: &str) -> AppError {
+  AppError::not_found(format!("Page {} not found", page_id)).with_field("pageId")
+}
+
+fn project_resource(project_dir: &Path) -> Result<ProjectResource, AppError> {
+  let doc = load_builder_doc(project_dir)?;
+  let pages = doc
+    .pages
+    .iter()
+    .map(|page| PageSummary {
+      id: page.id.clone(),
+      title: page.title.clone(),
+      route: page.route.clone(),
+    })
+    .collect();
+  Ok(ProjectResource {
+    project: project_record_from_dir(project_dir)?,
+    site: doc.site,
+    sitemap: doc.sitemap,
+    pages,
+  })
+}
+
+pub(crate) async fn list_projects_resource(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+) -> Result<Response, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let projects =
+    query_workspace_projects(&api.services.index, &api.workspace_root, &ProjectQuery::default())?
+      .projects;
+  tagged(&headers, &projects)
+}
+
+pub(crate) async fn get_project(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  extract::Path(slug): extract::Path<String>,
+) -> Result<Response, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let project_dir = project_dir(&api, &slug)?;
+  tagged(&headers, &project_resource(&project_dir)?)
+}
+
+pub(crate) async fn get_site(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  extract::Path(slug): extract::Path<String>,
+) -> Result<Response, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let project_dir = project_dir(&api, &slug)?;
+  tagged(&headers, &load_builder_doc(&project_dir)?.site)
+}
+
+/// `If-Match` takes the ETag from `GET site` or a previous `PATCH site`.
+pub(crate) async fn patch_site(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  extract::Path(slug): extract::Path<String>,
+  JsonBody(patch): JsonBody<SitePatch>,
+) -> Result<Response, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let project_dir = project_dir(&api, &slug)?;
+  let site = api.services.writes.run(&project_dir, || {
+    let mut doc = load_builder_doc(&project_dir)?;
+    check_if_match(&headers, &etag_of(&doc.site)?)?;
+    if let Some(site_name) = patch.site_name {
+      doc.site.site_name = site_name;
+    }
+    if let Some(base_url) = patch.base_url {
+      doc.site.base_url = normalize_site_url(&base_url);
+    }
+    save(&api, &project_dir, &doc, &headers)?;
+    Ok(doc.site)
+  })?;
+  tagged(&HeaderMap::new(), &site)
+}
+
+pub(crate) async fn get_page(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  extract::Path((slug, page_id)): extract::Path<(String, String)>,
+) -> Result<Response, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let project_dir = project_dir(&api, &slug)?;
+  let doc = load_builder_doc(&project_dir)?;
+  let page = doc
+    .pages
+    .iter()
+    .find(|page| page.id == page_id)
+    .ok_or_else(|| missing_page(&page_id))?;
+  tagged(&headers, page)
+}
+
+/// Replaces a page, or adds it at the end of the sitemap. Page ids derive from the route, so
+/// the body's route must keep the page at `pageId` (or be the route it already has).
+pub(crate) async fn put_page(
+  State(api): State<RemoteApiState>,
+  headers: HeaderMap,
+  extract::Path((slug, page_id)): extract::Path<(String, String)>,
+  JsonBody(mut page): JsonBody<PageDoc>,
+) -> Result<Response, AppError> {
+  require_remote_token(&headers, &api.token)?;
+  let project_dir = project_dir(&api, &slug)?;
+  let status = api.services.writes.run(&project_dir, || {
+    let mut doc = load_builder_doc(&project_dir)?;
+    let routed_id = canonical_page_id_from_route(&page.route);
+    let keeps_route =
+      doc.pages.iter().any(|existing| existing.id == page_id && existing.route == page.route);
+    if routed_id != page_id && !keeps_route {
+      return Err(
+        AppError::invalid(format!("Route {} belongs to page {}, not {}", page.route, routed_id, page_id))
+          .with_field("route"),
+      );
+    }
+    page.id = page_id.clone();
+
+    let status = match doc.pages.iter_mut().find(|existing| existing.id == page_id) {
+      Some(existing) => {
+        check_if_match(&headers, &etag_of(existing)?)?;
+        *existing = page.clone();
+        StatusCode::OK
+      }
+      None => {
+        // An `If-Match` expects an existing page; creating one would skip the check.
+        if headers.contains_key(IF_MATCH) {
+          return Err(AppError::precondition_failed(format!("Page {} does not exist", page_id)));
+        }
+        doc.pages.push(page.clone());
+        doc.sitemap.page_order.push(page_id.clone());
+        StatusCode::CREATED
+      }
+    };
+    save(&api, &project_dir, &doc, &headers)?;
+    Ok(status)
+  })?;
+  let mut response = tagged(&HeaderMap::new(), &page)?;
+  *response.status_mut() = status;
+  Ok(response)
+}
+
+fn save(
+  api: &RemoteApiState,
+  project_dir: &Path,
+  doc: &BuilderProjectDoc,
+  headers: &HeaderMap,
+) -> Result<(), AppError> {
+  save_builder_doc(
+    project_dir,
+    doc,
+    &api.services.events,
+    &api.services.locks,
+    &client_id(headers),
+    ChangeSource::Remote,
+    ActivityOperation::Save,
+  )?;
+  api.services.collab.reload(project_dir)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::body::Body;
+  use axum::http::{Method, Request};
+  use axum::Router;
+  use tower::ServiceExt;
+
+  const TOKEN: &str = "secret";
+
+  /// The remote router over a workspace holding one project, `site`.
+  fn router(workspace: &Path) -> Router {
+    crate::test_project(workspace);
+    crate::remote_router(RemoteApiState {
+      token: TOKEN.to_string(),
+      workspace_root: workspace.to_string_lossy().into_owned(),
+      frontend_dist: workspace.join("dist"),
+      services: crate::LiveServices::new(),
+    })
+  }
+
+  async fn send(
+    router: &Router,
+    method: Method,
+    path: &str,
+    headers: &[(HeaderName, &str)],
+    body: Option<serde_json::Value>,
+  ) -> Response {
+    let mut request = Request::builder()
+      .method(method)
+      .uri(format!("{}{}", crate::openapi::API_PREFIX, path))
+      .header("x-manifold-token", TOKEN);
+    for (name, value) in headers {
+      request = request.header(name, *value);
+    }
+    let body = match body {
+      Some(body) => {
+        request = request.header("content-type", "application/json");
+        Body::from(body.to_string())
+      }
+      None => Body::empty(),
+    };
+    router.clone().oneshot(request.body(body).unwrap()).await.unwrap()
+  }
+
+  fn etag(response: &Response) -> String {
+    response.headers()[ETAG].to_str().unwrap().to_string()
+  }
+
+  async fn body_json(response: Response) -> serde_json::Value {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+  }
+
+  #[tokio::test]
+  async fn site_patches_with_the_etag_from_get_site() {
+    let workspace = tempfile::tempdir().unwrap();
+    let router = router(workspace.path());
+    let read = send(&router, Method::GET, "/projects/site/site", &[], None).await;
+    assert_eq!(read.status(), StatusCode::OK);
+    let read_etag = etag(&read);
+
+    let rename = serde_json::json!({ "siteName": "Renamed" });
+    let patched = send(
+      &router,
+      Method::PATCH,
+      "/projects/site/site",
+      &[(IF_MATCH, &read_etag)],
+      Some(rename.clone()),
+    )
+    .await;
+    assert_eq!(patched.status(), StatusCode::OK);
+    assert_ne!(etag(&patched), read_etag);
+    assert_eq!(body_json(patched).await["siteName"], "Renamed");
+
+    let stale = send(&router, Method::PATCH, "/projects/site/site", &[(IF_MATCH, &read_etag)], Some(rename)).await;
+    assert_eq!(stale.status(), StatusCode::PRECONDITION_FAILED);
+  }
+
+  #[tokio::test]
+  async fn a_matching_if_none_match_answers_304() {
+    let workspace = tempfile::tempdir().unwrap();
+    let router = router(workspace.path());
+    let read = send(&router, Method::GET, "/projects/site", &[], None).await;
+    assert_eq!(read.status(), StatusCode::OK);
+    let read_etag = etag(&read);
+
+    let cached = send(&router, Method::GET, "/projects/site", &[(IF_NONE_MATCH, &read_etag)], None).await;
+    assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(etag(&cached), read_etag);
+    let other = send(&router, Method::GET, "/projects/site", &[(IF_NONE_MATCH, "\"other\"")], None).await;
+    assert_eq!(other.status(), StatusCode::OK);
+  }
+
+  #[tokio::test]
+  async fn put_page_checks_if_match_against_the_stored_page() {
+    let workspace = tempfile::tempdir().unwrap();
+    let router = router(workspace.path());
+    let read = send(&router, Method::GET, "/projects/site/pages/home", &[], None).await;
+    let read_etag = etag(&read);
+    let mut page = body_json(read).await;
+    page["title"] = "Welcome".into();
+
+    let stale = send(
+      &router,
+      Method::PUT,
+      "/projects/site/pages/home",
+      &[(IF_MATCH, "\"stale\"")],
+      Some(page.clone()),
+    )
+    .await;
+    assert_eq!(stale.status(), StatusCode::PRECONDITION_FAILED);
+    let saved = send(&router, Method::PUT, "/projects/site/pages/home", &[(IF_MATCH, &read_etag)], Some(page)).await;
+    assert_eq!(saved.status(), StatusCode::OK);
+    let reread = send(&router, Method::GET, "/projects/site/pages/home", &[], None).await;
+    assert_eq!(body_json(reread).await["title"], "Welcome");
+  }
+
+  #[tokio::test]
+  async fn put_page_adds_pages_only_at_their_route_id() {
+    let workspace = tempfile::tempdir().unwrap();
+    let router = router(workspace.path());
+    let read = send(&router, Method::GET, "/projects/site/pages/home", &[], None).await;
+    let mut page = body_json(read).await;
+    page["title"] = "About".into();
+    page["route"] = "/about".into();
+
+    let misrouted = send(&router, Method::PUT, "/projects/site/pages/team", &[], Some(page.clone())).await;
+    assert_eq!(misrouted.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body_json(misrouted).await["details"]["field"], "route");
+    let expected = send(
+      &router,
+      Method::PUT,
+      "/projects/site/pages/about",
+      &[(IF_MATCH, "*")],
+      Some(page.clone()),
+    )
+    .await;
+    assert_eq!(expected.status(), StatusCode::PRECONDITION_FAILED);
+
+    let created = send(&router, Method::PUT, "/projects/site/pages/about", &[], Some(page)).await;
+    assert_eq!(created.status(), StatusCode::CREATED);
+    assert_eq!(body_json(created).await["id"], "about");
+    let project = body_json(send(&router, Method::GET, "/projects/site", &[], None).await).await;
+    assert_eq!(project["sitemap"]["pageOrder"], serde_json::json!(["home", "about"]));
+  }
+}
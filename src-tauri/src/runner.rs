@@ -0,0 +1,565 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::oneshot;
+use ts_rs::TS;
+
+use crate::error::AppError;
+use crate::events::{EventHub, ProjectEvent};
+use crate::{now_iso, project_path_string, PROJECT_META_FILE};
+
+const TRANSCRIPTS_DIR: &str = "exports/agent-transcripts";
+const DEFAULT_RUN_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const FINISHED_RUN_LIMIT: usize = 50;
+/// How long to wait for output after exit; grandchildren can keep the pipes open.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Agent CLIs a run may launch, by the name a request gives. Each is looked up on `PATH`.
+const AGENT_CLIS: &[&str] = &["codex", "claude", "gemini", "aider"];
+
+/// Variables inherited from the studio process. Anything else must be named in `passEnv`.
+const BASE_ENV: &[&str] = &[
+  "PATH", "HOME", "USER", "USERNAME", "LANG", "LC_ALL", "LC_CTYPE", "TERM", "TMPDIR", "TEMP", "TMP",
+  "SYSTEMROOT", "USERPROFILE", "APPDATA", "LOCALAPPDATA",
+];
+
+#[derive(Debug, Clone, Deserialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentRunRequest {
+  /// Allow-listed agent CLI to launch, e.g. `codex`. Paths are not accepted.
+  command: String,
+  #[serde(default)]
+  #[ts(as = "Option<Vec<String>>", optional)]
+  args: Vec<String>,
+  #[serde(default)]
+  #[ts(type = "number", optional)]
+  timeout_secs: Option<u64>,
+  /// Extra environment variable names to inherit, such as an API key.
+  #[serde(default)]
+  #[ts(as = "Option<Vec<String>>", optional)]
+  pass_env: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AgentRunStatus {
+  Running,
+  Succeeded,
+  Failed,
+  Cancelled,
+  TimedOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum OutputStream {
+  Stdout,
+  Stderr,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentRunInfo {
+  run_id: String,
+  project_path: String,
+  command: String,
+  args: Vec<String>,
+  status: AgentRunStatus,
+  started_at: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  finished_at: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  exit_code: Option<i32>,
+  transcript_path: String,
+}
+
+struct RunEntry {
+  info: AgentRunInfo,
+  cancel: Option<oneshot::Sender<()>>,
+}
+
+/// Appends JSON lines to a run's transcript; write failures are logged, never fatal.
+#[derive(Clone)]
+struct Transcript {
+  file: Arc<Mutex<fs::File>>,
+}
+
+impl Transcript {
+  fn create(path: &Path) -> Result<Self, AppError> {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).map_err(|err| AppError::fs("Failed creating transcript dir", parent, err))?;
+    }
+    let file = fs::File::create(path).map_err(|err| AppError::fs("Failed creating transcript", path, err))?;
+    Ok(Self {
+      file: Arc::new(Mutex::new(file)),
+    })
+  }
+
+  fn append(&self, entry: serde_json::Value) {
+    let Ok(mut file) = self.file.lock() else {
+      return;
+    };
+    if let Err(err) = writeln!(file, "{}", entry) {
+      log::warn!("failed writing agent transcript: {}", err);
+    }
+  }
+}
+
+/// Supervises agent CLI processes: each runs in its project directory with a scrubbed
+/// environment, streams output lines as project events and ends on exit, cancel or timeout.
+#[derive(Clone)]
+pub(crate) struct AgentRunnerHub {
+  runs: Arc<Mutex<HashMap<String, RunEntry>>>,
+  next_run: Arc<AtomicU64>,
+  /// Allow-listed CLI names and the program each launches.
+  clis: Arc<HashMap<String, PathBuf>>,
+  events: EventHub,
+}
+
+impl AgentRunnerHub {
+  pub(crate) fn new(events: EventHub) -> Self {
+    let clis = AGENT_CLIS
+      .iter()
+      .map(|name| (name.to_string(), PathBuf::from(name)))
+      .collect();
+    Self::with_clis(events, clis)
+  }
+
+  /// A runner limited to `clis`, mapping request names to programs. Bare program names are
+  /// looked up on `PATH` when a run starts.
+  pub(crate) fn with_clis(events: EventHub, clis: HashMap<String, PathBuf>) -> Self {
+    Self {
+      runs: Arc::new(Mutex::new(HashMap::new())),
+      next_run: Arc::new(AtomicU64::new(1)),
+      clis: Arc::new(clis),
+      events,
+    }
+  }
+
+  /// The program behind an allow-listed CLI name.
+  fn resolve(&self, command: &str) -> Result<PathBuf, AppError> {
+    let Some(program) = self.clis.get(command) else {
+      let mut allowed: Vec<&str> = self.clis.keys().map(String::as_str).collect();
+      allowed.sort();
+      return Err(
+        AppError::permission_denied(format!(
+          "{} is not an allowed agent CLI; expected one of: {}.",
+          command,
+          allowed.join(", ")
+        ))
+        .with_field("command"),
+      );
+    };
+    if program.components().count() > 1 {
+      return Ok(program.clone());
+    }
+    find_on_path(program).ok_or_else(|| {
+      AppError::not_found(format!("{} was not found on PATH.", program.display())).with_field("command")
+    })
+  }
+
+  fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, RunEntry>>, AppError> {
+    self.runs.lock().map_err(|_| AppError::lock("Agent runner"))
+  }
+
+  /// Launches the process and returns immediately; must be called inside the async runtime.
+  pub(crate) fn start(&self, project_dir: &Path, request: AgentRunRequest) -> Result<AgentRunInfo, AppError> {
+    let command = request.command.trim().to_string();
+    if command.is_empty() {
+      return Err(AppError::invalid("Agent command is required.").with_field("command"));
+    }
+    if !project_dir.join(PROJECT_META_FILE).is_file() {
+      return Err(AppError::project_not_found(project_dir));
+    }
+    let project_dir = project_dir
+      .canonicalize()
+      .map_err(|err| AppError::fs("Failed resolving project", project_dir, err))?;
+    let project_path = project_path_string(&project_dir)?;
+
+    let run_id = format!(
+      "run-{}-{}",
+      Utc::now().format("%Y%m%dT%H%M%S%3f"),
+      self.next_run.fetch_add(1, Ordering::Relaxed)
+    );
+    let transcript_path = project_dir.join(TRANSCRIPTS_DIR).join(format!("{}.jsonl", run_id));
+
+    let program = self.resolve(&command)?;
+    let mut process = Command::new(&program);
+    process
+      .args(&request.args)
+      .current_dir(&project_dir)
+      .env_clear()
+      .envs(scrubbed_env(&request.pass_env))
+      .env("MANIFOLD_PROJECT_DIR", &project_path)
+      .stdin(Stdio::null())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .kill_on_drop(true);
+    let mut child = process.spawn().map_err(|err| {
+      let message = format!("Failed starting {}: {}", command, err);
+      if err.kind() == std::io::ErrorKind::NotFound {
+        AppError::not_found(message).with_field("command")
+      } else {
+        AppError::internal(message)
+      }
+    })?;
+    // Dropping the child on error kills it, so no run outlives a missing transcript.
+    let transcript = Transcript::create(&transcript_path)?;
+
+    let info = AgentRunInfo {
+      run_id: run_id.clone(),
+      project_path: project_path.clone(),
+      command: command.clone(),
+      args: request.args.clone(),
+      status: AgentRunStatus::Running,
+      started_at: now_iso(),
+      finished_at: None,
+      exit_code: None,
+      transcript_path: transcript_path.to_string_lossy().to_string(),
+    };
+    transcript.append(serde_json::json!({
+      "at": info.started_at,
+      "event": "started",
+      "command": command,
+      "args": request.args,
+      "cwd": project_path,
+    }));
+
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    self.lock()?.insert(
+      run_id.clone(),
+      RunEntry {
+        info: info.clone(),
+        cancel: Some(cancel_tx),
+      },
+    );
+    self.events.publish(ProjectEvent::AgentRunChanged { run: info.clone() });
+
+    let stdout = child.stdout.take().map(|pipe| {
+      tokio::spawn(forward_lines(
+        pipe,
+        OutputStream::Stdout,
+        self.events.clone(),
+        transcript.clone(),
+        project_path.clone(),
+        run_id.clone(),
+      ))
+    });
+    let stderr = child.stderr.take().map(|pipe| {
+      tokio::spawn(forward_lines(
+        pipe,
+        OutputStream::Stderr,
+        self.events.clone(),
+        transcript.clone(),
+        project_path.clone(),
+        run_id.clone(),
+      ))
+    });
+
+    let timeout = request
+      .timeout_secs
+      .filter(|secs| *secs > 0)
+      .map(Duration::from_secs)
+      .unwrap_or(DEFAULT_RUN_TIMEOUT);
+    let hub = self.clone();
+    tokio::spawn(async move {
+      let (status, exit_code) = tokio::select! {
+        result = child.wait() => match result {
+          Ok(exit) if exit.success() => (AgentRunStatus::Succeeded, exit.code()),
+          Ok(exit) => (AgentRunStatus::Failed, exit.code()),
+          Err(err) => {
+            log::warn!("agent run {} wait failed: {}", run_id, err);
+            (AgentRunStatus::Failed, None)
+          }
+        },
+        _ = cancel_rx => {
+          let _ = child.kill().await;
+          (AgentRunStatus::Cancelled, None)
+        }
+        _ = tokio::time::sleep(timeout) => {
+          let _ = child.kill().await;
+          (AgentRunStatus::TimedOut, None)
+        }
+      };
+      // Readers finish once the pipes close, so every line lands before the final record.
+      let mut readers: Vec<_> = [stdout, stderr].into_iter().flatten().collect();
+      let drained = tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, async {
+        for reader in readers.iter_mut() {
+          let _ = reader.await;
+        }
+      })
+      .await;
+      if drained.is_err() {
+        readers.iter().for_each(|reader| reader.abort());
+      }
+      hub.finish(&run_id, status, exit_code, &transcript);
+    });
+    Ok(info)
+  }
+
+  fn finish(&self, run_id: &str, status: AgentRunStatus, exit_code: Option<i32>, transcript: &Transcript) {
+    let finished_at = now_iso();
+    transcript.append(serde_json::json!({
+      "at": finished_at,
+      "event": "finished",
+      "status": status,
+      "exitCode": exit_code,
+    }));
+    let Ok(mut runs) = self.lock() else {
+      return;
+    };
+    let Some(entry) = runs.get_mut(run_id) else {
+      return;
+    };
+    entry.info.status = status;
+    entry.info.exit_code = exit_code;
+    entry.info.finished_at = Some(finished_at);
+    entry.cancel = None;
+    let info = entry.info.clone();
+    prune_finished(&mut runs);
+    self.events.publish(ProjectEvent::AgentRunChanged { run: info });
+  }
+
+  pub(crate) fn cancel(&self, run_id: &str) -> Result<(), AppError> {
+    let mut runs = self.lock()?;
+    let entry = runs.get_mut(run_id).ok_or_else(|| {
+      AppError::not_found(format!("Agent run {} does not exist.", run_id)).with_field("runId")
+    })?;
+    match entry.cancel.take() {
+      Some(cancel) => {
+        let _ = cancel.send(());
+        Ok(())
+      }
+      None => Err(AppError::conflict(format!("Agent run {} has already finished.", run_id))),
+    }
+  }
+
+  /// Runs for one project, newest first.
+  pub(crate) fn list(&self, project_dir: &Path) -> Result<Vec<AgentRunInfo>, AppError> {
+    let project_path = project_dir
+      .canonicalize()
+      .ok()
+      .and_then(|path| project_path_string(&path).ok())
+      .unwrap_or_default();
+    let runs = self.lock()?;
+    let mut infos: Vec<AgentRunInfo> = runs
+      .values()
+      .filter(|entry| entry.info.project_path == project_path)
+      .map(|entry| entry.info.clone())
+      .collect();
+    infos.sort_by(|a, b| b.run_id.cmp(&a.run_id));
+    Ok(infos)
+  }
+
+  /// Whether a run is still working in the project. Its process runs inside the directory,
+  /// so the project must not be renamed under it.
+  pub(crate) fn has_running(&self, project_dir: &Path) -> Result<bool, AppError> {
+    let project_path = project_dir
+      .canonicalize()
+      .ok()
+      .and_then(|path| project_path_string(&path).ok())
+      .unwrap_or_default();
+    let runs = self.lock()?;
+    Ok(runs.values().any(|entry| {
+      entry.info.project_path == project_path && entry.info.status == AgentRunStatus::Running
+    }))
+  }
+
+  /// Moves finished runs, and their transcript paths, to a renamed project directory. Takes
+  /// the resolved paths, since the old directory no longer exists to resolve.
+  pub(crate) fn move_project(&self, from: &Path, to: &Path) -> Result<(), AppError> {
+    let from_path = project_path_string(from)?;
+    let to_path = project_path_string(to)?;
+    let mut runs = self.lock()?;
+    for entry in runs.values_mut() {
+      if entry.info.project_path != from_path {
+        continue;
+      }
+      if let Ok(relative) = Path::new(&entry.info.transcript_path).strip_prefix(from) {
+        entry.info.transcript_path = to.join(relative).to_string_lossy().to_string();
+      }
+      entry.info.project_path = to_path.clone();
+    }
+    Ok(())
+  }
+}
+
+fn prune_finished(runs: &mut HashMap<String, RunEntry>) {
+  let mut finished: Vec<String> = runs
+    .iter()
+    .filter(|(_, entry)| entry.info.status != AgentRunStatus::Running)
+    .map(|(run_id, _)| run_id.clone())
+    .collect();
+  if finished.len() <= FINISHED_RUN_LIMIT {
+    return;
+  }
+  finished.sort();
+  for run_id in &finished[..finished.len() - FINISHED_RUN_LIMIT] {
+    runs.remove(run_id);
+  }
+}
+
+/// First match in an absolute `PATH` entry. Relative entries are skipped, since runs start in
+/// the project directory and must not pick up a program from there.
+fn find_on_path(program: &Path) -> Option<PathBuf> {
+  let path = std::env::var_os("PATH")?;
+  std::env::split_paths(&path)
+    .filter(|dir| dir.is_absolute())
+    .flat_map(|dir| {
+      let candidate = dir.join(program);
+      let exe = candidate.with_extension(std::env::consts::EXE_EXTENSION);
+      [candidate, exe]
+    })
+    .find(|candidate| candidate.is_file())
+}
+
+fn scrubbed_env(pass_env: &[String]) -> Vec<(String, String)> {
+  BASE_ENV
+    .iter()
+    .map(|name| name.to_string())
+    .chain(pass_env.iter().map(|name| name.trim().to_string()))
+    .filter(|name| !name.is_empty())
+    .filter_map(|name| std::env::var(&name).ok().map(|value| (name, value)))
+    .collect()
+}
+
+async fn forward_lines<R: AsyncRead + Unpin>(
+  pipe: R,
+  stream: OutputStream,
+  events: EventHub,
+  transcript: Transcript,
+  project_path: String,
+  run_id: String,
+) {
+  let mut lines = BufReader::new(pipe).lines();
+  loop {
+    match lines.next_line().await {
+      Ok(Some(line)) => {
+        transcript.append(serde_json::json!({ "at": now_iso(), "stream": stream, "line": line }));
+        events.publish(ProjectEvent::AgentRunOutput {
+          project_path: project_path.clone(),
+          run_id: run_id.clone(),
+          stream,
+          line,
+        });
+      }
+      Ok(None) => break,
+      Err(err) => {
+        log::warn!("agent run {} output read failed: {}", run_id, err);
+        break;
+      }
+    }
+  }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use super::*;
+  use std::os::unix::fs::PermissionsExt;
+
+  struct Fixture {
+    _workspace: tempfile::TempDir,
+    project_dir: PathBuf,
+    runner: AgentRunnerHub,
+    events: EventHub,
+  }
+
+  /// A project and a runner whose only CLI, `stub`, is a shell script with `body`.
+  fn fixture(body: &str) -> Fixture {
+    let workspace = tempfile::tempdir().unwrap();
+    let project_dir = crate::test_project(workspace.path());
+    let script = workspace.path().join("stub.sh");
+    fs::write(&script, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let events = EventHub::default();
+    let clis = HashMap::from([("stub".to_string(), script)]);
+    Fixture {
+      _workspace: workspace,
+      project_dir,
+      runner: AgentRunnerHub::with_clis(events.clone(), clis),
+      events,
+    }
+  }
+
+  fn request(command: &str, timeout_secs: Option<u64>) -> AgentRunRequest {
+    AgentRunRequest {
+      command: command.to_string(),
+      args: Vec::new(),
+      timeout_secs,
+      pass_env: Vec::new(),
+    }
+  }
+
+  /// Waits for the run's final status event, collecting output lines on the way.
+  async fn finish(
+    events: &mut tokio::sync::broadcast::Receiver<ProjectEvent>,
+  ) -> (AgentRunInfo, Vec<String>) {
+    let mut lines = Vec::new();
+    loop {
+      let event = tokio::time::timeout(Duration::from_secs(10), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+      match event {
+        ProjectEvent::AgentRunOutput { line, .. } => lines.push(line),
+        ProjectEvent::AgentRunChanged { run } if run.status != AgentRunStatus::Running => return (run, lines),
+        _ => {}
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn streams_output_into_events_and_the_transcript() {
+    let fixture = fixture("echo out-line\necho err-line >&2");
+    let mut events = fixture.events.subscribe();
+    fixture.runner.start(&fixture.project_dir, request("stub", None)).unwrap();
+
+    let (run, mut lines) = finish(&mut events).await;
+    assert_eq!(run.status, AgentRunStatus::Succeeded);
+    lines.sort();
+    assert_eq!(lines, ["err-line", "out-line"]);
+    let transcript = fs::read_to_string(&run.transcript_path).unwrap();
+    assert!(transcript.contains("\"started\""));
+    assert!(transcript.contains("out-line"));
+    assert!(transcript.contains("err-line"));
+    assert!(transcript.contains("\"finished\""));
+  }
+
+  #[tokio::test]
+  async fn times_out_and_cancels_long_runs() {
+    let fixture = fixture("sleep 30");
+    let mut events = fixture.events.subscribe();
+    fixture.runner.start(&fixture.project_dir, request("stub", Some(1))).unwrap();
+    let (run, _) = finish(&mut events).await;
+    assert_eq!(run.status, AgentRunStatus::TimedOut);
+
+    let run = fixture.runner.start(&fixture.project_dir, request("stub", None)).unwrap();
+    fixture.runner.cancel(&run.run_id).unwrap();
+    let (run, _) = finish(&mut events).await;
+    assert_eq!(run.status, AgentRunStatus::Cancelled);
+    assert!(fs::read_to_string(&run.transcript_path).unwrap().contains("\"cancelled\""));
+  }
+
+  #[tokio::test]
+  async fn refuses_commands_outside_the_allow_list() {
+    let fixture = fixture("true");
+    for command in ["sh", "/bin/sh", "./stub.sh"] {
+      let err = fixture.runner.start(&fixture.project_dir, request(command, None)).unwrap_err();
+      assert_eq!(err.code(), crate::error::ErrorCode::PermissionDenied);
+    }
+  }
+}
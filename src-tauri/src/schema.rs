@@ -0,0 +1,198 @@
+//! JSON Schemas for the on-disk project format, derived from the serde types that read it, and
+//! a check that reports where a file departs from them.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use ts_rs::TS;
+
+use crate::error::AppError;
+use crate::{
+  write_json_file, PageDoc, ProjectMetadata, SiteDoc, SitemapDoc, PAGES_DIR, PROJECT_META_FILE,
+  SITEMAP_FILE, SITE_FILE,
+};
+
+/// Directory inside a project that holds the exported schemas.
+pub(crate) const SCHEMAS_DIR: &str = "schemas";
+
+const PROJECT_SCHEMA: &str = "project.schema.json";
+const SITE_SCHEMA: &str = "site.schema.json";
+const SITEMAP_SCHEMA: &str = "sitemap.schema.json";
+const PAGE_SCHEMA: &str = "page.schema.json";
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SchemaViolation {
+  /// JSON pointer into the file, `""` for the whole document.
+  pub(crate) pointer: String,
+  pub(crate) message: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileValidation {
+  /// Path relative to the project, with `/` separators.
+  pub(crate) file: String,
+  pub(crate) schema: String,
+  pub(crate) violations: Vec<SchemaViolation>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProjectFilesReport {
+  pub(crate) valid: bool,
+  pub(crate) files: Vec<FileValidation>,
+}
+
+fn schema_of<T: JsonSchema>() -> Value {
+  serde_json::to_value(schemars::schema_for!(T)).unwrap_or(Value::Bool(true))
+}
+
+/// Schemas keyed by file name, e.g. `page.schema.json`.
+pub(crate) fn project_schemas() -> BTreeMap<String, Value> {
+  BTreeMap::from([
+    (PROJECT_SCHEMA.to_string(), schema_of::<ProjectMetadata>()),
+    (SITE_SCHEMA.to_string(), schema_of::<SiteDoc>()),
+    (SITEMAP_SCHEMA.to_string(), schema_of::<SitemapDoc>()),
+    (PAGE_SCHEMA.to_string(), schema_of::<PageDoc>()),
+  ])
+}
+
+/// Writes every schema into `dir`, creating it when needed.
+pub(crate) fn write_schemas(dir: &Path) -> Result<(), AppError> {
+  fs::create_dir_all(dir).map_err(|err| AppError::fs("Failed creating", dir, err))?;
+  for (name, schema) in project_schemas() {
+    write_json_file(&dir.join(name), &schema)?;
+  }
+  Ok(())
+}
+
+/// Checks `project.json`, `site.json`, `sitemap.json` and every `pages/*.json` against its
+/// schema. Files the studio would regenerate on load (site, sitemap) are skipped when missing.
+pub(crate) fn validate_project_files(project_dir: &Path) -> Result<ProjectFilesReport, AppError> {
+  if !project_dir.join(PROJECT_META_FILE).is_file() {
+    return Err(AppError::project_not_found(project_dir));
+  }
+  let validators = project_schemas()
+    .iter()
+    .map(|(name, schema)| Ok((name.clone(), compile(name, schema)?)))
+    .collect::<Result<BTreeMap<_, _>, AppError>>()?;
+  let mut targets = vec![
+    (PROJECT_META_FILE.to_string(), PROJECT_SCHEMA),
+    (SITE_FILE.to_string(), SITE_SCHEMA),
+    (SITEMAP_FILE.to_string(), SITEMAP_SCHEMA),
+  ];
+  let pages_dir = project_dir.join(PAGES_DIR);
+  if pages_dir.is_dir() {
+    let entries =
+      fs::read_dir(&pages_dir).map_err(|err| AppError::fs("Failed reading pages dir", &pages_dir, err))?;
+    let mut pages = entries
+      .filter_map(|entry| entry.ok())
+      .map(|entry| entry.file_name().to_string_lossy().to_string())
+      .filter(|name| name.ends_with(".json"))
+      .collect::<Vec<_>>();
+    pages.sort();
+    targets.extend(pages.into_iter().map(|name| (format!("{}/{}", PAGES_DIR, name), PAGE_SCHEMA)));
+  }
+
+  let mut files = Vec::new();
+  for (file, schema_name) in targets {
+    let path = project_dir.join(&file);
+    if !path.is_file() {
+      continue;
+    }
+    let content = fs::read_to_string(&path).map_err(|err| AppError::fs("Failed reading", &path, err))?;
+    let violations = match serde_json::from_str::<Value>(&content) {
+      Ok(value) => validate(&validators[schema_name], &value),
+      Err(err) => vec![SchemaViolation {
+        pointer: String::new(),
+        message: format!("Invalid JSON: {}", err),
+      }],
+    };
+    files.push(FileValidation {
+      file,
+      schema: schema_name.to_string(),
+      violations,
+    });
+  }
+  Ok(ProjectFilesReport {
+    valid: files.iter().all(|file| file.violations.is_empty()),
+    files,
+  })
+}
+
+/// The first violation in `report`, described for an error message.
+pub(crate) fn first_violation(report: &ProjectFilesReport) -> Option<String> {
+  report.files.iter().find_map(|file| {
+    file.violations.first().map(|violation| {
+      format!(
+        "{} does not match its schema at {}: {}",
+        file.file,
+        if violation.pointer.is_empty() { "/" } else { &violation.pointer },
+        violation.message
+      )
+    })
+  })
+}
+
+/// Compiles a schema from `project_schemas`; they are generated, so failing is a bug.
+fn compile(name: &str, schema: &Value) -> Result<jsonschema::Validator, AppError> {
+  jsonschema::validator_for(schema)
+    .map_err(|err| AppError::internal(format!("Schema {} does not compile: {}", name, err)))
+}
+
+/// Every place `value` departs from the schema, located by JSON pointer.
+pub(crate) fn validate(validator: &jsonschema::Validator, value: &Value) -> Vec<SchemaViolation> {
+  validator
+    .iter_errors(value)
+    .map(|error| SchemaViolation {
+      pointer: error.instance_path().as_str().to_string(),
+      message: error.to_string(),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn violations(schema: Value, value: Value) -> Vec<SchemaViolation> {
+    validate(&compile("test", &schema).unwrap(), &value)
+  }
+
+  #[test]
+  fn a_new_project_matches_its_schemas() {
+    let workspace = tempfile::tempdir().unwrap();
+    let project_dir = crate::test_project(workspace.path());
+    let report = validate_project_files(&project_dir).unwrap();
+    assert!(report.valid, "{:?}", first_violation(&report));
+  }
+
+  #[test]
+  fn one_of_rejects_values_matching_several_branches() {
+    let schema = serde_json::json!({
+      "$schema": "http://json-schema.org/draft-07/schema#",
+      "oneOf": [{ "type": "integer" }, { "type": "number" }]
+    });
+    assert!(violations(schema.clone(), serde_json::json!(1.5)).is_empty());
+    assert_eq!(violations(schema, serde_json::json!(1)).len(), 1);
+  }
+
+  #[test]
+  fn pointers_locate_nested_violations() {
+    let schema = serde_json::json!({
+      "$schema": "http://json-schema.org/draft-07/schema#",
+      "type": "object",
+      "properties": {
+        "a/b": { "type": "array", "items": { "type": "string", "maxLength": 2 } }
+      }
+    });
+    let found = violations(schema, serde_json::json!({ "a/b": ["ok", "too long"] }));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].pointer, "/a~1b/1");
+  }
+}
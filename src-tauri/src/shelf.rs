@@ -0,0 +1,435 @@
+//! Archive and trash areas of a workspace. Both are dot-directories, and `list_projects` only
+//! looks at top-level `*.manifold` directories, so shelved projects drop out of the listing
+//! until restored. Only trashed projects can be deleted for good.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ts_rs::TS;
+
+use crate::activity::ActivityOperation;
+use crate::archive::free_project_slug;
+use crate::error::AppError;
+use crate::events::{ChangeSource, ProjectEvent};
+use crate::{
+  move_project_dir, normalize_slug, now_iso, project_path_string, project_record_from_dir,
+  read_json_file, read_project_metadata, touch_project, write_json_file, LiveServices, ProjectRecord,
+  PROJECT_META_FILE,
+};
+
+pub(crate) const ARCHIVE_DIR: &str = ".archive";
+pub(crate) const TRASH_DIR: &str = ".trash";
+/// Written into a shelved project. Dot-files stay out of exports and copies.
+const SHELF_MARKER_FILE: &str = ".shelved.json";
+/// A trashed project is renamed to this hidden prefix before removal, so a removal that fails
+/// half-way leaves nothing that lists as a project.
+const DELETING_PREFIX: &str = ".deleting-";
+/// Hex digits of the SHA-256 used as the deletion confirmation token.
+const TOKEN_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS, clap::ValueEnum)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ProjectShelf {
+  Archive,
+  Trash,
+}
+
+impl ProjectShelf {
+  fn dir_name(self) -> &'static str {
+    match self {
+      ProjectShelf::Archive => ARCHIVE_DIR,
+      ProjectShelf::Trash => TRASH_DIR,
+    }
+  }
+
+  fn label(self) -> &'static str {
+    match self {
+      ProjectShelf::Archive => "archived",
+      ProjectShelf::Trash => "in the trash",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShelfMarker {
+  shelf: ProjectShelf,
+  shelved_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ShelvedProject {
+  pub(crate) project: ProjectRecord,
+  pub(crate) shelf: ProjectShelf,
+  pub(crate) shelved_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeletionRequest {
+  pub(crate) project: ProjectRecord,
+  /// Pass to `delete_project`. It changes whenever the project does, so a confirmation given
+  /// for an older state is refused.
+  pub(crate) confirmation_token: String,
+  /// Everything under the project directory, exports and transcripts included.
+  #[ts(type = "number")]
+  pub(crate) files: u64,
+  #[ts(type = "number")]
+  pub(crate) bytes: u64,
+}
+
+/// The workspace a project belongs to, and the shelf it is on, if any.
+fn locate(project_dir: &Path) -> Result<(PathBuf, Option<ProjectShelf>), AppError> {
+  if !project_dir.join(PROJECT_META_FILE).is_file() {
+    return Err(AppError::project_not_found(project_dir));
+  }
+  let parent = project_dir
+    .parent()
+    .ok_or_else(|| AppError::invalid("Project has no parent directory").with_path(project_dir))?;
+  let shelf = [ProjectShelf::Archive, ProjectShelf::Trash]
+    .into_iter()
+    .find(|shelf| parent.file_name().is_some_and(|name| name == shelf.dir_name()));
+  let workspace = match shelf {
+    Some(_) => parent
+      .parent()
+      .ok_or_else(|| AppError::invalid("Shelf has no parent directory").with_path(parent))?,
+    None => parent,
+  };
+  Ok((workspace.to_path_buf(), shelf))
+}
+
+/// Slug the project returns under; the directory name's when the metadata has none.
+fn project_slug(project_dir: &Path) -> Result<String, AppError> {
+  let slug = normalize_slug(&read_project_metadata(project_dir)?.slug);
+  if !slug.is_empty() {
+    return Ok(slug);
+  }
+  let dir_name = project_dir
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_default();
+  Ok(normalize_slug(dir_name.trim_end_matches(".manifold")))
+}
+
+fn read_marker(project_dir: &Path) -> Option<ShelfMarker> {
+  read_json_file::<ShelfMarker>(&project_dir.join(SHELF_MARKER_FILE)).ok()
+}
+
+fn shelved_project(project_dir: &Path, shelf: ProjectShelf) -> Result<ShelvedProject, AppError> {
+  Ok(ShelvedProject {
+    project: project_record_from_dir(project_dir)?,
+    shelf,
+    // Projects moved in by hand have no marker; they sort last.
+    shelved_at: read_marker(project_dir)
+      .map(|marker| marker.shelved_at)
+      .unwrap_or_default(),
+  })
+}
+
+/// Moves a project into the archive or the trash, under a free `<slug>.manifold` name there.
+/// Moving between the two is allowed.
+pub(crate) fn shelve_project(
+  services: &LiveServices,
+  project_dir: &Path,
+  shelf: ProjectShelf,
+  source: ChangeSource,
+) -> Result<ShelvedProject, AppError> {
+  let (workspace, current) = locate(project_dir)?;
+  if current == Some(shelf) {
+    return Err(AppError::conflict(format!("Project is already {}.", shelf.label())).with_path(project_dir));
+  }
+  let shelf_dir = workspace.join(shelf.dir_name());
+  fs::create_dir_all(&shelf_dir).map_err(|err| AppError::fs("Failed creating", &shelf_dir, err))?;
+  let slug = free_project_slug(&shelf_dir, &project_slug(project_dir)?);
+  let target = shelf_dir.join(format!("{}.manifold", slug));
+
+  let marker_path = project_dir.join(SHELF_MARKER_FILE);
+  let previous = read_marker(project_dir);
+  let marker = ShelfMarker {
+    shelf,
+    shelved_at: now_iso(),
+  };
+  write_json_file(&marker_path, &marker)?;
+  if let Err(err) = move_project_dir(services, project_dir, &target) {
+    let _ = match previous {
+      Some(previous) => write_json_file(&marker_path, &previous),
+      None => fs::remove_file(&marker_path).map_err(|err| AppError::fs("Failed removing", &marker_path, err)),
+    };
+    return Err(err);
+  }
+
+  let operation = match shelf {
+    ProjectShelf::Archive => ActivityOperation::Archive,
+    ProjectShelf::Trash => ActivityOperation::Trash,
+  };
+  touch_project(&target, operation, Vec::new(), source)?;
+  let shelved = shelved_project(&target, shelf)?;
+  services.events.publish(ProjectEvent::ProjectShelved {
+    project_path: project_path_string(project_dir)?,
+    project: shelved.project.clone(),
+    shelf,
+    source,
+  });
+  Ok(shelved)
+}
+
+/// Moves an archived or trashed project back to `<workspace>/<slug>.manifold`. Refused when
+/// that name has been taken since; rename the shelved project first.
+pub(crate) fn restore_project(
+  services: &LiveServices,
+  project_dir: &Path,
+  source: ChangeSource,
+) -> Result<ProjectRecord, AppError> {
+  let (workspace, current) = locate(project_dir)?;
+  if current.is_none() {
+    return Err(AppError::conflict("Project is not archived or in the trash.").with_path(project_dir));
+  }
+  let target = workspace.join(format!("{}.manifold", project_slug(project_dir)?));
+  move_project_dir(services, project_dir, &target)?;
+  let marker_path = target.join(SHELF_MARKER_FILE);
+  if let Err(err) = fs::remove_file(&marker_path) {
+    if err.kind() != std::io::ErrorKind::NotFound {
+      log::warn!("failed removing {}: {}", marker_path.display(), err);
+    }
+  }
+  touch_project(&target, ActivityOperation::Restore, Vec::new(), source)?;
+
+  let record = project_record_from_dir(&target)?;
+  services.events.publish(ProjectEvent::ProjectRestored {
+    project_path: project_path_string(project_dir)?,
+    project: record.clone(),
+    source,
+  });
+  Ok(record)
+}
+
+/// Shelved projects in a workspace, most recently shelved first.
+pub(crate) fn list_shelved_projects(
+  workspace: &Path,
+  shelf: Option<ProjectShelf>,
+) -> Result<Vec<ShelvedProject>, AppError> {
+  let shelves = match shelf {
+    Some(shelf) => vec![shelf],
+    None => vec![ProjectShelf::Archive, ProjectShelf::Trash],
+  };
+  let mut projects = Vec::new();
+  for shelf in shelves {
+    let shelf_dir = workspace.join(shelf.dir_name());
+    if !shelf_dir.is_dir() {
+      continue;
+    }
+    let entries = fs::read_dir(&shelf_dir).map_err(|err| AppError::fs("Failed reading", &shelf_dir, err))?;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+      let path = entry.path();
+      let name = entry.file_name().to_string_lossy().to_string();
+      if name.starts_with('.') || !name.ends_with(".manifold") || !path.join(PROJECT_META_FILE).is_file() {
+        continue;
+      }
+      if let Ok(project) = shelved_project(&path, shelf) {
+        projects.push(project);
+      }
+    }
+  }
+  projects.sort_by(|a, b| b.shelved_at.cmp(&a.shelved_at));
+  Ok(projects)
+}
+
+fn require_trashed(project_dir: &Path) -> Result<(), AppError> {
+  let (_, current) = locate(project_dir)?;
+  if current != Some(ProjectShelf::Trash) {
+    return Err(
+      AppError::conflict("Only projects in the trash can be deleted; move it to the trash first.")
+        .with_path(project_dir),
+    );
+  }
+  Ok(())
+}
+
+/// Derived from the project's path, last update and trash time rather than stored, so it
+/// survives restarts and works from the CLI. It guards against mistakes, not attackers.
+fn confirmation_token(project_dir: &Path) -> Result<String, AppError> {
+  let resolved = project_dir
+    .canonicalize()
+    .map_err(|err| AppError::fs("Failed resolving project", project_dir, err))?;
+  let metadata = read_project_metadata(project_dir)?;
+  let shelved_at = read_marker(project_dir)
+    .map(|marker| marker.shelved_at)
+    .unwrap_or_default();
+  let mut hasher = Sha256::new();
+  hasher.update(resolved.to_string_lossy().as_bytes());
+  hasher.update(b"\n");
+  hasher.update(metadata.updated_at.as_bytes());
+  hasher.update(b"\n");
+  hasher.update(shelved_at.as_bytes());
+  let digest = hasher.finalize();
+  Ok(digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>()[..TOKEN_LEN].to_string())
+}
+
+/// File count and total size, without following symlinks.
+fn dir_usage(dir: &Path) -> Result<(u64, u64), AppError> {
+  let mut files = 0;
+  let mut bytes = 0;
+  let entries = fs::read_dir(dir).map_err(|err| AppError::fs("Failed reading", dir, err))?;
+  for entry in entries.filter_map(|entry| entry.ok()) {
+    let Ok(file_type) = entry.file_type() else {
+      continue;
+    };
+    if file_type.is_dir() {
+      let (dir_files, dir_bytes) = dir_usage(&entry.path())?;
+      files += dir_files;
+      bytes += dir_bytes;
+    } else {
+      files += 1;
+      bytes += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    }
+  }
+  Ok((files, bytes))
+}
+
+/// First step of a permanent delete: describes what would go and issues the token
+/// `delete_project` needs.
+pub(crate) fn request_deletion(project_dir: &Path) -> Result<DeletionRequest, AppError> {
+  require_trashed(project_dir)?;
+  let (files, bytes) = dir_usage(project_dir)?;
+  Ok(DeletionRequest {
+    project: project_record_from_dir(project_dir)?,
+    confirmation_token: confirmation_token(project_dir)?,
+    files,
+    bytes,
+  })
+}
+
+/// Removes a trashed project from disk for good.
+pub(crate) fn delete_project(
+  services: &LiveServices,
+  project_dir: &Path,
+  token: &str,
+  source: ChangeSource,
+) -> Result<(), AppError> {
+  require_trashed(project_dir)?;
+  if token.trim() != confirmation_token(project_dir)? {
+    return Err(
+      AppError::precondition_failed("Confirmation token does not match; request deletion again.")
+        .with_field("confirmationToken"),
+    );
+  }
+  if services.runner.has_running(project_dir)? {
+    return Err(AppError::conflict(
+      "An agent run is working in this project; cancel it or wait for it to finish first.",
+    ));
+  }
+  let trash_dir = project_dir
+    .parent()
+    .ok_or_else(|| AppError::invalid("Project has no parent directory").with_path(project_dir))?;
+  let dir_name = project_dir
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_default();
+  let doomed = trash_dir.join(format!("{}{}", DELETING_PREFIX, dir_name));
+  if doomed.exists() {
+    // Left over from a removal that failed part-way.
+    fs::remove_dir_all(&doomed).map_err(|err| AppError::fs("Failed deleting project", &doomed, err))?;
+  }
+  fs::rename(project_dir, &doomed).map_err(|err| AppError::fs("Failed deleting project", project_dir, err))?;
+  fs::remove_dir_all(&doomed).map_err(|err| AppError::fs("Failed deleting project", &doomed, err))?;
+
+  services.events.publish(ProjectEvent::ProjectDeleted {
+    project_path: project_path_string(project_dir)?,
+    source,
+  });
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::error::ErrorCode;
+
+  fn trashed(services: &LiveServices, workspace: &Path) -> PathBuf {
+    let project_dir = crate::test_project(workspace);
+    let shelved = shelve_project(services, &project_dir, ProjectShelf::Trash, ChangeSource::Desktop).unwrap();
+    PathBuf::from(shelved.project.path)
+  }
+
+  #[test]
+  fn archived_projects_restore_to_their_slug() {
+    let workspace = tempfile::tempdir().unwrap();
+    let services = LiveServices::new();
+    let project_dir = crate::test_project(workspace.path());
+
+    let shelved = shelve_project(&services, &project_dir, ProjectShelf::Archive, ChangeSource::Desktop).unwrap();
+    let archived = PathBuf::from(&shelved.project.path);
+    assert_eq!(archived, workspace.path().join(ARCHIVE_DIR).join("site.manifold"));
+    assert!(!project_dir.exists());
+    let listed = list_shelved_projects(workspace.path(), None).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].shelf, ProjectShelf::Archive);
+
+    let restored = restore_project(&services, &archived, ChangeSource::Desktop).unwrap();
+    assert_eq!(PathBuf::from(restored.path), project_dir);
+    assert!(!project_dir.join(SHELF_MARKER_FILE).exists());
+    assert!(list_shelved_projects(workspace.path(), None).unwrap().is_empty());
+    let operations: Vec<_> = crate::activity::read_activity(&project_dir, 10)
+      .unwrap()
+      .into_iter()
+      .map(|entry| entry.operation)
+      .collect();
+    assert_eq!(operations, [ActivityOperation::Restore, ActivityOperation::Archive]);
+  }
+
+  #[test]
+  fn restore_refuses_a_taken_slug() {
+    let workspace = tempfile::tempdir().unwrap();
+    let services = LiveServices::new();
+    let trashed_dir = trashed(&services, workspace.path());
+    crate::test_project(workspace.path());
+
+    let err = restore_project(&services, &trashed_dir, ChangeSource::Desktop).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Conflict);
+    assert!(trashed_dir.join(PROJECT_META_FILE).is_file());
+  }
+
+  #[test]
+  fn only_trashed_projects_can_be_deleted() {
+    let workspace = tempfile::tempdir().unwrap();
+    let services = LiveServices::new();
+    let project_dir = crate::test_project(workspace.path());
+
+    assert_eq!(request_deletion(&project_dir).unwrap_err().code(), ErrorCode::Conflict);
+    let token = confirmation_token(&project_dir).unwrap();
+    let err = delete_project(&services, &project_dir, &token, ChangeSource::Desktop).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Conflict);
+    assert!(project_dir.join(PROJECT_META_FILE).is_file());
+  }
+
+  #[test]
+  fn a_token_from_before_a_change_is_refused() {
+    let workspace = tempfile::tempdir().unwrap();
+    let services = LiveServices::new();
+    let trashed_dir = trashed(&services, workspace.path());
+    let request = request_deletion(&trashed_dir).unwrap();
+
+    touch_project(&trashed_dir, ActivityOperation::UpdateDetails, Vec::new(), ChangeSource::Desktop).unwrap();
+    let err = delete_project(&services, &trashed_dir, &request.confirmation_token, ChangeSource::Desktop)
+      .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::PreconditionFailed);
+    assert!(trashed_dir.join(PROJECT_META_FILE).is_file());
+  }
+
+  #[test]
+  fn a_fresh_token_deletes_the_project() {
+    let workspace = tempfile::tempdir().unwrap();
+    let services = LiveServices::new();
+    let trashed_dir = trashed(&services, workspace.path());
+    let request = request_deletion(&trashed_dir).unwrap();
+    assert!(request.files > 0);
+
+    delete_project(&services, &trashed_dir, &request.confirmation_token, ChangeSource::Desktop).unwrap();
+    assert!(!trashed_dir.exists());
+    assert!(list_shelved_projects(workspace.path(), None).unwrap().is_empty());
+  }
+}
@@ -0,0 +1,358 @@
+//! Starting points for new projects: built-in templates plus `.manifold` skeletons in the
+//! workspace's `templates/` folder. Text in a template may use `{{projectName}}`, `{{siteUrl}}`
+//! and `{{clientName}}`, which are filled in when a project is created from it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use ts_rs::TS;
+
+use crate::agent::default_block_style;
+use crate::archive::project_files;
+use crate::error::AppError;
+use crate::schema::{first_violation, validate_project_files, write_schemas, SCHEMAS_DIR};
+use crate::{
+  canonical_page_id_from_route, default_builder_doc, load_builder_doc, normalize_slug,
+  persist_builder_doc, read_json_file, read_page_docs, renumber_block_ids, write_json_file, BlockDoc,
+  BuilderProjectDoc, PageDoc, PageSeoDoc, ProjectColor, ProjectMetadata, SiteDoc, SitemapDoc, PAGES_DIR,
+  PROJECT_FORMAT_VERSION, PROJECT_META_FILE, SITEMAP_FILE, SITE_FILE,
+};
+
+pub(crate) const TEMPLATES_DIR: &str = "templates";
+/// Optional `{ "name": ..., "description": ... }` inside a workspace template.
+const TEMPLATE_META_FILE: &str = "template.json";
+const BLANK_TEMPLATE: &str = "blank";
+const BUSINESS_TEMPLATE: &str = "business";
+/// Files whose text is searched for placeholders besides JSON, which is rewritten by value.
+const TEXT_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "html", "htm", "css"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TemplateSource {
+  Builtin,
+  /// A skeleton in `<workspace>/templates`; overrides a built-in with the same id.
+  Workspace,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProjectTemplate {
+  /// Passed as `template` to `create_project`.
+  pub(crate) id: String,
+  pub(crate) name: String,
+  pub(crate) description: String,
+  pub(crate) source: TemplateSource,
+  /// Page titles in sitemap order, before placeholder substitution.
+  pub(crate) pages: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TemplateMeta {
+  #[serde(default)]
+  name: Option<String>,
+  #[serde(default)]
+  description: Option<String>,
+}
+
+/// Values substituted for the template placeholders.
+pub(crate) struct Placeholders<'a> {
+  pub(crate) project_name: &'a str,
+  pub(crate) site_url: &'a str,
+  pub(crate) client_name: &'a str,
+}
+
+impl Placeholders<'_> {
+  fn apply(&self, text: &str) -> String {
+    text
+      .replace("{{projectName}}", self.project_name)
+      .replace("{{siteUrl}}", self.site_url)
+      .replace("{{clientName}}", self.client_name)
+  }
+
+  /// Rewrites string values only, so a name containing quotes cannot break the JSON.
+  fn apply_json(&self, value: &mut Value) {
+    match value {
+      Value::String(text) => *text = self.apply(text),
+      Value::Array(items) => items.iter_mut().for_each(|item| self.apply_json(item)),
+      Value::Object(map) => map.values_mut().for_each(|item| self.apply_json(item)),
+      _ => {}
+    }
+  }
+}
+
+fn block(block_type: &str, props: Value) -> BlockDoc {
+  BlockDoc {
+    id: String::new(),
+    block_type: block_type.to_string(),
+    props,
+    visibility: "visible".to_string(),
+    style_overrides: default_block_style(),
+  }
+}
+
+fn page(route: &str, title: &str, seo_title: &str, description: &str, blocks: Vec<BlockDoc>) -> PageDoc {
+  PageDoc {
+    id: canonical_page_id_from_route(route),
+    title: title.to_string(),
+    route: route.to_string(),
+    seo: PageSeoDoc {
+      title: seo_title.to_string(),
+      description: description.to_string(),
+    },
+    blocks,
+  }
+}
+
+/// Built-in templates, still holding their placeholders.
+fn builtin_doc(id: &str) -> Option<BuilderProjectDoc> {
+  match id {
+    BLANK_TEMPLATE => Some(default_builder_doc(&ProjectMetadata {
+      name: "{{projectName}}".to_string(),
+      slug: String::new(),
+      site_url: "{{siteUrl}}".to_string(),
+      created_at: String::new(),
+      updated_at: String::new(),
+      revision: 0,
+      tags: Vec::new(),
+      client_name: String::new(),
+      pinned: false,
+      color: ProjectColor::None,
+      format_version: PROJECT_FORMAT_VERSION,
+    })),
+    BUSINESS_TEMPLATE => {
+      let pages = vec![
+        page(
+          "/",
+          "Home",
+          "{{projectName}}",
+          "{{clientName}}: what we do and how to reach us.",
+          vec![
+            block(
+              "hero",
+              json!({
+                "eyebrow": "{{clientName}}",
+                "headline": "Welcome to {{projectName}}",
+                "ctaLabel": "Get in touch",
+                "ctaUrl": "/contact",
+              }),
+            ),
+            block("services_list", json!({ "sectionTitle": "What we do" })),
+            block(
+              "cta",
+              json!({ "copy": "Ready to start a project?", "label": "Contact us", "link": "/contact" }),
+            ),
+          ],
+        ),
+        page(
+          "/about",
+          "About",
+          "About | {{projectName}}",
+          "The story and team behind {{clientName}}.",
+          vec![block("image_text", json!({ "sectionTitle": "About {{clientName}}" }))],
+        ),
+        page(
+          "/services",
+          "Services",
+          "Services | {{projectName}}",
+          "Services offered by {{clientName}}.",
+          vec![
+            block("services_list", json!({ "sectionTitle": "Services" })),
+            block("faq", json!({ "sectionTitle": "Questions" })),
+          ],
+        ),
+        page(
+          "/contact",
+          "Contact",
+          "Contact | {{projectName}}",
+          "Get in touch with {{clientName}}.",
+          vec![block(
+            "contact_section",
+            json!({ "sectionTitle": "Contact {{clientName}}", "ctaLabel": "Email us" }),
+          )],
+        ),
+      ];
+      let mut doc = BuilderProjectDoc {
+        site: SiteDoc {
+          site_name: "{{projectName}}".to_string(),
+          base_url: "{{siteUrl}}".to_string(),
+        },
+        sitemap: SitemapDoc {
+          page_order: pages.iter().map(|page| page.id.clone()).collect(),
+          root_page_id: "home".to_string(),
+        },
+        selected_page_id: "home".to_string(),
+        pages,
+      };
+      renumber_block_ids(&mut doc);
+      Some(doc)
+    }
+    _ => None,
+  }
+}
+
+fn builtin_template(id: &str, name: &str, description: &str) -> Option<ProjectTemplate> {
+  let doc = builtin_doc(id)?;
+  Some(ProjectTemplate {
+    id: id.to_string(),
+    name: name.to_string(),
+    description: description.to_string(),
+    source: TemplateSource::Builtin,
+    pages: doc.pages.into_iter().map(|page| page.title).collect(),
+  })
+}
+
+/// `<workspace>/templates/<id>.manifold`, when `id` is a plain slug and the directory exists.
+fn workspace_template_dir(workspace: &Path, id: &str) -> Option<PathBuf> {
+  if id.is_empty() || normalize_slug(id) != id {
+    return None;
+  }
+  let dir = workspace.join(TEMPLATES_DIR).join(format!("{}.manifold", id));
+  dir.is_dir().then_some(dir)
+}
+
+/// Page titles in sitemap order, read without normalizing (which would write to the template).
+fn workspace_template_pages(dir: &Path) -> Vec<String> {
+  let pages_dir = dir.join(PAGES_DIR);
+  let Ok(mut pages) = read_page_docs(&pages_dir) else {
+    return Vec::new();
+  };
+  let order = read_json_file::<SitemapDoc>(&dir.join(SITEMAP_FILE))
+    .map(|sitemap| sitemap.page_order)
+    .unwrap_or_default();
+  let mut titles = order
+    .iter()
+    .filter_map(|page_id| pages.remove(page_id))
+    .map(|page| page.title)
+    .collect::<Vec<_>>();
+  let mut remaining = pages.into_values().map(|page| page.title).collect::<Vec<_>>();
+  remaining.sort();
+  titles.extend(remaining);
+  titles
+}
+
+fn workspace_template(dir: &Path, id: &str) -> ProjectTemplate {
+  let meta = read_json_file::<TemplateMeta>(&dir.join(TEMPLATE_META_FILE)).unwrap_or_default();
+  ProjectTemplate {
+    id: id.to_string(),
+    name: meta.name.unwrap_or_else(|| id.to_string()),
+    description: meta.description.unwrap_or_default(),
+    source: TemplateSource::Workspace,
+    pages: workspace_template_pages(dir),
+  }
+}
+
+/// Built-in templates followed by the workspace's, sorted by id.
+pub(crate) fn list_templates(workspace: &Path) -> Result<Vec<ProjectTemplate>, AppError> {
+  let mut workspace_templates = Vec::new();
+  let templates_dir = workspace.join(TEMPLATES_DIR);
+  if templates_dir.is_dir() {
+    let entries =
+      fs::read_dir(&templates_dir).map_err(|err| AppError::fs("Failed reading", &templates_dir, err))?;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+      let name = entry.file_name().to_string_lossy().to_string();
+      let Some(id) = name.strip_suffix(".manifold") else {
+        continue;
+      };
+      if let Some(dir) = workspace_template_dir(workspace, id) {
+        workspace_templates.push(workspace_template(&dir, id));
+      }
+    }
+  }
+  workspace_templates.sort_by(|a, b| a.id.cmp(&b.id));
+
+  let builtins = [
+    builtin_template(BLANK_TEMPLATE, "Blank", "A single empty Home page."),
+    builtin_template(
+      BUSINESS_TEMPLATE,
+      "Small business",
+      "Home, About, Services and Contact pages with starter sections.",
+    ),
+  ];
+  let mut templates = builtins
+    .into_iter()
+    .flatten()
+    .filter(|builtin| !workspace_templates.iter().any(|template| template.id == builtin.id))
+    .collect::<Vec<_>>();
+  templates.extend(workspace_templates);
+  Ok(templates)
+}
+
+/// Fills `project_dir`, whose `project.json` is already written, from a template. Workspace
+/// templates are copied file by file (their own `project.json` is ignored), then checked
+/// against the schemas and normalized like any loaded project.
+pub(crate) fn apply_template(
+  workspace: &Path,
+  template_id: &str,
+  project_dir: &Path,
+  placeholders: &Placeholders,
+) -> Result<(), AppError> {
+  if let Some(template_dir) = workspace_template_dir(workspace, template_id) {
+    copy_template(&template_dir, project_dir, placeholders)?;
+  } else if let Some(doc) = builtin_doc(template_id) {
+    let mut value = serde_json::to_value(&doc)
+      .map_err(|err| AppError::internal(format!("Failed serializing template: {}", err)))?;
+    placeholders.apply_json(&mut value);
+    let doc = serde_json::from_value::<BuilderProjectDoc>(value)
+      .map_err(|err| AppError::internal(format!("Failed reading template: {}", err)))?;
+    persist_builder_doc(project_dir, &doc)?;
+  } else {
+    return Err(AppError::not_found(format!("Template {} not found", template_id)).with_field("template"));
+  }
+
+  write_schemas(&project_dir.join(SCHEMAS_DIR))?;
+  if let Some(violation) = first_violation(&validate_project_files(project_dir)?) {
+    return Err(AppError::invalid(format!("Template {}: {}", template_id, violation)).with_field("template"));
+  }
+  load_builder_doc(project_dir)?;
+  Ok(())
+}
+
+fn copy_template(template_dir: &Path, project_dir: &Path, placeholders: &Placeholders) -> Result<(), AppError> {
+  for (relative, source) in project_files(template_dir)? {
+    if relative == PROJECT_META_FILE {
+      continue;
+    }
+    let target = project_dir.join(&relative);
+    if let Some(parent) = target.parent() {
+      fs::create_dir_all(parent).map_err(|err| AppError::fs("Failed creating", parent, err))?;
+    }
+    let extension = source
+      .extension()
+      .and_then(|extension| extension.to_str())
+      .unwrap_or_default()
+      .to_lowercase();
+    if extension == "json" {
+      let mut value = read_json_file::<Value>(&source)?;
+      placeholders.apply_json(&mut value);
+      write_json_file(&target, &value)?;
+    } else if TEXT_EXTENSIONS.contains(&extension.as_str()) {
+      let text = fs::read_to_string(&source).map_err(|err| AppError::fs("Failed reading", &source, err))?;
+      fs::write(&target, placeholders.apply(&text)).map_err(|err| AppError::fs("Failed writing", &target, err))?;
+    } else {
+      fs::copy(&source, &target).map_err(|err| AppError::fs("Failed copying", &source, err))?;
+    }
+  }
+
+  // A skeleton may hold only pages; loading would replace a project missing either file with
+  // the default document.
+  let site_path = project_dir.join(SITE_FILE);
+  if !site_path.is_file() {
+    let site = SiteDoc {
+      site_name: placeholders.project_name.to_string(),
+      base_url: placeholders.site_url.to_string(),
+    };
+    write_json_file(&site_path, &site)?;
+  }
+  let sitemap_path = project_dir.join(SITEMAP_FILE);
+  if !sitemap_path.is_file() {
+    let sitemap = SitemapDoc {
+      page_order: Vec::new(),
+      root_page_id: String::new(),
+    };
+    write_json_file(&sitemap_path, &sitemap)?;
+  }
+  Ok(())
+}
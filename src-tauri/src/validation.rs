@@ -0,0 +1,251 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::BuilderProjectDoc;
+
+/// Block types shipped in the studio catalog (`src-ui/src/features/builder/catalog.ts`).
+pub(crate) const KNOWN_BLOCK_TYPES: &[&str] = &[
+  "hero",
+  "image_text",
+  "feature_grid",
+  "services_list",
+  "cta",
+  "testimonials",
+  "pricing",
+  "faq",
+  "logo_cloud",
+  "contact_section",
+  "footer",
+  "custom_section",
+];
+
+const SEO_TITLE_MAX: usize = 60;
+const SEO_DESCRIPTION_MAX: usize = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum IssueSeverity {
+  Error,
+  Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProjectIssue {
+  pub(crate) severity: IssueSeverity,
+  pub(crate) code: &'static str,
+  pub(crate) message: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) page_id: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) block_id: Option<String>,
+}
+
+impl ProjectIssue {
+  fn new(severity: IssueSeverity, code: &'static str, message: String) -> Self {
+    Self {
+      severity,
+      code,
+      message,
+      page_id: None,
+      block_id: None,
+    }
+  }
+
+  fn on_page(mut self, page_id: &str) -> Self {
+    self.page_id = Some(page_id.to_string());
+    self
+  }
+
+  fn on_block(mut self, block_id: &str) -> Self {
+    self.block_id = Some(block_id.to_string());
+    self
+  }
+}
+
+/// Structural checks that must pass before export. Issues are ordered by page then block.
+pub(crate) fn validate_builder_doc(doc: &BuilderProjectDoc) -> Vec<ProjectIssue> {
+  let mut issues = Vec::new();
+  let mut routes = HashMap::<&str, &str>::new();
+  let mut block_ids = HashMap::<&str, &str>::new();
+
+  if !doc.pages.iter().any(|page| page.id == doc.sitemap.root_page_id) {
+    issues.push(ProjectIssue::new(
+      IssueSeverity::Error,
+      "missing-root-page",
+      format!("Root page {} does not exist.", doc.sitemap.root_page_id),
+    ));
+  }
+
+  for page in &doc.pages {
+    if page.title.trim().is_empty() {
+      issues.push(
+        ProjectIssue::new(IssueSeverity::Error, "empty-page-title", "Page title is empty.".to_string())
+          .on_page(&page.id),
+      );
+    }
+    if !page.route.starts_with('/') {
+      issues.push(
+        ProjectIssue::new(
+          IssueSeverity::Error,
+          "invalid-route",
+          format!("Route {} must start with /.", page.route),
+        )
+        .on_page(&page.id),
+      );
+    }
+    if let Some(other) = routes.insert(page.route.as_str(), page.id.as_str()) {
+      issues.push(
+        ProjectIssue::new(
+          IssueSeverity::Error,
+          "duplicate-route",
+          format!("Route {} is also used by page {}.", page.route, other),
+        )
+        .on_page(&page.id),
+      );
+    }
+
+    for block in &page.blocks {
+      if let Some(other_page) = block_ids.insert(block.id.as_str(), page.id.as_str()) {
+        issues.push(
+          ProjectIssue::new(
+            IssueSeverity::Error,
+            "duplicate-block-id",
+            format!("Block id {} is also used on page {}.", block.id, other_page),
+          )
+          .on_page(&page.id)
+          .on_block(&block.id),
+        );
+      }
+      if !KNOWN_BLOCK_TYPES.contains(&block.block_type.as_str()) {
+        issues.push(
+          ProjectIssue::new(
+            IssueSeverity::Error,
+            "unknown-block-type",
+            format!("Block type {} is not in the catalog.", block.block_type),
+          )
+          .on_page(&page.id)
+          .on_block(&block.id),
+        );
+      }
+      if block.visibility != "visible" && block.visibility != "hidden" {
+        issues.push(
+          ProjectIssue::new(
+            IssueSeverity::Error,
+            "invalid-visibility",
+            format!("Visibility {} must be visible or hidden.", block.visibility),
+          )
+          .on_page(&page.id)
+          .on_block(&block.id),
+        );
+      }
+      if !block.props.is_object() {
+        issues.push(
+          ProjectIssue::new(
+            IssueSeverity::Error,
+            "invalid-props",
+            "Block props must be an object.".to_string(),
+          )
+          .on_page(&page.id)
+          .on_block(&block.id),
+        );
+      }
+    }
+  }
+
+  let page_ids: HashSet<&str> = doc.pages.iter().map(|page| page.id.as_str()).collect();
+  for page_id in &doc.sitemap.page_order {
+    if !page_ids.contains(page_id.as_str()) {
+      issues.push(ProjectIssue::new(
+        IssueSeverity::Error,
+        "unknown-sitemap-page",
+        format!("Sitemap lists missing page {}.", page_id),
+      ));
+    }
+  }
+  issues
+}
+
+/// Content-quality rules: SEO metadata, alt text and link hygiene. All issues are warnings.
+pub(crate) fn lint_builder_doc(doc: &BuilderProjectDoc) -> Vec<ProjectIssue> {
+  let mut issues = Vec::new();
+  for page in &doc.pages {
+    let seo_title = page.seo.title.trim();
+    if seo_title.is_empty() {
+      issues.push(warning("seo-title-missing", "SEO title is empty.".to_string()).on_page(&page.id));
+    } else if seo_title.chars().count() > SEO_TITLE_MAX {
+      issues.push(
+        warning(
+          "seo-title-length",
+          format!("SEO title is longer than {} characters.", SEO_TITLE_MAX),
+        )
+        .on_page(&page.id),
+      );
+    }
+    let seo_description = page.seo.description.trim();
+    if seo_description.is_empty() {
+      issues.push(
+        warning("seo-description-missing", "SEO description is empty.".to_string()).on_page(&page.id),
+      );
+    } else if seo_description.chars().count() > SEO_DESCRIPTION_MAX {
+      issues.push(
+        warning(
+          "seo-description-length",
+          format!("SEO description is longer than {} characters.", SEO_DESCRIPTION_MAX),
+        )
+        .on_page(&page.id),
+      );
+    }
+
+    for block in &page.blocks {
+      let Some(props) = block.props.as_object() else {
+        continue;
+      };
+      let mut keys: Vec<&String> = props.keys().collect();
+      keys.sort();
+      for key in keys {
+        let value = props[key].as_str().unwrap_or("").trim();
+        let lower = key.to_ascii_lowercase();
+        if lower.ends_with("alt") && value.is_empty() {
+          issues.push(
+            warning("missing-alt-text", format!("{} is empty.", key))
+              .on_page(&page.id)
+              .on_block(&block.id),
+          );
+        } else if is_link_key(&lower) && !value.is_empty() {
+          if let Some(problem) = link_problem(value) {
+            issues.push(
+              warning("link-hygiene", format!("{} {}", key, problem))
+                .on_page(&page.id)
+                .on_block(&block.id),
+            );
+          }
+        }
+      }
+    }
+  }
+  issues
+}
+
+fn warning(code: &'static str, message: String) -> ProjectIssue {
+  ProjectIssue::new(IssueSeverity::Warning, code, message)
+}
+
+fn is_link_key(lower_key: &str) -> bool {
+  lower_key.ends_with("href") || lower_key.ends_with("url") || lower_key.ends_with("link")
+}
+
+fn link_problem(value: &str) -> Option<&'static str> {
+  const ALLOWED_PREFIXES: &[&str] = &["/", "#", "https://", "mailto:", "tel:"];
+  if value.starts_with("http://") {
+    return Some("uses insecure http://.");
+  }
+  if value.contains(char::is_whitespace) {
+    return Some("contains whitespace.");
+  }
+  if !ALLOWED_PREFIXES.iter().any(|prefix| value.starts_with(prefix)) {
+    return Some("is not an absolute https, mailto, tel, anchor or site-relative link.");
+  }
+  None
+}
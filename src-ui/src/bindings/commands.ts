@@ -0,0 +1,173 @@
+// Generated by `manifold bindings` from the Rust types in src-tauri. Do not edit.
+
+import {
+  remoteDownload,
+  remotePost,
+  remoteResource,
+  remoteUpload,
+  type RemoteResourceOptions,
+} from "../features/remote/client";
+import { invokeCommand } from "../lib/appError";
+import type {
+  ActivityEntry,
+  AgentAccessMode,
+  AgentOperation,
+  AgentResponse,
+  AgentRunInfo,
+  AgentRunRequest,
+  AgentSessionInput,
+  AgentSessionPolicy,
+  AgentSessionStartInput,
+  AgentSessionUpdateInput,
+  ArchiveExport,
+  BuilderProjectDoc,
+  CollabChanges,
+  CollabChangesInput,
+  CollabClientInput,
+  CollabJoinInput,
+  CollabPresenceInput,
+  CollabSnapshot,
+  CollabSubmitInput,
+  CreateProjectInput,
+  DeleteProjectInput,
+  DeletionRequest,
+  DocOperation,
+  DuplicateProjectInput,
+  JsonPatch,
+  JsonValue,
+  ListShelvedInput,
+  OpenPageInput,
+  PageDoc,
+  PagePresence,
+  Participant,
+  PatchProposal,
+  PolicyDenial,
+  ProjectActivityInput,
+  ProjectDetailsPatch,
+  ProjectFilesReport,
+  ProjectPage,
+  ProjectPathInput,
+  ProjectQuery,
+  ProjectRecord,
+  ProjectResource,
+  ProjectShelf,
+  ProjectTemplate,
+  ProposalOutcome,
+  ProposalResolveInput,
+  ProposalRevertInput,
+  ProposalSubmitInput,
+  QueryProjectsInput,
+  RemoteContextDoc,
+  RemoteOk,
+  RemoteServerStatus,
+  RenameProjectInput,
+  SaveBuilderProjectInput,
+  SaveProjectOutcome,
+  ShelvedProject,
+  SiteDoc,
+  SitePatch,
+  StoredAsset,
+  SubmitOutcome,
+  UpdateProjectDetailsInput,
+  UpdateSiteUrlInput,
+  WorkspaceRootInput,
+} from "./types";
+
+export const commands = {
+  listProjects: (args: { workspaceRoot: string }) => invokeCommand<Array<ProjectRecord>>("list_projects", args),
+  queryProjects: (args: { workspaceRoot: string; query?: ProjectQuery | null }) => invokeCommand<ProjectPage>("query_projects", args),
+  createProject: (args: { workspaceRoot: string; name: string; slug: string; siteUrl: string; template?: string | null; clientName?: string | null }) => invokeCommand<ProjectRecord>("create_project", args),
+  listProjectTemplates: (args: { workspaceRoot: string }) => invokeCommand<Array<ProjectTemplate>>("list_project_templates", args),
+  duplicateProject: (args: { projectPath: string; newName: string; newSlug: string }) => invokeCommand<ProjectRecord>("duplicate_project", args),
+  renameProject: (args: { projectPath: string; newName: string; newSlug: string }) => invokeCommand<ProjectRecord>("rename_project", args),
+  archiveProject: (args: { projectPath: string }) => invokeCommand<ShelvedProject>("archive_project", args),
+  trashProject: (args: { projectPath: string }) => invokeCommand<ShelvedProject>("trash_project", args),
+  restoreProject: (args: { projectPath: string }) => invokeCommand<ProjectRecord>("restore_project", args),
+  listShelvedProjects: (args: { workspaceRoot: string; shelf?: ProjectShelf | null }) => invokeCommand<Array<ShelvedProject>>("list_shelved_projects", args),
+  requestProjectDeletion: (args: { projectPath: string }) => invokeCommand<DeletionRequest>("request_project_deletion", args),
+  deleteProject: (args: { projectPath: string; confirmationToken: string }) => invokeCommand<void>("delete_project", args),
+  updateProjectSiteUrl: (args: { projectPath: string; siteUrl: string }) => invokeCommand<ProjectRecord>("update_project_site_url", args),
+  updateProjectDetails: (args: { projectPath: string; details: ProjectDetailsPatch }) => invokeCommand<ProjectRecord>("update_project_details", args),
+  getProjectActivity: (args: { projectPath: string; limit?: number | null }) => invokeCommand<Array<ActivityEntry>>("get_project_activity", args),
+  pickWorkspaceDirectory: () => invokeCommand<string | null>("pick_workspace_directory"),
+  loadBuilderProject: (args: { projectPath: string }) => invokeCommand<BuilderProjectDoc>("load_builder_project", args),
+  getProjectSchemas: () => invokeCommand<{ [key in string]?: JsonValue }>("get_project_schemas"),
+  validateProjectFiles: (args: { projectPath: string }) => invokeCommand<ProjectFilesReport>("validate_project_files", args),
+  exportProjectArchive: (args: { projectPath: string; destination?: string | null }) => invokeCommand<ArchiveExport>("export_project_archive", args),
+  importProjectArchive: (args: { workspaceRoot: string; archivePath: string }) => invokeCommand<ProjectRecord>("import_project_archive", args),
+  saveBuilderProject: (args: { projectPath: string; document: BuilderProjectDoc; clientId?: string | null }) => invokeCommand<void>("save_builder_project", args),
+  runAgentCommand: (args: { request: JsonValue }) => invokeCommand<AgentResponse>("run_agent_command", args),
+  startAgentSession: (args: { projectPath: string; mode: AgentAccessMode; operations: Array<AgentOperation> }) => invokeCommand<AgentSessionPolicy>("start_agent_session", args),
+  updateAgentSession: (args: { sessionId: string; mode?: AgentAccessMode | null; operations?: Array<AgentOperation> | null }) => invokeCommand<AgentSessionPolicy>("update_agent_session", args),
+  getAgentSession: (args: { sessionId: string }) => invokeCommand<AgentSessionPolicy>("get_agent_session", args),
+  endAgentSession: (args: { sessionId: string }) => invokeCommand<void>("end_agent_session", args),
+  listAgentDenials: (args: { projectPath?: string | null }) => invokeCommand<Array<PolicyDenial>>("list_agent_denials", args),
+  startAgentRun: (args: { projectPath: string; request: AgentRunRequest }) => invokeCommand<AgentRunInfo>("start_agent_run", args),
+  cancelAgentRun: (args: { runId: string }) => invokeCommand<void>("cancel_agent_run", args),
+  listAgentRuns: (args: { projectPath: string }) => invokeCommand<Array<AgentRunInfo>>("list_agent_runs", args),
+  openBuilderPage: (args: { projectPath: string; pageId: string; lock: boolean; clientId?: string | null; displayName?: string | null }) => invokeCommand<Array<PagePresence>>("open_builder_page", args),
+  closeBuilderPage: (args: { projectPath: string; clientId?: string | null }) => invokeCommand<void>("close_builder_page", args),
+  listPagePresence: (args: { projectPath: string }) => invokeCommand<Array<PagePresence>>("list_page_presence", args),
+  collabJoin: (args: { projectPath: string; clientId: string; displayName: string }) => invokeCommand<CollabSnapshot>("collab_join", args),
+  collabSubmit: (args: { projectPath: string; clientId: string; operations: Array<DocOperation> }) => invokeCommand<SubmitOutcome>("collab_submit", args),
+  collabChanges: (args: { projectPath: string; clientId: string; since: number }) => invokeCommand<CollabChanges>("collab_changes", args),
+  collabUpdatePresence: (args: { projectPath: string; clientId: string; pageId?: string | null; blockId?: string | null }) => invokeCommand<Array<Participant>>("collab_update_presence", args),
+  collabLeave: (args: { projectPath: string; clientId: string }) => invokeCommand<void>("collab_leave", args),
+  submitPatchProposal: (args: { projectPath: string; patch: JsonPatch; summary?: string | null; clientId?: string | null }) => invokeCommand<PatchProposal>("submit_patch_proposal", args),
+  listPatchProposals: (args: { projectPath: string }) => invokeCommand<Array<PatchProposal>>("list_patch_proposals", args),
+  approvePatchProposal: (args: { projectPath: string; proposalId: string }) => invokeCommand<ProposalOutcome>("approve_patch_proposal", args),
+  rejectPatchProposal: (args: { projectPath: string; proposalId: string; reason?: string | null }) => invokeCommand<PatchProposal>("reject_patch_proposal", args),
+  revertLastChange: (args: { projectPath: string }) => invokeCommand<ProposalOutcome>("revert_last_change", args),
+  startRemoteServer: (args: { host: string; port: number; token: string; workspaceRoot: string }) => invokeCommand<RemoteServerStatus>("start_remote_server", args),
+  stopRemoteServer: () => invokeCommand<RemoteServerStatus>("stop_remote_server"),
+  getRemoteServerStatus: () => invokeCommand<RemoteServerStatus>("get_remote_server_status"),
+};
+
+export const remote = {
+  remoteContext: () => remotePost<RemoteContextDoc>("/api/v1/remote-context", {}),
+  listProjects: (input: WorkspaceRootInput) => remotePost<Array<ProjectRecord>>("/api/v1/list-projects", input),
+  queryProjects: (input: QueryProjectsInput) => remotePost<ProjectPage>("/api/v1/query-projects", input),
+  createProject: (input: CreateProjectInput) => remotePost<ProjectRecord>("/api/v1/create-project", input),
+  listProjectTemplates: (input: WorkspaceRootInput) => remotePost<Array<ProjectTemplate>>("/api/v1/list-project-templates", input),
+  duplicateProject: (input: DuplicateProjectInput) => remotePost<ProjectRecord>("/api/v1/duplicate-project", input),
+  renameProject: (input: RenameProjectInput) => remotePost<ProjectRecord>("/api/v1/rename-project", input),
+  archiveProject: (input: ProjectPathInput) => remotePost<ShelvedProject>("/api/v1/archive-project", input),
+  trashProject: (input: ProjectPathInput) => remotePost<ShelvedProject>("/api/v1/trash-project", input),
+  restoreProject: (input: ProjectPathInput) => remotePost<ProjectRecord>("/api/v1/restore-project", input),
+  listShelvedProjects: (input: ListShelvedInput) => remotePost<Array<ShelvedProject>>("/api/v1/list-shelved-projects", input),
+  requestProjectDeletion: (input: ProjectPathInput) => remotePost<DeletionRequest>("/api/v1/request-project-deletion", input),
+  deleteProject: (input: DeleteProjectInput) => remotePost<RemoteOk>("/api/v1/delete-project", input),
+  updateProjectSiteUrl: (input: UpdateSiteUrlInput) => remotePost<ProjectRecord>("/api/v1/update-project-site-url", input),
+  updateProjectDetails: (input: UpdateProjectDetailsInput) => remotePost<ProjectRecord>("/api/v1/update-project-details", input),
+  projectActivity: (input: ProjectActivityInput) => remotePost<Array<ActivityEntry>>("/api/v1/project-activity", input),
+  loadBuilderProject: (input: ProjectPathInput) => remotePost<BuilderProjectDoc>("/api/v1/load-builder-project", input),
+  saveBuilderProject: (input: SaveBuilderProjectInput) => remotePost<SaveProjectOutcome>("/api/v1/save-builder-project", input),
+  projectSchemas: () => remotePost<{ [key in string]?: JsonValue }>("/api/v1/project-schemas", {}),
+  validateProjectFiles: (input: ProjectPathInput) => remotePost<ProjectFilesReport>("/api/v1/validate-project-files", input),
+  agent: (input: JsonValue) => remotePost<AgentResponse>("/api/v1/agent", input),
+  agentSessionsStart: (input: AgentSessionStartInput) => remotePost<AgentSessionPolicy>("/api/v1/agent/sessions/start", input),
+  agentSessionsUpdate: (input: AgentSessionUpdateInput) => remotePost<AgentSessionPolicy>("/api/v1/agent/sessions/update", input),
+  agentSessionsEnd: (input: AgentSessionInput) => remotePost<RemoteOk>("/api/v1/agent/sessions/end", input),
+  agentDenials: (input: ProjectPathInput) => remotePost<Array<PolicyDenial>>("/api/v1/agent/denials", input),
+  pagesOpen: (input: OpenPageInput) => remotePost<Array<PagePresence>>("/api/v1/pages/open", input),
+  pagesClose: (input: CollabClientInput) => remotePost<RemoteOk>("/api/v1/pages/close", input),
+  pagesPresence: (input: ProjectPathInput) => remotePost<Array<PagePresence>>("/api/v1/pages/presence", input),
+  collabJoin: (input: CollabJoinInput) => remotePost<CollabSnapshot>("/api/v1/collab/join", input),
+  collabSubmit: (input: CollabSubmitInput) => remotePost<SubmitOutcome>("/api/v1/collab/submit", input),
+  collabChanges: (input: CollabChangesInput) => remotePost<CollabChanges>("/api/v1/collab/changes", input),
+  collabPresence: (input: CollabPresenceInput) => remotePost<Array<Participant>>("/api/v1/collab/presence", input),
+  collabLeave: (input: CollabClientInput) => remotePost<RemoteOk>("/api/v1/collab/leave", input),
+  proposalsSubmit: (input: ProposalSubmitInput) => remotePost<PatchProposal>("/api/v1/proposals/submit", input),
+  proposalsList: (input: ProjectPathInput) => remotePost<Array<PatchProposal>>("/api/v1/proposals/list", input),
+  proposalsApprove: (input: ProposalResolveInput) => remotePost<ProposalOutcome>("/api/v1/proposals/approve", input),
+  proposalsReject: (input: ProposalResolveInput) => remotePost<PatchProposal>("/api/v1/proposals/reject", input),
+  proposalsRevert: (input: ProposalRevertInput) => remotePost<ProposalOutcome>("/api/v1/proposals/revert", input),
+  getProjects: (options?: RemoteResourceOptions) => remoteResource<Array<ProjectRecord>>("GET", `/api/v1/projects`, undefined, options),
+  getProject: (params: { slug: string }, options?: RemoteResourceOptions) => remoteResource<ProjectResource>("GET", `/api/v1/projects/${encodeURIComponent(params.slug)}`, undefined, options),
+  getSite: (params: { slug: string }, options?: RemoteResourceOptions) => remoteResource<SiteDoc>("GET", `/api/v1/projects/${encodeURIComponent(params.slug)}/site`, undefined, options),
+  patchSite: (params: { slug: string }, body: SitePatch, options?: RemoteResourceOptions) => remoteResource<SiteDoc>("PATCH", `/api/v1/projects/${encodeURIComponent(params.slug)}/site`, body, options),
+  getPage: (params: { slug: string; pageId: string }, options?: RemoteResourceOptions) => remoteResource<PageDoc>("GET", `/api/v1/projects/${encodeURIComponent(params.slug)}/pages/${encodeURIComponent(params.pageId)}`, undefined, options),
+  putPage: (params: { slug: string; pageId: string }, body: PageDoc, options?: RemoteResourceOptions) => remoteResource<PageDoc>("PUT", `/api/v1/projects/${encodeURIComponent(params.slug)}/pages/${encodeURIComponent(params.pageId)}`, body, options),
+  downloadProjectArchive: (params: { slug: string }) => remoteDownload(`/api/v1/projects/${encodeURIComponent(params.slug)}/archive`),
+  uploadAsset: (params: { slug: string }, file: Blob, fileName?: string) => remoteUpload<StoredAsset>(`/api/v1/projects/${encodeURIComponent(params.slug)}/assets`, file, fileName),
+};
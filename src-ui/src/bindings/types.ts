@@ -0,0 +1,404 @@
+// Generated by `manifold bindings` from the Rust types in src-tauri. Do not edit.
+
+export type ActivityEntry = { 
+/**
+ * Matches the `updatedAt` the change wrote to `project.json`.
+ */
+at: string, operation: ActivityOperation, 
+/**
+ * Pages added, updated or removed; empty for project-level changes.
+ */
+pages: Array<string>, source: ChangeSource, };
+
+export type ActivityOperation = "save" | "collabEdit" | "applyProposal" | "revertProposal" | "updateSiteUrl" | "updateDetails" | "updateTheme" | "rename" | "duplicate" | "addAsset" | "archive" | "trash" | "restore";
+
+/**
+ * Sessions without explicit write mode can read everything in the project.
+ */
+export type AgentAccessMode = "readOnly" | "write";
+
+export type AgentError = { code: string, message: string, details?: JsonValue, };
+
+/**
+ * Operation families the user switches on per session.
+ */
+export type AgentOperation = "editContent" | "editAssets" | "runTools";
+
+export type AgentResponse = { version: number, id: JsonValue, ok: boolean, result?: JsonValue, error?: AgentError, };
+
+export type AgentRunInfo = { runId: string, projectPath: string, command: string, args: Array<string>, status: AgentRunStatus, startedAt: string, finishedAt?: string, exitCode?: number, transcriptPath: string, };
+
+export type AgentRunRequest = { 
+/**
+ * Allow-listed agent CLI to launch, e.g. `codex`. Paths are not accepted.
+ */
+command: string, args?: Array<string>, timeoutSecs?: number, 
+/**
+ * Extra environment variable names to inherit, such as an API key.
+ */
+passEnv?: Array<string>, };
+
+export type AgentRunStatus = "running" | "succeeded" | "failed" | "cancelled" | "timedOut";
+
+export type AgentSessionInput = { sessionId: string, };
+
+export type AgentSessionPolicy = { sessionId: string, projectPath: string, mode: AgentAccessMode, operations: Array<AgentOperation>, createdAt: string, updatedAt: string, };
+
+export type AgentSessionStartInput = { projectPath: string, mode: AgentAccessMode, operations?: Array<AgentOperation>, };
+
+export type AgentSessionUpdateInput = { sessionId: string, mode?: AgentAccessMode, operations?: Array<AgentOperation>, };
+
+/**
+ * Error returned by every command and remote route. `message` is safe to show to users.
+ */
+export type AppError = { code: ErrorCode, message: string, details?: ErrorDetails, };
+
+export type AppliedOperation = { version: number, clientId: string, operation: DocOperation, };
+
+export type ArchiveEntry = { 
+/**
+ * Path inside the archive and the project, with `/` separators.
+ */
+path: string, size: number, sha256: string, };
+
+export type ArchiveExport = { 
+/**
+ * Where the archive was written.
+ */
+path: string, size: number, manifest: ArchiveManifest, };
+
+export type ArchiveManifest = { 
+/**
+ * Always `manifold-archive`.
+ */
+format: string, version: number, 
+/**
+ * Studio build that wrote the archive.
+ */
+appVersion: string, exportedAt: string, name: string, slug: string, 
+/**
+ * Every file in the archive except the manifest, sorted by path.
+ */
+files: Array<ArchiveEntry>, };
+
+export type BlockDoc = { id: string, 
+/**
+ * Catalog block type, e.g. `hero`.
+ */
+type: string, props: JsonValue, 
+/**
+ * `visible` or `hidden`.
+ */
+visibility: string, styleOverrides: BlockStyleDoc, };
+
+export type BlockStyleDoc = { variant: string, marginTop?: string | null, marginRight?: string | null, marginBottom?: string | null, marginLeft?: string | null, paddingTop?: string | null, paddingRight?: string | null, paddingBottom?: string | null, paddingLeft?: string | null, borderWidth?: string | null, borderStyle?: string | null, borderColor?: string | null, borderRadius?: string | null, backgroundColor?: string | null, backgroundImage?: string | null, textColor?: string | null, fontSize?: string | null, translateX?: string | null, translateY?: string | null, primitiveStyles?: { [key in string]?: { [key in string]?: string } } | null, viewportStyles?: { [key in string]?: { [key in string]?: string } } | null, primitiveViewportStyles?: { [key in string]?: { [key in string]?: { [key in string]?: string } } } | null, stateViewportStyles?: { [key in string]?: { [key in string]?: { [key in string]?: string } } } | null, primitiveStateViewportStyles?: { [key in string]?: { [key in string]?: { [key in string]?: { [key in string]?: string } } } } | null, };
+
+export type BuilderProjectDoc = { site: SiteDoc, sitemap: SitemapDoc, pages: Array<PageDoc>, selectedPageId: string, };
+
+export type ChangeSource = "desktop" | "remote" | "agent" | "cli";
+
+export type CollabChanges = { version: number, operations: Array<AppliedOperation>, 
+/**
+ * Set when the requested version fell out of the operation log; clients replace their copy.
+ */
+document: BuilderProjectDoc | null, participants: Array<Participant>, };
+
+export type CollabChangesInput = { projectPath: string, clientId: string, since: number, };
+
+export type CollabClientInput = { projectPath: string, clientId: string, };
+
+export type CollabJoinInput = { projectPath: string, clientId: string, displayName?: string, };
+
+export type CollabPresenceInput = { projectPath: string, clientId: string, pageId: string | null, blockId: string | null, };
+
+export type CollabSnapshot = { projectPath: string, version: number, document: BuilderProjectDoc, participants: Array<Participant>, };
+
+export type CollabSubmitInput = { projectPath: string, clientId: string, operations: Array<DocOperation>, };
+
+export type CreateProjectInput = { workspaceRoot: string, name: string, slug: string, siteUrl: string, 
+/**
+ * Template id from `/list-project-templates`; a single empty Home page when omitted.
+ */
+template?: string, 
+/**
+ * Saved as the project's client and fills `{{clientName}}` in the template, where it
+ * defaults to the project name.
+ */
+clientName?: string, };
+
+export type DeleteProjectInput = { projectPath: string, 
+/**
+ * From `/request-project-deletion`.
+ */
+confirmationToken: string, };
+
+export type DeletionRequest = { project: ProjectRecord, 
+/**
+ * Pass to `delete_project`. It changes whenever the project does, so a confirmation given
+ * for an older state is refused.
+ */
+confirmationToken: string, 
+/**
+ * Everything under the project directory, exports and transcripts included.
+ */
+files: number, bytes: number, };
+
+/**
+ * One human-readable change between two documents, located by JSON pointer into the
+ * document it refers to (the proposed one, or the current one for removals).
+ */
+export type DocChange = { path: string, summary: string, };
+
+/**
+ * Edits exchanged by co-editing clients. Targets are addressed by page/block id rather than
+ * index, so operations built against an older version still land on the intended block.
+ */
+export type DocOperation = { "op": "setBlockProp", pageId: string, blockId: string, key: string, value: JsonValue, } | { "op": "removeBlockProp", pageId: string, blockId: string, key: string, } | { "op": "setBlockStyle", pageId: string, blockId: string, field: string, value: JsonValue, } | { "op": "setBlockVisibility", pageId: string, blockId: string, visibility: string, } | { "op": "insertBlock", pageId: string, afterBlockId: string | null, block: BlockDoc, } | { "op": "removeBlock", pageId: string, blockId: string, } | { "op": "moveBlock", pageId: string, blockId: string, afterBlockId: string | null, } | { "op": "setPageTitle", pageId: string, title: string, } | { "op": "setPageSeo", pageId: string, title: string | null, description: string | null, };
+
+export type DuplicateProjectInput = { projectPath: string, newName: string, newSlug: string, };
+
+/**
+ * Stable, machine-readable error codes shared by Tauri commands, the remote API and the CLI.
+ * Renaming a variant is a breaking change for clients.
+ */
+export type ErrorCode = "invalid_input" | "not_found" | "conflict" | "precondition_failed" | "payload_too_large" | "unauthorized" | "permission_denied" | "corrupt_data" | "service_unavailable" | "io" | "internal";
+
+export type ErrorDetails = { 
+/**
+ * File or directory the error is about.
+ */
+path?: string, 
+/**
+ * Input field (camelCase, as sent by the client) the error is about.
+ */
+field?: string, };
+
+export type FileValidation = { 
+/**
+ * Path relative to the project, with `/` separators.
+ */
+file: string, schema: string, violations: Array<SchemaViolation>, };
+
+export type HealthDoc = { status: string, 
+/**
+ * Server build, e.g. `0.1.0`.
+ */
+version: string, 
+/**
+ * Supported API versions, mounted at `/api/<version>`.
+ */
+apiVersions: Array<string>, features: Array<string>, };
+
+export type JsonValue = number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null;
+
+export type ListShelvedInput = { workspaceRoot: string, 
+/**
+ * Both shelves when omitted.
+ */
+shelf?: ProjectShelf, };
+
+export type OpenPageInput = { projectPath: string, clientId: string, displayName?: string, pageId: string, lock?: boolean, };
+
+export type OutputStream = "stdout" | "stderr";
+
+export type PageDoc = { 
+/**
+ * Also the file name under `pages/`.
+ */
+id: string, title: string, route: string, seo: PageSeoDoc, blocks: Array<BlockDoc>, };
+
+export type PagePresence = { clientId: string, displayName: string, pageId: string, locked: boolean, since: string, };
+
+export type PageSeoDoc = { title: string, description: string, };
+
+export type PageSummary = { id: string, title: string, route: string, };
+
+export type Participant = { clientId: string, displayName: string, pageId: string | null, blockId: string | null, };
+
+export type PatchProposal = { id: string, projectPath: string, summary: string, author: string, source: ChangeSource, status: ProposalStatus, baseRevision: number, patch: JsonPatch, changes: Array<DocChange>, createdAt: string, resolvedAt?: string, resolvedBy?: string, reason?: string, };
+
+export type PolicyDenial = { code: string, message: string, projectPath: string, sessionId?: string, method: string, path?: string, at: string, };
+
+export type ProjectActivityInput = { projectPath: string, 
+/**
+ * Newest entries to return; 100 when omitted.
+ */
+limit?: number, };
+
+/**
+ * Launcher color label.
+ */
+export type ProjectColor = "none" | "red" | "orange" | "yellow" | "green" | "blue" | "purple" | "gray";
+
+/**
+ * Launcher details to change; omitted fields keep their value.
+ */
+export type ProjectDetailsPatch = { 
+/**
+ * Replaces every tag; an empty list clears them.
+ */
+tags?: Array<string>, 
+/**
+ * An empty name removes the project from its client.
+ */
+clientName?: string, pinned?: boolean, color?: ProjectColor, };
+
+export type ProjectEvent = { "type": "projectSaved", projectPath: string, revision: number, source: ChangeSource, } | { "type": "pagesChanged", projectPath: string, revision: number, source: ChangeSource, added: Array<string>, updated: Array<string>, removed: Array<string>, } | { "type": "assetAdded", projectPath: string, asset: StoredAsset, source: ChangeSource, } | { "type": "projectRenamed", projectPath: string, project: ProjectRecord, source: ChangeSource, } | { "type": "projectDuplicated", projectPath: string, project: ProjectRecord, source: ChangeSource, } | { "type": "projectShelved", projectPath: string, project: ProjectRecord, shelf: ProjectShelf, source: ChangeSource, } | { "type": "projectRestored", projectPath: string, project: ProjectRecord, source: ChangeSource, } | { "type": "projectDeleted", projectPath: string, source: ChangeSource, } | { "type": "serverStatusChanged", status: RemoteServerStatus, } | { "type": "collabOperations", projectPath: string, version: number, operations: Array<AppliedOperation>, } | { "type": "collabPresence", projectPath: string, participants: Array<Participant>, } | { "type": "pagePresence", projectPath: string, presence: Array<PagePresence>, } | { "type": "proposalChanged", projectPath: string, proposalId: string, status: ProposalStatus, } | { "type": "agentRunChanged", run: AgentRunInfo, } | { "type": "agentRunOutput", projectPath: string, runId: string, stream: OutputStream, line: string, };
+
+export type ProjectFilesReport = { valid: boolean, files: Array<FileValidation>, };
+
+export type ProjectPage = { projects: Array<ProjectRecord>, 
+/**
+ * Matching projects before `offset` and `limit` were applied.
+ */
+total: number, offset: number, };
+
+export type ProjectPathInput = { projectPath: string, };
+
+/**
+ * Filters and paging for `query_projects`. Every field is optional; the default lists every
+ * project, most recently updated first.
+ */
+export type ProjectQuery = { 
+/**
+ * Case-insensitive substring of the name, slug, site URL or client name.
+ */
+search?: string, 
+/**
+ * Only projects carrying every one of these tags, compared case-insensitively.
+ */
+tags?: Array<string>, 
+/**
+ * Only this client's projects, compared case-insensitively.
+ */
+clientName?: string, 
+/**
+ * Only pinned projects when true, only unpinned ones when false.
+ */
+pinned?: boolean, sort?: ProjectSort, 
+/**
+ * Reverses the default order, which is A–Z for names and clients and newest first for dates.
+ */
+reverse?: boolean, limit?: number, offset?: number, };
+
+export type ProjectRecord = { id: string, name: string, 
+/**
+ * The project directory is `<slug>.manifold`; REST routes address projects by it.
+ */
+slug: string, path: string, createdAt: string, updatedAt: string, siteUrl: string, tags: Array<string>, 
+/**
+ * Empty when the project is not filed under a client.
+ */
+clientName: string, pinned: boolean, color: ProjectColor, };
+
+export type ProjectResource = { project: ProjectRecord, site: SiteDoc, sitemap: SitemapDoc, 
+/**
+ * In sitemap order; fetch each one from `pages/{pageId}`.
+ */
+pages: Array<PageSummary>, };
+
+export type ProjectShelf = "archive" | "trash";
+
+export type ProjectSort = "name" | "created" | "updated" | "client";
+
+export type ProjectTemplate = { 
+/**
+ * Passed as `template` to `create_project`.
+ */
+id: string, name: string, description: string, source: TemplateSource, 
+/**
+ * Page titles in sitemap order, before placeholder substitution.
+ */
+pages: Array<string>, };
+
+export type ProposalOutcome = { proposal: PatchProposal, revision?: number, };
+
+export type ProposalResolveInput = { projectPath: string, proposalId: string, clientId?: string, reason?: string, };
+
+export type ProposalRevertInput = { projectPath: string, clientId?: string, };
+
+export type ProposalStatus = "pending" | "applied" | "rejected" | "reverted" | "stale";
+
+export type ProposalSubmitInput = { projectPath: string, patch: JsonPatch, summary?: string, clientId?: string, };
+
+export type QueryProjectsInput = { workspaceRoot: string, query?: ProjectQuery, };
+
+export type RejectedOperation = { index: number, reason: string, };
+
+export type RemoteContextDoc = { workspaceRoot: string, };
+
+/**
+ * Acknowledgement returned by remote routes that have nothing else to report.
+ */
+export type RemoteOk = { ok: boolean, };
+
+export type RemoteServerStatus = { running: boolean, host: string, port: number, serverUrl: string, };
+
+export type RenameProjectInput = { projectPath: string, newName: string, 
+/**
+ * The directory becomes `<newSlug>.manifold`; pass the current slug to keep it.
+ */
+newSlug: string, };
+
+export type SaveBuilderProjectInput = { projectPath: string, document: BuilderProjectDoc, clientId?: string, };
+
+export type SaveProjectOutcome = { ok: boolean, revision: number, };
+
+export type SchemaViolation = { 
+/**
+ * JSON pointer into the file, `""` for the whole document.
+ */
+pointer: string, message: string, };
+
+export type ShelvedProject = { project: ProjectRecord, shelf: ProjectShelf, shelvedAt: string, };
+
+export type SiteDoc = { siteName: string, baseUrl: string, };
+
+/**
+ * Fields to change; omitted fields keep their value.
+ */
+export type SitePatch = { siteName?: string, baseUrl?: string, };
+
+export type SitemapDoc = { 
+/**
+ * Page ids in navigation order.
+ */
+pageOrder: Array<string>, rootPageId: string, };
+
+export type StoredAsset = { 
+/**
+ * `<sha256 prefix>.<ext>`, e.g. `3f9a0c1d2b4e5f60.png`.
+ */
+fileName: string, 
+/**
+ * Relative to the project, with `/` separators; what blocks reference.
+ */
+path: string, 
+/**
+ * Detected from the file's contents, not the client's declared type.
+ */
+contentType: string, size: number, sha256: string, 
+/**
+ * File name the client sent, if any.
+ */
+originalName: string | null, 
+/**
+ * False when an identical file was already stored; the response then describes that file.
+ */
+created: boolean, };
+
+export type SubmitOutcome = { version: number, applied: Array<AppliedOperation>, rejected: Array<RejectedOperation>, };
+
+export type TemplateSource = "builtin" | "workspace";
+
+export type UpdateProjectDetailsInput = { projectPath: string, details: ProjectDetailsPatch, };
+
+export type UpdateSiteUrlInput = { projectPath: string, siteUrl: string, };
+
+export type WorkspaceRootInput = { workspaceRoot: string, };
+
+export type JsonPatch = Array<
+  | { op: "add" | "replace" | "test"; path: string; value: JsonValue }
+  | { op: "remove"; path: string }
+  | { op: "move" | "copy"; from: string; path: string }
+>;
@@ -1,26 +1,26 @@
-import { core } from "@tauri-apps/api";
+import { commands, remote } from "../../bindings/commands";
+import type { BlockStyleDoc, BuilderProjectDoc as BuilderProjectWireDoc } from "../../bindings/types";
+import { shouldUseRemoteHttpTransport } from "../remote/client";
+import type { BlockInstance, BuilderPage } from "./types";
 
-import { remotePost, shouldUseRemoteHttpTransport } from "../remote/client";
-import type { BuilderPage } from "./types";
-
-export type BuilderProjectDoc = {
-  site: {
-    siteName: string;
-    baseUrl: string;
-  };
-  sitemap: {
-    pageOrder: string[];
-    rootPageId: string;
-  };
+export type BuilderProjectDoc = Omit<BuilderProjectWireDoc, "pages"> & {
   pages: BuilderPage[];
-  selectedPageId: string;
 };
 
+// Every style field the editor can set must exist on the Rust side, or serde drops it on save.
+// A field missing from `BlockStyleDoc` fails to compile here; regenerate with `manifold bindings`.
+type AssertNever<T extends never> = T;
+export type UnpersistedStyleFields = AssertNever<
+  Exclude<keyof BlockInstance["styleOverrides"], keyof BlockStyleDoc>
+>;
+
 export async function loadBuilderProject(projectPath: string): Promise<BuilderProjectDoc> {
-  if (shouldUseRemoteHttpTransport()) {
-    return remotePost<BuilderProjectDoc>("/api/load-builder-project", { projectPath });
-  }
-  return core.invoke<BuilderProjectDoc>("load_builder_project", { projectPath });
+  const doc = shouldUseRemoteHttpTransport()
+    ? await remote.loadBuilderProject({ projectPath })
+    : await commands.loadBuilderProject({ projectPath });
+  // The wire type is wider (plain strings, `null` for unset style fields) but only ever holds
+  // what the editor saved.
+  return doc as BuilderProjectDoc;
 }
 
 export async function saveBuilderProject(input: {
@@ -28,8 +28,8 @@ export async function saveBuilderProject(input: {
   document: BuilderProjectDoc;
 }): Promise<void> {
   if (shouldUseRemoteHttpTransport()) {
-    await remotePost<void>("/api/save-builder-project", input);
+    await remote.saveBuilderProject(input);
     return;
   }
-  await core.invoke("save_builder_project", input);
+  await commands.saveBuilderProject(input);
 }
@@ -1,42 +1,46 @@
-import { core } from "@tauri-apps/api";
+import { commands, remote } from "../../bindings/commands";
+import { shouldUseRemoteHttpTransport } from "../remote/client";
+import type {
+  ActivityEntry,
+  DeletionRequest,
+  ProjectDetailsPatch,
+  ProjectPage,
+  ProjectQuery,
+  ProjectRecord,
+  ProjectShelf,
+  ProjectTemplate,
+  ShelvedProject,
+} from "./types";
 
-import { fetchRemoteContext, remotePost, shouldUseRemoteHttpTransport } from "../remote/client";
-import type { ProjectRecord } from "./types";
-
-type RawProjectRecord = {
-  id: string;
-  name: string;
-  path: string;
-  updatedAt: string;
-  siteUrl: string;
-};
-
-function normalizeProject(raw: RawProjectRecord): ProjectRecord {
-  return {
-    id: raw.id,
-    name: raw.name,
-    path: raw.path,
-    updatedAt: raw.updatedAt,
-    siteUrl: raw.siteUrl,
-  };
+async function resolveRemoteWorkspaceRoot(workspaceRoot: string): Promise<string> {
+  const trimmed = workspaceRoot.trim();
+  if (trimmed) {
+    return trimmed;
+  }
+  const context = await remote.remoteContext();
+  return context.workspaceRoot.trim();
 }
 
 export async function listProjects(workspaceRoot: string): Promise<ProjectRecord[]> {
   if (shouldUseRemoteHttpTransport()) {
-    let resolvedWorkspaceRoot = workspaceRoot.trim();
-    if (!resolvedWorkspaceRoot) {
-      const context = await fetchRemoteContext();
-      resolvedWorkspaceRoot = context.workspaceRoot.trim();
-    }
-    const rows = await remotePost<RawProjectRecord[]>("/api/list-projects", {
-      workspaceRoot: resolvedWorkspaceRoot,
+    return remote.listProjects({
+      workspaceRoot: await resolveRemoteWorkspaceRoot(workspaceRoot),
+    });
+  }
+  return commands.listProjects({ workspaceRoot });
+}
+
+export async function queryProjects(
+  workspaceRoot: string,
+  query?: ProjectQuery
+): Promise<ProjectPage> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.queryProjects({
+      workspaceRoot: await resolveRemoteWorkspaceRoot(workspaceRoot),
+      query,
     });
-    return rows.map(normalizeProject);
   }
-  const rows = await core.invoke<RawProjectRecord[]>("list_projects", {
-    workspaceRoot,
-  });
-  return rows.map(normalizeProject);
+  return commands.queryProjects({ workspaceRoot, query });
 }
 
 export async function createProject(input: {
@@ -44,21 +48,85 @@ export async function createProject(input: {
   name: string;
   slug: string;
   siteUrl: string;
+  template?: string;
+  clientName?: string;
 }): Promise<ProjectRecord> {
   if (shouldUseRemoteHttpTransport()) {
-    let resolvedWorkspaceRoot = input.workspaceRoot.trim();
-    if (!resolvedWorkspaceRoot) {
-      const context = await fetchRemoteContext();
-      resolvedWorkspaceRoot = context.workspaceRoot.trim();
-    }
-    const row = await remotePost<RawProjectRecord>("/api/create-project", {
+    return remote.createProject({
       ...input,
-      workspaceRoot: resolvedWorkspaceRoot,
+      workspaceRoot: await resolveRemoteWorkspaceRoot(input.workspaceRoot),
+    });
+  }
+  return commands.createProject(input);
+}
+
+export async function listProjectTemplates(workspaceRoot: string): Promise<ProjectTemplate[]> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.listProjectTemplates({
+      workspaceRoot: await resolveRemoteWorkspaceRoot(workspaceRoot),
+    });
+  }
+  return commands.listProjectTemplates({ workspaceRoot });
+}
+
+export async function renameProject(input: {
+  projectPath: string;
+  newName: string;
+  newSlug: string;
+}): Promise<ProjectRecord> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.renameProject(input);
+  }
+  return commands.renameProject(input);
+}
+
+export async function archiveProject(projectPath: string): Promise<ShelvedProject> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.archiveProject({ projectPath });
+  }
+  return commands.archiveProject({ projectPath });
+}
+
+export async function trashProject(projectPath: string): Promise<ShelvedProject> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.trashProject({ projectPath });
+  }
+  return commands.trashProject({ projectPath });
+}
+
+export async function restoreProject(projectPath: string): Promise<ProjectRecord> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.restoreProject({ projectPath });
+  }
+  return commands.restoreProject({ projectPath });
+}
+
+export async function listShelvedProjects(
+  workspaceRoot: string,
+  shelf?: ProjectShelf
+): Promise<ShelvedProject[]> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.listShelvedProjects({
+      workspaceRoot: await resolveRemoteWorkspaceRoot(workspaceRoot),
+      shelf,
     });
-    return normalizeProject(row);
   }
-  const row = await core.invoke<RawProjectRecord>("create_project", input);
-  return normalizeProject(row);
+  return commands.listShelvedProjects({ workspaceRoot, shelf });
+}
+
+export async function requestProjectDeletion(projectPath: string): Promise<DeletionRequest> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.requestProjectDeletion({ projectPath });
+  }
+  return commands.requestProjectDeletion({ projectPath });
+}
+
+export async function deleteProject(projectPath: string, confirmationToken: string): Promise<void> {
+  if (shouldUseRemoteHttpTransport()) {
+    await remote.deleteProject({ projectPath, confirmationToken });
+    return;
+  }
+  await commands.deleteProject({ projectPath, confirmationToken });
 }
 
 export async function updateProjectSiteUrl(input: {
@@ -66,9 +134,27 @@ export async function updateProjectSiteUrl(input: {
   siteUrl: string;
 }): Promise<ProjectRecord> {
   if (shouldUseRemoteHttpTransport()) {
-    const row = await remotePost<RawProjectRecord>("/api/update-project-site-url", input);
-    return normalizeProject(row);
+    return remote.updateProjectSiteUrl(input);
+  }
+  return commands.updateProjectSiteUrl(input);
+}
+
+export async function updateProjectDetails(input: {
+  projectPath: string;
+  details: ProjectDetailsPatch;
+}): Promise<ProjectRecord> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.updateProjectDetails(input);
+  }
+  return commands.updateProjectDetails(input);
+}
+
+export async function getProjectActivity(
+  projectPath: string,
+  limit?: number
+): Promise<ActivityEntry[]> {
+  if (shouldUseRemoteHttpTransport()) {
+    return remote.projectActivity({ projectPath, limit });
   }
-  const row = await core.invoke<RawProjectRecord>("update_project_site_url", input);
-  return normalizeProject(row);
+  return commands.getProjectActivity({ projectPath, limit });
 }
@@ -1,6 +1,6 @@
 import { useEffect, useState } from "react";
 
-import type { ProjectSession } from "./types";
+import type { ProjectRecord, ProjectSession } from "./types";
 
 const SESSION_KEY = "manifold.activeProject.v1";
 const SESSION_EVENT = "manifold:project-session";
@@ -30,6 +30,30 @@ export function setActiveProjectSession(session: ProjectSession | null): void {
   window.dispatchEvent(new Event(SESSION_EVENT));
 }
 
+/** Follows a renamed project: per-project storage keyed by its path, and the active session. */
+export function moveProjectSession(previousPath: string, project: ProjectRecord): void {
+  if (previousPath !== project.path) {
+    const suffix = `:${previousPath}`;
+    const keys = Array.from({ length: window.localStorage.length }, (_, index) =>
+      window.localStorage.key(index)
+    );
+    for (const key of keys) {
+      if (!key?.startsWith("manifold.") || !key.endsWith(suffix)) {
+        continue;
+      }
+      const value = window.localStorage.getItem(key);
+      window.localStorage.removeItem(key);
+      if (value !== null) {
+        window.localStorage.setItem(`${key.slice(0, -suffix.length)}:${project.path}`, value);
+      }
+    }
+  }
+  const session = readSession();
+  if (session?.project.path === previousPath) {
+    setActiveProjectSession({ ...session, project });
+  }
+}
+
 export function useActiveProjectSession(): ProjectSession | null {
   const [session, setSession] = useState<ProjectSession | null>(() => readSession());
 
@@ -1,9 +1,31 @@
-export type ProjectRecord = {
-  id: string;
-  name: string;
-  path: string;
-  updatedAt: string;
-  siteUrl: string;
+import type {
+  ActivityEntry,
+  ActivityOperation,
+  DeletionRequest,
+  ProjectColor,
+  ProjectDetailsPatch,
+  ProjectPage,
+  ProjectQuery,
+  ProjectRecord,
+  ProjectShelf,
+  ProjectSort,
+  ProjectTemplate,
+  ShelvedProject,
+} from "../../bindings/types";
+
+export type {
+  ActivityEntry,
+  ActivityOperation,
+  DeletionRequest,
+  ProjectColor,
+  ProjectDetailsPatch,
+  ProjectPage,
+  ProjectQuery,
+  ProjectRecord,
+  ProjectShelf,
+  ProjectSort,
+  ProjectTemplate,
+  ShelvedProject,
 };
 
 export type ProjectSession = {
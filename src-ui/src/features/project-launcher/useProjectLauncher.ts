@@ -1,8 +1,17 @@
 import { useCallback, useEffect, useMemo, useState } from "react";
 
-import { createProject, listProjects, updateProjectSiteUrl } from "./api";
+import {
+  archiveProject,
+  createProject,
+  listProjects,
+  renameProject,
+  trashProject,
+  updateProjectDetails,
+  updateProjectSiteUrl,
+} from "./api";
+import { moveProjectSession } from "./session";
 import { fetchRemoteContext, shouldUseRemoteHttpTransport } from "../remote/client";
-import type { ProjectRecord } from "./types";
+import type { ProjectDetailsPatch, ProjectRecord } from "./types";
 
 const WORKSPACE_ROOT_KEY = "manifold.workspace.root.v1";
 
@@ -19,8 +28,18 @@ export function useProjectLauncher(): {
   errorMessage: string | null;
   setWorkspaceRoot: (next: string) => Promise<void>;
   rescan: () => Promise<void>;
-  createProject: (input: { name: string; slug: string; siteUrl: string }) => Promise<ProjectRecord>;
+  createProject: (input: {
+    name: string;
+    slug: string;
+    siteUrl: string;
+    template?: string;
+    clientName?: string;
+  }) => Promise<ProjectRecord>;
+  renameProject: (projectPath: string, name: string, slug: string) => Promise<ProjectRecord>;
+  archiveProject: (projectPath: string) => Promise<void>;
+  trashProject: (projectPath: string) => Promise<void>;
   saveProjectSiteUrl: (projectPath: string, siteUrl: string) => Promise<void>;
+  saveProjectDetails: (projectPath: string, details: ProjectDetailsPatch) => Promise<void>;
 } {
   const [workspaceRoot, setWorkspaceRootState] = useState<string>(() => readWorkspaceRoot());
   const [projects, setProjects] = useState<ProjectRecord[]>([]);
@@ -108,13 +127,35 @@ export function useProjectLauncher(): {
         name: input.name,
         slug: input.slug,
         siteUrl: input.siteUrl,
+        template: input.template,
+        clientName: input.clientName,
       });
       setProjects((prev) => [created, ...prev]);
       return created;
     },
+    renameProject: async (projectPath, name, slug) => {
+      const renamed = await renameProject({ projectPath, newName: name, newSlug: slug });
+      moveProjectSession(projectPath, renamed);
+      setProjects((prev) => prev.map((item) => (item.path === projectPath ? renamed : item)));
+      return renamed;
+    },
+    archiveProject: async (projectPath) => {
+      const shelved = await archiveProject(projectPath);
+      moveProjectSession(projectPath, shelved.project);
+      setProjects((prev) => prev.filter((item) => item.path !== projectPath));
+    },
+    trashProject: async (projectPath) => {
+      const shelved = await trashProject(projectPath);
+      moveProjectSession(projectPath, shelved.project);
+      setProjects((prev) => prev.filter((item) => item.path !== projectPath));
+    },
     saveProjectSiteUrl: async (projectPath, siteUrl) => {
       const updated = await updateProjectSiteUrl({ projectPath, siteUrl });
       setProjects((prev) => prev.map((item) => (item.path === projectPath ? updated : item)));
     },
+    saveProjectDetails: async (projectPath, details) => {
+      const updated = await updateProjectDetails({ projectPath, details });
+      setProjects((prev) => prev.map((item) => (item.path === projectPath ? updated : item)));
+    },
   };
 }
@@ -1,3 +1,4 @@
+import { toAppError } from "../../lib/appError";
 import { resolveRemoteTransportSettings } from "../app-settings/useAppSettings";
 
 async function parseResponse<T>(response: Response): Promise<T> {
@@ -5,7 +6,13 @@ async function parseResponse<T>(response: Response): Promise<T> {
     return response.json() as Promise<T>;
   }
   const text = await response.text();
-  throw new Error(text || `Remote request failed (${response.status})`);
+  let body: unknown = text || `Remote request failed (${response.status})`;
+  try {
+    body = JSON.parse(text);
+  } catch {
+    // Plain-text bodies come from outside the API, e.g. a proxy or the request extractor.
+  }
+  throw toAppError(body, response.status);
 }
 
 export function shouldUseRemoteHttpTransport(): boolean {
@@ -13,13 +20,17 @@ export function shouldUseRemoteHttpTransport(): boolean {
 }
 
 export async function fetchRemoteContext(): Promise<{ workspaceRoot: string }> {
-  return remotePost<{ workspaceRoot: string }>("/api/remote-context", {});
+  return remotePost<{ workspaceRoot: string }>("/api/v1/remote-context", {});
+}
+
+function remoteUrl(path: string): string {
+  const base = resolveRemoteTransportSettings().serverBaseUrl.replace(/\/$/, "");
+  return `${base}${path.startsWith("/") ? path : `/${path}`}`;
 }
 
 export async function remotePost<T>(path: string, body: unknown): Promise<T> {
   const remote = resolveRemoteTransportSettings();
-  const base = remote.serverBaseUrl.replace(/\/$/, "");
-  const url = `${base}${path.startsWith("/") ? path : `/${path}`}`;
+  const url = remoteUrl(path);
   const response = await fetch(url, {
     method: "POST",
     headers: {
@@ -30,3 +41,72 @@ export async function remotePost<T>(path: string, body: unknown): Promise<T> {
   });
   return parseResponse<T>(response);
 }
+
+export type RemoteResourceOptions = {
+  /** ETag from the last read; the server answers 412 when the resource changed since. */
+  ifMatch?: string;
+  /** Sent so the caller's own page locks do not block the write. */
+  clientId?: string;
+};
+
+export type RemoteResource<T> = {
+  body: T;
+  etag: string | null;
+};
+
+/** Calls a `/api/v1/projects` resource route and returns the body with its ETag. */
+export async function remoteResource<T>(
+  method: string,
+  path: string,
+  body: unknown,
+  options: RemoteResourceOptions = {}
+): Promise<RemoteResource<T>> {
+  const remote = resolveRemoteTransportSettings();
+  const headers: Record<string, string> = { "x-manifold-token": remote.token };
+  if (body !== undefined) {
+    headers["content-type"] = "application/json";
+  }
+  if (options.ifMatch) {
+    headers["if-match"] = options.ifMatch;
+  }
+  if (options.clientId) {
+    headers["x-manifold-client-id"] = options.clientId;
+  }
+  const response = await fetch(remoteUrl(path), {
+    method,
+    headers,
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  const etag = response.headers.get("etag");
+  return { body: await parseResponse<T>(response), etag };
+}
+
+/** Streams `file` to a multipart upload route as its `file` field. */
+export async function remoteUpload<T>(path: string, file: Blob, fileName?: string): Promise<T> {
+  const remote = resolveRemoteTransportSettings();
+  const form = new FormData();
+  if (fileName === undefined) {
+    form.append("file", file);
+  } else {
+    form.append("file", file, fileName);
+  }
+  // No content-type header: fetch sets the multipart boundary itself.
+  const response = await fetch(remoteUrl(path), {
+    method: "POST",
+    headers: { "x-manifold-token": remote.token },
+    body: form,
+  });
+  return parseResponse<T>(response);
+}
+
+/** Fetches a binary route, e.g. a project archive. */
+export async function remoteDownload(path: string): Promise<Blob> {
+  const remote = resolveRemoteTransportSettings();
+  const response = await fetch(remoteUrl(path), {
+    headers: { "x-manifold-token": remote.token },
+  });
+  if (!response.ok) {
+    return parseResponse<Blob>(response);
+  }
+  return response.blob();
+}
@@ -1,18 +1,14 @@
-import { core } from "@tauri-apps/api";
+import { commands } from "../../bindings/commands";
+import type { RemoteServerStatus } from "../../bindings/types";
 import { isTauriRuntime } from "../app-settings/useAppSettings";
 
-export type RemoteServerStatus = {
-  running: boolean;
-  host: string;
-  port: number;
-  serverUrl: string;
-};
+export type { RemoteServerStatus };
 
 export async function getRemoteServerStatus(): Promise<RemoteServerStatus> {
   if (!isTauriRuntime()) {
     throw new Error("Remote server controls are available only in desktop Tauri.");
   }
-  return core.invoke<RemoteServerStatus>("get_remote_server_status");
+  return commands.getRemoteServerStatus();
 }
 
 export async function startRemoteServer(input: {
@@ -24,18 +20,12 @@ export async function startRemoteServer(input: {
   if (!isTauriRuntime()) {
     throw new Error("Remote server controls are available only in desktop Tauri.");
   }
-  return core.invoke<RemoteServerStatus>("start_remote_server", {
-    host: input.host,
-    port: input.port,
-    token: input.token,
-    workspaceRoot: input.workspaceRoot,
-    workspace_root: input.workspaceRoot,
-  });
+  return commands.startRemoteServer(input);
 }
 
 export async function stopRemoteServer(): Promise<RemoteServerStatus> {
   if (!isTauriRuntime()) {
     throw new Error("Remote server controls are available only in desktop Tauri.");
   }
-  return core.invoke<RemoteServerStatus>("stop_remote_server");
+  return commands.stopRemoteServer();
 }
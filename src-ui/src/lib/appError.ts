@@ -0,0 +1,45 @@
+import { core } from "@tauri-apps/api";
+import type { AppError as AppErrorBody, ErrorCode, ErrorDetails } from "../bindings/types";
+
+/** Error raised by desktop commands and the remote API; `message` is safe to show as-is. */
+export class AppError extends Error {
+  readonly code: ErrorCode;
+  readonly details: ErrorDetails;
+  readonly status: number | null;
+
+  constructor(body: AppErrorBody, status: number | null = null) {
+    super(body.message);
+    this.name = "AppError";
+    this.code = body.code;
+    this.details = body.details ?? {};
+    this.status = status;
+  }
+}
+
+function isAppErrorBody(value: unknown): value is AppErrorBody {
+  if (!value || typeof value !== "object") {
+    return false;
+  }
+  const candidate = value as Record<string, unknown>;
+  return typeof candidate.code === "string" && typeof candidate.message === "string";
+}
+
+export function toAppError(raw: unknown, status: number | null = null): AppError {
+  if (raw instanceof AppError) {
+    return raw;
+  }
+  if (isAppErrorBody(raw)) {
+    return new AppError(raw, status);
+  }
+  const message =
+    raw instanceof Error ? raw.message : typeof raw === "string" && raw ? raw : "Request failed.";
+  return new AppError({ code: "internal", message }, status);
+}
+
+export async function invokeCommand<T>(command: string, args?: Record<string, unknown>): Promise<T> {
+  try {
+    return await core.invoke<T>(command, args);
+  } catch (error) {
+    throw toAppError(error);
+  }
+}
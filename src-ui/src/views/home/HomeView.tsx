@@ -259,10 +259,7 @@ export function HomeView() {
                   className="primary-btn"
                   onClick={() =>
                     onOpenProject({
-                      id: selectedProject.id,
-                      name: selectedProject.name,
-                      path: selectedProject.path,
-                      updatedAt: selectedProject.updatedAt,
+                      ...selectedProject,
                       siteUrl: selectedProject.siteUrlDraft,
                     })
                   }